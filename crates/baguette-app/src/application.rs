@@ -1,6 +1,6 @@
 use input::
 {
-    baguette_math::Vec2,
+    baguette_math::{Color, Vec2},
     winit::
     {
         application::ApplicationHandler,
@@ -18,13 +18,60 @@ pub(crate) struct AppData
     pub renderer: rendering::RendererData,
 
     /// is the window focused
-    pub focused: bool
+    pub focused: bool,
+
+    /// the window events received since the last update
+    pub events: Vec<AppEvent>,
+    /// set when the window is asked to close, the app will exit after the next update
+    /// unless a state cancels it
+    pub close_requested: bool,
+
+    pub cursor: crate::cursor::CursorData,
+
+    pub audio: audio::AudioPlayer,
+    pub music: audio::MusicPlayer,
+    /// when the audio was last updated, used to advance the fades
+    pub last_audio_update: std::time::Instant
+}
+
+/// window and music events that states can react to,
+/// they are collected between two updates and can be read with [App::events]
+#[derive(Debug, Clone, PartialEq)]
+pub enum AppEvent
+{
+    /// a file was dropped on the window
+    DroppedFile(std::path::PathBuf),
+    /// a file is being dragged over the window
+    HoveredFile(std::path::PathBuf),
+    /// the file being dragged over the window left it or the drag was cancelled
+    HoveredFileCancelled,
+    /// the window was resized, the size is in pixels
+    Resized { width: u32, height: u32 },
+    /// the window gained or lost focus
+    Focused(bool),
+    /// the user asked to close the window,
+    /// the app will exit after this update unless [App::cancel_close] is called
+    CloseRequested,
+    /// a beat of the music was heard, only tracks with a tempo emit them,
+    /// see [audio::Track::with_tempo]
+    Beat(audio::BeatEvent)
 }
 
 pub struct AppHandler
 {
     data: AppData,
     fsm: Fsm,
+    /// where to save the input recording when exiting
+    recording_path: Option<std::path::PathBuf>
+}
+
+/// describes whether the input should be recorded or replayed
+pub enum InputReplay
+{
+    /// records the input and saves it to this path when exiting
+    Record(std::path::PathBuf),
+    /// replaces the live input with this recording
+    Playback(input::InputRecording)
 }
 
 impl AppHandler
@@ -32,14 +79,79 @@ impl AppHandler
     /// creates a new [`AppHandler`]
     ///
     /// panics if called more than once
-    pub fn new(w_attributes: WindowAttributes, color: Option<(f64, f64, f64)>, fsm: Fsm) -> Self
+    pub fn new(w_attributes: WindowAttributes, color: Option<Color>, audio_backend: audio::AudioBackend, fsm: Fsm) -> Self
     {
         setup_logger().unwrap();
 
         Self
         {
-            data: AppData::new(w_attributes, color),
-            fsm
+            data: AppData::new(w_attributes, color, audio_backend),
+            fsm,
+            recording_path: None
+        }
+    }
+
+    /// records or replays the input from the first frame
+    pub fn with_input_replay(mut self, replay: InputReplay) -> Self
+    {
+        match replay
+        {
+            InputReplay::Record(path) =>
+            {
+                self.data.input.start_recording();
+                self.recording_path = Some(path)
+            }
+            InputReplay::Playback(recording) => self.data.input.start_playback(recording)
+        }
+
+        self
+    }
+
+    /// updates the states and draws the frame,
+    /// exits afterwards if the window was asked to close and no state cancelled it
+    fn update(&mut self, target: &ActiveEventLoop)
+    {
+        // begin gathering input before user update
+        self.data.renderer.begin_egui_frame();
+
+        // states can still move the listener during their update
+        self.data.update_listener();
+
+        // before the update, so that the beats reach the states the frame they're heard
+        self.data.update_audio();
+        self.data.collect_beats();
+        
+        self.fsm.update(&mut self.data.to_user_mut());
+
+        self.data.update_cursor(target);
+
+        if let Some(err) = self.data.renderer.render(target).err()
+        {
+            match err
+            {
+                // Reconfigure the surface if it's lost or outdated
+                rendering::SurfaceError::Lost | rendering::SurfaceError::Outdated => log::error!("surface lost or outdated, reconnecting"),
+
+                // The system is out of memory, we should probably quit
+                rendering::SurfaceError::OutOfMemory =>
+                {
+                    log::error!("system out of memory");
+                    target.exit()
+                },
+
+                rendering::SurfaceError::Timeout => log::error!("surface timeout")
+            }
+        }
+        
+        self.data.renderer.post_render();
+        self.data.input.flush_released_keys();
+        self.data.events.clear();
+
+        // no state cancelled the close request,
+        // this directs us to ApplicationHandler::on_exiting, where we do our cleanup
+        if self.data.close_requested
+        {
+            target.exit()
         }
     }
 }
@@ -89,6 +201,7 @@ impl ApplicationHandler for AppHandler
     fn resumed(&mut self, event_loop: &ActiveEventLoop)
     {
         self.data.renderer.resume(event_loop);
+        self.data.audio.resume();
         self.fsm.resume(&mut self.data.to_user_mut())
     }
 
@@ -104,59 +217,70 @@ impl ApplicationHandler for AppHandler
 
         match event
         {
-            WindowEvent::RedrawRequested if self.data.focused =>
-            {
-                // begin gathering input before user update
-                self.data.renderer.begin_egui_frame();
-                
-                self.fsm.update(&mut self.data.to_user_mut());
+            // a close request needs to reach the states even if the window is not focused
+            WindowEvent::RedrawRequested if self.data.focused || self.data.close_requested => self.update(target),
+            
+            // the fades and the positional sounds go on while the states aren't updated,
+            // the beats are skipped since no state would hear them
+            WindowEvent::RedrawRequested => self.data.update_audio(),
 
-                if let Some(err) = self.data.renderer.render(target).err()
-                {
-                    match err
-                    {
-                        // Reconfigure the surface if it's lost or outdated
-                        rendering::SurfaceError::Lost | rendering::SurfaceError::Outdated => log::error!("surface lost or outdated, reconnecting"),
-
-                        // The system is out of memory, we should probably quit
-                        rendering::SurfaceError::OutOfMemory =>
-                        {
-                            log::error!("system out of memory");
-                            target.exit()
-                        },
-
-                        rendering::SurfaceError::Timeout => log::error!("surface timeout")
-                    }
-                }
-                
-                self.data.renderer.post_render();
-                self.data.input.flush_released_keys()
+            // answered by the next redraw, or in ApplicationHandler::about_to_wait
+            // if the window is hidden and doesn't get one
+            WindowEvent::CloseRequested =>
+            {
+                self.data.close_requested = true;
+                self.data.events.push(AppEvent::CloseRequested)
             }
             
-            // this directs us to ApplicationHandler::on_exiting, where we do our cleanup
-            WindowEvent::CloseRequested => target.exit(),
-            
             WindowEvent::Resized(new_size) if new_size.width > 0 && new_size.height > 0 =>
             {
-                self.data.renderer.resize(new_size.into())
+                self.data.renderer.resize(new_size.into());
+                self.data.events.push(AppEvent::Resized { width: new_size.width, height: new_size.height })
+            }
+            WindowEvent::Focused(value) =>
+            {
+                self.data.focused = value;
+                self.data.events.push(AppEvent::Focused(value))
             }
-            WindowEvent::Focused(value) => self.data.focused = value,
+            WindowEvent::DroppedFile(path) => self.data.events.push(AppEvent::DroppedFile(path)),
+            WindowEvent::HoveredFile(path) => self.data.events.push(AppEvent::HoveredFile(path)),
+            WindowEvent::HoveredFileCancelled => self.data.events.push(AppEvent::HoveredFileCancelled),
             _ => ()
         }
 
         self.data.window().request_redraw()
     }
+
+    fn about_to_wait(&mut self, target: &ActiveEventLoop)
+    {
+        // a minimized or occluded window may never be redrawn,
+        // the states still get to cancel the close request before the app exits
+        if self.data.close_requested && !target.exiting()
+        {
+            self.update(target)
+        }
+    }
     
     fn suspended(&mut self, _: &ActiveEventLoop)
     {
         self.data.focused = false;
+        self.data.audio.pause();
         self.data.renderer.suspend()
     }
     
     fn exiting(&mut self, _event_loop: &ActiveEventLoop)
     {
         log::debug!("exiting");
-        self.fsm.clear()
+        self.fsm.clear();
+
+        if let (Some(path), Some(recording)) = (&self.recording_path, self.data.input.stop_recording())
+        {
+            match recording.save(path)
+            {
+                Ok(()) => log::info!("input recording saved to {}", path.display()),
+                Err(err) => log::error!("failed to save the input recording: {err}")
+            }
+        }
     }
 
     fn memory_warning(&mut self, target: &ActiveEventLoop)
@@ -167,13 +291,19 @@ impl ApplicationHandler for AppHandler
 
 impl AppData
 {
-    pub fn new(w_attributes: WindowAttributes, color: Option<(f64,f64,f64)>) -> Self
+    pub fn new(w_attributes: WindowAttributes, color: Option<Color>, audio_backend: audio::AudioBackend) -> Self
     {
         Self
         {
             input: Default::default(),
             renderer: rendering::RendererData::new(w_attributes, color),
             focused: true,
+            events: vec![],
+            close_requested: false,
+            cursor: Default::default(),
+            audio: audio::AudioPlayer::with_backend(audio_backend),
+            music: Default::default(),
+            last_audio_update: std::time::Instant::now(),
         }
     }
 
@@ -199,6 +329,43 @@ impl AppData
         }
     }
 
+    /// moves the software cursor and applies the cursor changes requested during the update
+    fn update_cursor(&mut self, target: &ActiveEventLoop)
+    {
+        let position = input::Input::from(&self.input).cursor_position();
+
+        self.cursor.update_software(&mut (&mut self.renderer).into(), position);
+        self.cursor.apply(target, &mut self.renderer)
+    }
+
+    /// moves the audio listener to the camera
+    fn update_listener(&mut self)
+    {
+        let mut renderer: rendering::Renderer = (&mut self.renderer).into();
+        self.audio.set_listener_position(renderer.get_camera().position().truncate())
+    }
+
+    /// applies the music requests, updates the fades and the positional sounds,
+    /// runs every frame even when the window isn't focused
+    fn update_audio(&mut self)
+    {
+        // the redraws can stop while the window is hidden or suspended,
+        // the fades go on from where they were instead of snapping to the end
+        const MAX_DELTA: std::time::Duration = std::time::Duration::from_millis(250);
+
+        let now = std::time::Instant::now();
+        let delta = (now - std::mem::replace(&mut self.last_audio_update, now)).min(MAX_DELTA);
+
+        self.music.update(&self.audio, delta);
+        self.audio.update(delta);
+    }
+
+    /// sends the beats heard during the last audio update to the states
+    fn collect_beats(&mut self)
+    {
+        self.events.extend(self.music.beats().iter().map(|beat| AppEvent::Beat(*beat)))
+    }
+
     /// return a wrapper that doesn't contain engine implementation methods 
     pub fn to_user_mut(&mut self) -> App
     {
@@ -206,6 +373,11 @@ impl AppData
         {
             input: (&self.input).into(),
             renderer: (&mut self.renderer).into(),
+            events: &self.events,
+            close_requested: &mut self.close_requested,
+            cursor: &mut self.cursor,
+            audio: &self.audio,
+            music: &mut self.music,
         }
     }
 }
@@ -215,6 +387,16 @@ pub struct App<'a>
     pub input: input::Input<'a>,
     /// the application's renderer tasked with drawing to the screen
     pub renderer: rendering::Renderer<'a>,
+    /// plays sounds, the listener of positional sounds follows the camera
+    /// unless it's moved during the update
+    pub audio: &'a audio::AudioPlayer,
+    /// crossfades between music tracks, states can request a track when entered with [crate::AppState::music].
+    ///
+    /// the requests are applied at the start of the next frame, before the beats are collected
+    pub music: &'a mut audio::MusicPlayer,
+    events: &'a [AppEvent],
+    close_requested: &'a mut bool,
+    cursor: &'a mut crate::cursor::CursorData,
 }
 
 impl<'a> App<'a>
@@ -233,6 +415,116 @@ impl<'a> App<'a>
         self.ui().context().send_viewport_cmd(rendering::ui::egui::ViewportCommand::Close)
     }
 
+    /// the window events received since the last update
+    pub fn events(&self) -> &[AppEvent]
+    {
+        self.events
+    }
+
+    /// how long the previous frame lasted, use it to move things at the same speed at any framerate.
+    ///
+    /// while replaying an input recording it's the recorded one, so that the replay matches the recording
+    pub fn delta_time(&self) -> std::time::Duration
+    {
+        self.input.frame_delta()
+    }
+
+    /// the files dropped on the window since the last update
+    /// ```
+    /// for path in app.dropped_files()
+    /// {
+    ///     if path.extension().is_some_and(|ext| ext == "png") { ... }
+    /// }
+    /// ```
+    pub fn dropped_files(&self) -> impl Iterator<Item = &std::path::Path>
+    {
+        self.events.iter().filter_map(|event| match event
+        {
+            AppEvent::DroppedFile(path) => Some(path.as_path()),
+            _ => None
+        })
+    }
+
+    /// returns true if the window was asked to close and the app will exit after this update
+    pub fn close_requested(&self) -> bool
+    {
+        *self.close_requested
+    }
+
+    /// keeps the app running after a close request, for example to show an "unsaved changes" dialog,
+    /// 
+    /// use [App::close] to close it afterwards
+    pub fn cancel_close(&mut self)
+    {
+        *self.close_requested = false
+    }
+
+    /// sets the cursor to one of the system cursors
+    pub fn set_cursor_icon(&mut self, icon: crate::CursorIcon)
+    {
+        self.cursor.set_icon(icon)
+    }
+
+    /// sets the cursor to an image from a `byte slice`,
+    /// `hotspot` is the pixel of the image that points at the cursor position
+    /// 
+    /// # example
+    /// 
+    /// ```
+    /// app.set_cursor_image(include_bytes!("cursor.png"), (0, 0))?;
+    /// ```
+    pub fn set_cursor_image(&mut self, bytes: &[u8], hotspot: (u16, u16)) -> Result<(), crate::CursorError>
+    {
+        self.cursor.set_image(bytes, hotspot)
+    }
+
+    /// same as [App::set_cursor_image] but loads the image from a path
+    pub fn set_cursor_image_from_path
+    (
+        &mut self, path: impl AsRef<std::path::Path>, hotspot: (u16, u16)
+    ) -> Result<(), crate::CursorError>
+    {
+        let bytes = std::fs::read(path)
+            .map_err(|err| crate::CursorError::Image(rendering::image::ImageError::IoError(err)))?;
+
+        self.cursor.set_image(&bytes, hotspot)
+    }
+
+    pub fn set_cursor_visible(&mut self, visible: bool)
+    {
+        self.cursor.set_visible(visible)
+    }
+
+    /// confines the cursor to the window or locks it in place,
+    /// if the platform doesn't support the requested mode the other one is used
+    pub fn set_cursor_grab(&mut self, mode: crate::CursorGrabMode)
+    {
+        self.cursor.set_grab(mode)
+    }
+
+    /// replaces the hardware cursor with a sprite that follows it,
+    /// every instance of the sprite is moved to the cursor position.
+    /// 
+    /// the layer is the one set on the builder, use a layer above everything else
+    /// and set the pivot to the hotspot of the image
+    /// 
+    /// # example
+    /// 
+    /// ```
+    /// app.set_software_cursor(SpriteBuilder::new("assets/cursor.png").set_layer::<255>([]))
+    /// ```
+    pub fn set_software_cursor(&mut self, sprite: rendering::SpriteBuilder)
+    {
+        let sprite = self.renderer.add_sprite(sprite);
+        self.cursor.set_software(Some(sprite))
+    }
+
+    /// removes the software cursor and shows the hardware one again
+    pub fn remove_software_cursor(&mut self)
+    {
+        self.cursor.set_software(None)
+    }
+
     /// returns the screen size in the format you decide,
     /// ex:
     /// ```
@@ -285,6 +577,16 @@ impl<'a> App<'a>
         self.input.get_key_up(keycode)
     }
     
+    pub fn get_key_pressed_within(&self, keycode: input::KeyCode, window: std::time::Duration) -> bool
+    {
+        self.input.get_key_pressed_within(keycode, window)
+    }
+
+    pub fn combo_performed(&self, combo: &input::Combo) -> bool
+    {
+        self.input.combo_performed(combo)
+    }
+    
     pub fn get_mouse_button_down(&self, click: input::MouseButton) -> bool
     {
         self.input.get_mouse_button_down(click)
@@ -0,0 +1,186 @@
+//! hardware and software cursors
+
+use input::winit::
+{
+    event_loop::ActiveEventLoop,
+    window::{Cursor, CustomCursor, CustomCursorSource}
+};
+
+pub use input::winit::window::{CursorIcon, CursorGrabMode, BadImage};
+
+/// errors that can occur when loading a cursor image
+#[derive(Debug)]
+pub enum CursorError
+{
+    /// the image couldn't be read or decoded
+    Image(rendering::image::ImageError),
+    /// the image is too large to be used as a cursor or the hotspot is outside of it
+    BadImage(BadImage),
+}
+
+impl std::fmt::Display for CursorError
+{
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result
+    {
+        match self
+        {
+            CursorError::Image(err) => write!(f, "failed to load the cursor image: {err}"),
+            CursorError::BadImage(err) => write!(f, "invalid cursor image: {err}"),
+        }
+    }
+}
+
+impl std::error::Error for CursorError {}
+
+impl From<rendering::image::ImageError> for CursorError
+{
+    fn from(err: rendering::image::ImageError) -> Self { Self::Image(err) }
+}
+
+impl From<BadImage> for CursorError
+{
+    fn from(err: BadImage) -> Self { Self::BadImage(err) }
+}
+
+/// requests to the window that can only be applied by the engine
+enum CursorCommand
+{
+    Icon(CursorIcon),
+    Custom(CustomCursorSource),
+    Visible(bool),
+    Grab(CursorGrabMode)
+}
+
+/// this is handled by the engine
+pub(crate) struct CursorData
+{
+    commands: Vec<CursorCommand>,
+    /// the cursor shown when the ui doesn't need a specific one
+    cursor: Cursor,
+    visible: bool,
+    /// a sprite following the cursor, the hardware cursor is hidden while this is present
+    software: Option<rendering::Sprite>
+}
+
+impl Default for CursorData
+{
+    fn default() -> Self
+    {
+        Self
+        {
+            commands: vec![],
+            cursor: Cursor::default(),
+            visible: true,
+            software: None,
+        }
+    }
+}
+
+impl CursorData
+{
+    pub fn set_icon(&mut self, icon: CursorIcon)
+    {
+        self.commands.push(CursorCommand::Icon(icon))
+    }
+
+    /// decodes the image and queues it to be used as cursor,
+    /// `hotspot` is the pixel of the image that points at the cursor position
+    pub fn set_image(&mut self, bytes: &[u8], hotspot: (u16, u16)) -> Result<(), CursorError>
+    {
+        let image = rendering::image::load_from_memory(bytes)?.to_rgba8();
+
+        let (width, height) = image.dimensions();
+
+        // let winit report the error for images that are too large
+        let clamp = |size: u32| u16::try_from(size).unwrap_or(u16::MAX);
+
+        let source = CustomCursor::from_rgba
+        (
+            image.into_raw(), clamp(width), clamp(height), hotspot.0, hotspot.1
+        )?;
+
+        self.commands.push(CursorCommand::Custom(source));
+
+        Ok(())
+    }
+
+    pub fn set_visible(&mut self, visible: bool)
+    {
+        self.commands.push(CursorCommand::Visible(visible))
+    }
+
+    pub fn set_grab(&mut self, mode: CursorGrabMode)
+    {
+        self.commands.push(CursorCommand::Grab(mode))
+    }
+
+    pub fn set_software(&mut self, sprite: Option<rendering::Sprite>)
+    {
+        self.software = sprite;
+
+        // we need to update the visibility of the hardware cursor
+        self.commands.push(CursorCommand::Visible(self.visible))
+    }
+
+    /// applies the requested changes to the window
+    pub fn apply(&mut self, target: &ActiveEventLoop, renderer: &mut rendering::RendererData)
+    {
+        if self.commands.is_empty()
+        {
+            return
+        }
+
+        let window = renderer.window.clone().expect("the window should have been created");
+
+        for command in std::mem::take(&mut self.commands)
+        {
+            match command
+            {
+                CursorCommand::Icon(icon) => self.cursor = icon.into(),
+
+                CursorCommand::Custom(source) => self.cursor = target.create_custom_cursor(source).into(),
+
+                CursorCommand::Visible(visible) => self.visible = visible,
+
+                CursorCommand::Grab(mode) => if let Err(err) = window.set_cursor_grab(mode)
+                {
+                    // not every platform supports every mode, try the other one
+                    let fallback = match mode
+                    {
+                        CursorGrabMode::Locked => CursorGrabMode::Confined,
+                        CursorGrabMode::Confined => CursorGrabMode::Locked,
+                        CursorGrabMode::None => CursorGrabMode::None
+                    };
+
+                    if fallback == mode || window.set_cursor_grab(fallback).is_err()
+                    {
+                        log::warn!("failed to grab the cursor with {mode:?}: {err}")
+                    }
+                }
+            }
+        }
+
+        // the ui is the one setting the cursor, otherwise it would override ours
+        renderer.ui.set_default_cursor(self.cursor.clone(), self.visible && self.software.is_none())
+    }
+
+    /// moves the software cursor to the cursor position
+    pub fn update_software(&mut self, renderer: &mut rendering::Renderer, position: input::baguette_math::Vec2)
+    {
+        let Some(sprite) = &mut self.software
+        else
+        {
+            return
+        };
+
+        let world = renderer.get_camera().screen_to_world(position, renderer.screen_size::<f32>());
+
+        for (_, instances) in sprite.iter_all_mut()
+        {
+            for instance in instances
+            {
+                instance.translation = world
+            }
+        }
+    }
+}
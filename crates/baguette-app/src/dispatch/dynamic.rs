@@ -13,6 +13,13 @@ pub trait AppState
         None
     }
 
+    /// the music to crossfade to when entering this state,
+    /// the current music keeps playing if this is [None] or it's the same track
+    fn music(&self) -> Option<audio::Track>
+    {
+        None
+    }
+
     fn id() -> StateId where Self: Sized + 'static
     {
         StateId::of::<Self>()
@@ -149,7 +156,15 @@ impl FsmData<ActiveState>
             {
                 self.current.event = StateEvent::Exit(state_id)
             }
-            StateEvent::Enter => self.current.event = StateEvent::Update,
+            StateEvent::Enter =>
+            {
+                if let Some(track) = self.current.state.music()
+                {
+                    app.music.play(track)
+                }
+
+                self.current.event = StateEvent::Update
+            }
 
             StateEvent::Exit(id) =>
             {
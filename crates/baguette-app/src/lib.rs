@@ -5,6 +5,9 @@ pub mod dynamic;
 pub mod application;
 pub use application::*;
 
+pub mod cursor;
+pub use cursor::*;
+
 pub use rendering::*;
 
 pub use dynamic::*;
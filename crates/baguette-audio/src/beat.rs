@@ -0,0 +1,242 @@
+//! keeps the game in sync with the music
+
+use std::time::Duration;
+
+use crate::PlaybackHandle;
+
+/// the tempo of a music track
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Tempo
+{
+    /// beats per minute
+    pub bpm: f64,
+    /// when the first beat is heard, from the start of the track
+    pub offset: Duration,
+    pub beats_per_bar: u32
+}
+
+impl Tempo
+{
+    /// a tempo in 4/4 starting at the beginning of the track
+    pub fn new(bpm: f64) -> Self
+    {
+        Self { bpm, offset: Duration::ZERO, beats_per_bar: 4 }
+    }
+
+    pub fn offset(mut self, offset: Duration) -> Self
+    {
+        self.offset = offset;
+        self
+    }
+
+    pub fn beats_per_bar(mut self, beats_per_bar: u32) -> Self
+    {
+        self.beats_per_bar = beats_per_bar.max(1);
+        self
+    }
+
+    /// the length of a beat
+    pub fn beat_duration(&self) -> Duration
+    {
+        Duration::from_secs_f64(60. / self.bpm.max(f64::EPSILON))
+    }
+}
+
+/// emitted by the [BeatClock] when a beat is heard
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BeatEvent
+{
+    /// a beat started, `index` counts the beats from the start of the track
+    /// and `in_bar` is the position of the beat in its bar, starting from zero
+    Beat { index: u64, in_bar: u32 },
+    /// a bar started, this is emitted together with its first beat
+    Bar(u64)
+}
+
+/// follows the position of a sound and tells when its beats are heard.
+///
+/// the position reported by the audio thread moves in steps as the samples are sent to the
+/// output, so the clock advances on its own every frame and slowly corrects itself towards it
+///
+/// # example
+///
+/// ```ignore
+/// let mut clock = BeatClock::new(Tempo::new(128.));
+/// clock.follow(player.stream("assets/song.ogg", PlaySettings::default().bus(Bus::Music))?);
+///
+/// // every frame
+/// clock.update(delta);
+///
+/// for event in clock.events()
+/// {
+///     if let BeatEvent::Beat { .. } = event { sprite.pulse() }
+/// }
+/// ```
+pub struct BeatClock
+{
+    tempo: Tempo,
+    handle: Option<PlaybackHandle>,
+    /// subtracted from the position, the time it takes for the samples to be heard
+    latency: Duration,
+    /// the smoothed time heard from the sound, in seconds
+    time: f64,
+    /// the index of the last beat that was emitted
+    last_beat: i64,
+    events: Vec<BeatEvent>
+}
+
+impl BeatClock
+{
+    /// past this difference from the reported position the clock jumps to it,
+    /// for example after a seek or when a loop restarts
+    const MAX_DRIFT: f64 = 0.1;
+    /// how much of the difference from the reported position is corrected every frame
+    const CORRECTION: f64 = 0.1;
+
+    pub fn new(tempo: Tempo) -> Self
+    {
+        Self { tempo, handle: None, latency: Duration::ZERO, time: 0., last_beat: -1, events: vec![] }
+    }
+
+    /// the time it takes for the sound to be heard after it's sent to the output,
+    /// see [crate::AudioPlayer::output_latency]
+    pub fn with_latency(mut self, latency: Duration) -> Self
+    {
+        self.latency = latency;
+        self
+    }
+
+    pub fn set_latency(&mut self, latency: Duration)
+    {
+        self.latency = latency
+    }
+
+    pub fn latency(&self) -> Duration
+    {
+        self.latency
+    }
+
+    pub fn tempo(&self) -> Tempo
+    {
+        self.tempo
+    }
+
+    pub fn set_tempo(&mut self, tempo: Tempo)
+    {
+        self.tempo = tempo
+    }
+
+    /// follows this sound from its current position,
+    /// the beat already playing is only emitted if the sound just started
+    pub fn follow(&mut self, handle: PlaybackHandle)
+    {
+        self.time = Self::heard(&handle, self.latency);
+
+        self.last_beat = match handle.position().is_zero()
+        {
+            true => self.beat().floor() as i64 - 1,
+            false => self.beat().floor() as i64
+        };
+
+        self.handle = Some(handle)
+    }
+
+    /// the sound being followed
+    pub fn handle(&self) -> Option<&PlaybackHandle>
+    {
+        self.handle.as_ref()
+    }
+
+    /// the position of the sound minus the latency, in seconds
+    fn heard(handle: &PlaybackHandle, latency: Duration) -> f64
+    {
+        handle.position().as_secs_f64() - latency.as_secs_f64()
+    }
+
+    /// advances the clock and collects the beats heard since the last update,
+    /// should be called every frame
+    pub fn update(&mut self, delta: Duration)
+    {
+        self.events.clear();
+
+        let Some(handle) = &self.handle
+        else
+        {
+            return
+        };
+
+        let reported = Self::heard(handle, self.latency);
+
+        let predicted = match handle.is_paused() || handle.is_finished()
+        {
+            true => self.time,
+            false => self.time + delta.as_secs_f64() * handle.speed() as f64
+        };
+
+        let drift = reported - predicted;
+
+        self.time = match drift.abs() > Self::MAX_DRIFT
+        {
+            true => reported,
+            // never go backwards because of a correction, the beats would be emitted twice
+            false => f64::max(predicted + drift * Self::CORRECTION, self.time)
+        };
+
+        let beat = self.beat().floor() as i64;
+
+        // the sound jumped back, emit the current beat again
+        let last = match self.last_beat > beat
+        {
+            true => beat - 1,
+            false => self.last_beat
+        };
+
+        for index in (last + 1..=beat).filter(|index| *index >= 0)
+        {
+            let index = index as u64;
+            let beats_per_bar = self.tempo.beats_per_bar.max(1) as u64;
+
+            let in_bar = (index % beats_per_bar) as u32;
+
+            if in_bar == 0
+            {
+                self.events.push(BeatEvent::Bar(index / beats_per_bar))
+            }
+
+            self.events.push(BeatEvent::Beat { index, in_bar })
+        }
+
+        self.last_beat = beat
+    }
+
+    /// the beats heard during the last update
+    pub fn events(&self) -> &[BeatEvent]
+    {
+        &self.events
+    }
+
+    /// the time heard from the sound, compensated for the latency
+    pub fn time(&self) -> Duration
+    {
+        Duration::from_secs_f64(self.time.max(0.))
+    }
+
+    /// the beats heard from the first one, the fractional part is the progress in the current beat.
+    /// it's negative before the first beat
+    pub fn beat(&self) -> f64
+    {
+        (self.time - self.tempo.offset.as_secs_f64()) * self.tempo.bpm / 60.
+    }
+
+    /// the progress in the current beat between `0.` and `1.`, useful to animate to the music
+    pub fn beat_phase(&self) -> f32
+    {
+        self.beat().rem_euclid(1.) as f32
+    }
+
+    /// the bars heard from the first one, the fractional part is the progress in the current bar
+    pub fn bar(&self) -> f64
+    {
+        self.beat() / self.tempo.beats_per_bar.max(1) as f64
+    }
+}
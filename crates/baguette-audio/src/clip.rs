@@ -0,0 +1,181 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use rodio::Source;
+
+use crate::AudioError;
+
+/// an audio file decoded once and kept in memory,
+/// cloning it is cheap since the samples are shared
+#[derive(Clone)]
+pub struct AudioClip
+{
+    /// interleaved samples
+    samples: Arc<[f32]>,
+    channels: u16,
+    sample_rate: u32,
+    /// how many instances of this clip can be played at the same time
+    max_instances: Option<usize>
+}
+
+impl AudioClip
+{
+    /// decodes an audio file, the supported formats are wav, ogg, mp3 and flac
+    pub fn from_path(path: impl AsRef<std::path::Path>) -> Result<Self, AudioError>
+    {
+        Self::from_bytes(&std::fs::read(path)?)
+    }
+
+    /// decodes an audio file from a `byte slice`
+    ///
+    /// # example
+    ///
+    /// ```ignore
+    /// let jump = AudioClip::from_bytes(include_bytes!("jump.ogg"))?;
+    /// ```
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, AudioError>
+    {
+        let decoder = rodio::Decoder::new(std::io::Cursor::new(bytes.to_vec()))?;
+
+        let channels = decoder.channels();
+        let sample_rate = decoder.sample_rate();
+
+        let samples = decoder.convert_samples::<f32>().collect();
+
+        Ok(Self { samples, channels, sample_rate, max_instances: None })
+    }
+
+    /// creates a clip from interleaved samples between `-1.` and `1.`
+    pub fn from_samples(channels: u16, sample_rate: u32, samples: impl Into<Arc<[f32]>>) -> Self
+    {
+        Self { samples: samples.into(), channels: channels.max(1), sample_rate, max_instances: None }
+    }
+
+    /// limits how many instances of this clip can be played at the same time,
+    /// once reached playing the clip again stops its oldest instance
+    ///
+    /// # example
+    ///
+    /// ```ignore
+    /// let explosion = AudioClip::from_path("explosion.ogg")?.with_max_instances(4);
+    /// ```
+    pub fn with_max_instances(mut self, max_instances: usize) -> Self
+    {
+        self.max_instances = Some(max_instances.max(1));
+        self
+    }
+
+    pub fn max_instances(&self) -> Option<usize>
+    {
+        self.max_instances
+    }
+
+    /// identifies the samples of the clip, shared by its clones
+    pub(crate) fn id(&self) -> usize
+    {
+        self.samples.as_ptr() as usize
+    }
+
+    pub fn channels(&self) -> u16
+    {
+        self.channels
+    }
+
+    pub fn sample_rate(&self) -> u32
+    {
+        self.sample_rate
+    }
+
+    /// the interleaved samples of the clip
+    pub fn samples(&self) -> &[f32]
+    {
+        &self.samples
+    }
+
+    pub fn duration(&self) -> Duration
+    {
+        let frames = self.samples.len() / self.channels as usize;
+
+        Duration::from_secs_f64(frames as f64 / self.sample_rate as f64)
+    }
+
+    /// returns a source that plays the clip from the start
+    pub(crate) fn source(&self) -> ClipSource
+    {
+        ClipSource { clip: self.clone(), position: 0 }
+    }
+}
+
+impl std::fmt::Debug for AudioClip
+{
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result
+    {
+        f.debug_struct("AudioClip")
+            .field("channels", &self.channels)
+            .field("sample_rate", &self.sample_rate)
+            .field("duration", &self.duration())
+            .field("max_instances", &self.max_instances)
+            .finish()
+    }
+}
+
+/// plays an [AudioClip] without copying its samples
+pub(crate) struct ClipSource
+{
+    clip: AudioClip,
+    /// the index of the next sample
+    position: usize
+}
+
+impl Iterator for ClipSource
+{
+    type Item = f32;
+
+    fn next(&mut self) -> Option<f32>
+    {
+        let sample = self.clip.samples.get(self.position).copied()?;
+        self.position += 1;
+
+        Some(sample)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>)
+    {
+        let remaining = self.clip.samples.len().saturating_sub(self.position);
+        (remaining, Some(remaining))
+    }
+}
+
+impl Source for ClipSource
+{
+    fn current_frame_len(&self) -> Option<usize>
+    {
+        Some(self.clip.samples.len().saturating_sub(self.position))
+    }
+
+    fn channels(&self) -> u16
+    {
+        self.clip.channels
+    }
+
+    fn sample_rate(&self) -> u32
+    {
+        self.clip.sample_rate
+    }
+
+    fn total_duration(&self) -> Option<Duration>
+    {
+        Some(self.clip.duration())
+    }
+
+    fn try_seek(&mut self, pos: Duration) -> Result<(), rodio::source::SeekError>
+    {
+        let channels = self.clip.channels as usize;
+        let frame = (pos.as_secs_f64() * self.clip.sample_rate as f64) as usize;
+
+        // saturate at the end of the clip
+        self.position = usize::min(frame * channels, self.clip.samples.len());
+
+        Ok(())
+    }
+}
@@ -0,0 +1,933 @@
+//! effects applied to the sounds while they are played,
+//! they can be attached to a [crate::Bus] or to a single [crate::PlaybackHandle]
+
+use std::sync::{Arc, Mutex};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::Duration;
+
+use rodio::Source;
+
+/// an effect and its parameters
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Effect
+{
+    /// removes the frequencies above the cutoff, in hertz.
+    /// a low cutoff muffles the sound, like being underwater
+    LowPass { cutoff: f32 },
+    /// removes the frequencies below the cutoff, in hertz.
+    /// a high cutoff makes the sound thin, like coming out of a radio
+    HighPass { cutoff: f32 },
+    /// makes the sound echo like in a room
+    Reverb
+    {
+        /// the size of the room between `0.` and `1.`, larger rooms echo for longer
+        room_size: f32,
+        /// how much the walls absorb the high frequencies, between `0.` and `1.`
+        damping: f32,
+        /// how much of the reverb is heard, between `0.` and `1.`
+        mix: f32
+    },
+    /// repeats the sound after some time
+    Delay
+    {
+        /// the time between the repetitions, up to [Effect::MAX_DELAY]
+        time: Duration,
+        /// how much of every repetition is repeated again, between `0.` and `1.`
+        feedback: f32,
+        /// the volume of the repetitions
+        mix: f32
+    },
+    /// lowers the volume of the sound when it's louder than the threshold
+    Compressor
+    {
+        /// in decibels, the volume above which the sound is compressed
+        threshold: f32,
+        /// how much the sound is compressed, `4.` means that 4 decibels above the threshold
+        /// become 1 decibel
+        ratio: f32,
+        attack: Duration,
+        release: Duration,
+        /// in decibels, the volume added after compressing
+        makeup: f32
+    },
+    /// keeps the sound below the ceiling, in decibels
+    Limiter { ceiling: f32 }
+}
+
+impl Effect
+{
+    /// the longest time supported by [Effect::Delay]
+    pub const MAX_DELAY: Duration = Duration::from_secs(2);
+
+    pub fn low_pass(cutoff: f32) -> Self
+    {
+        Self::LowPass { cutoff }
+    }
+
+    pub fn high_pass(cutoff: f32) -> Self
+    {
+        Self::HighPass { cutoff }
+    }
+
+    /// a reverb with medium room and damping
+    pub fn reverb(mix: f32) -> Self
+    {
+        Self::Reverb { room_size: 0.5, damping: 0.5, mix }
+    }
+
+    pub fn delay(time: Duration, feedback: f32, mix: f32) -> Self
+    {
+        Self::Delay { time, feedback, mix }
+    }
+
+    /// a compressor with a fast attack and a medium release
+    pub fn compressor(threshold: f32, ratio: f32) -> Self
+    {
+        Self::Compressor
+        {
+            threshold,
+            ratio,
+            attack: Duration::from_millis(5),
+            release: Duration::from_millis(100),
+            makeup: 0.
+        }
+    }
+
+    pub fn limiter(ceiling: f32) -> Self
+    {
+        Self::Limiter { ceiling }
+    }
+}
+
+/// identifies an effect pushed to an [EffectChain], it stays valid when the other effects are removed
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct EffectId(u64);
+
+struct ChainState
+{
+    effects: Mutex<Vec<(EffectId, Effect)>>,
+    /// incremented every time the effects change, so that the sounds know when to read them again
+    version: AtomicU64,
+    next_id: AtomicU64
+}
+
+/// a list of effects applied in order, changes are heard immediately.
+///
+/// cloning the chain returns a handle to the same effects
+///
+/// # example
+///
+/// ```
+/// use baguette_audio::{AudioBackend, AudioPlayer, Bus, Effect};
+///
+/// let player = AudioPlayer::with_backend(AudioBackend::Null);
+///
+/// // muffle everything while the pause menu is open
+/// let muffle = player.mixer().effects(Bus::Master).push(Effect::low_pass(800.));
+///
+/// // and back to normal once it's closed
+/// player.mixer().effects(Bus::Master).remove(muffle);
+/// ```
+#[derive(Clone)]
+pub struct EffectChain(Arc<ChainState>);
+
+impl Default for EffectChain
+{
+    fn default() -> Self
+    {
+        Self(Arc::new(ChainState { effects: Mutex::new(vec![]), version: AtomicU64::new(0), next_id: AtomicU64::new(0) }))
+    }
+}
+
+impl EffectChain
+{
+    /// changes the effects and notifies the sounds
+    fn modify<T>(&self, f: impl FnOnce(&mut Vec<(EffectId, Effect)>) -> T) -> T
+    {
+        let result = f(&mut self.0.effects.lock().unwrap());
+        self.0.version.fetch_add(1, Ordering::Release);
+
+        result
+    }
+
+    /// adds an effect at the end of the chain
+    pub fn push(&self, effect: Effect) -> EffectId
+    {
+        let id = EffectId(self.0.next_id.fetch_add(1, Ordering::Relaxed));
+
+        self.modify(|effects| effects.push((id, effect)));
+        id
+    }
+
+    /// replaces the effect, used to change its parameters.
+    /// the state of the effect, like the reverb tail, is kept if it's the same kind of effect.
+    ///
+    /// returns false if the effect was removed
+    pub fn set(&self, id: EffectId, effect: Effect) -> bool
+    {
+        self.modify(|effects| match effects.iter_mut().find(|(effect_id, _)| *effect_id == id)
+        {
+            Some((_, old)) =>
+            {
+                *old = effect;
+                true
+            }
+            None => false
+        })
+    }
+
+    /// removes the effect, [None] if it was already removed
+    pub fn remove(&self, id: EffectId) -> Option<Effect>
+    {
+        self.modify(|effects|
+        {
+            let index = effects.iter().position(|(effect_id, _)| *effect_id == id)?;
+            Some(effects.remove(index).1)
+        })
+    }
+
+    pub fn clear(&self)
+    {
+        self.modify(|effects| effects.clear())
+    }
+
+    pub fn get(&self, id: EffectId) -> Option<Effect>
+    {
+        self.0.effects.lock().unwrap().iter().find(|(effect_id, _)| *effect_id == id).map(|(_, effect)| *effect)
+    }
+
+    /// the effects in the order they're applied
+    pub fn effects(&self) -> Vec<Effect>
+    {
+        self.0.effects.lock().unwrap().iter().map(|(_, effect)| *effect).collect()
+    }
+
+    pub fn len(&self) -> usize
+    {
+        self.0.effects.lock().unwrap().len()
+    }
+
+    pub fn is_empty(&self) -> bool
+    {
+        self.len() == 0
+    }
+}
+
+/// -60db, the level under which the tail of an effect isn't heard anymore
+const SILENCE: f32 = 0.001;
+
+/// turns a level in decibels into a volume multiplier
+fn db_to_gain(db: f32) -> f32
+{
+    10f32.powf(db / 20.)
+}
+
+/// the smoothing coefficient that reaches the target in about `time`
+fn time_coefficient(time: Duration, sample_rate: f32) -> f32
+{
+    match time.as_secs_f32() * sample_rate
+    {
+        samples if samples < 1. => 0.,
+        samples => (-1. / samples).exp()
+    }
+}
+
+/// a second order filter, used for the low and high pass
+#[derive(Default)]
+struct Biquad
+{
+    b: [f32; 3],
+    a: [f32; 2],
+    /// the last two inputs and outputs of each channel
+    x: [[f32; 2]; 2],
+    y: [[f32; 2]; 2]
+}
+
+impl Biquad
+{
+    fn configure(&mut self, cutoff: f32, high_pass: bool, sample_rate: f32)
+    {
+        // butterworth response
+        const Q: f32 = std::f32::consts::FRAC_1_SQRT_2;
+
+        let cutoff = cutoff.clamp(10., sample_rate * 0.45);
+
+        let w0 = std::f32::consts::TAU * cutoff / sample_rate;
+        let (sin, cos) = w0.sin_cos();
+        let alpha = sin / (2. * Q);
+
+        let a0 = 1. + alpha;
+
+        let b = match high_pass
+        {
+            true => [(1. + cos) / 2., -(1. + cos), (1. + cos) / 2.],
+            false => [(1. - cos) / 2., 1. - cos, (1. - cos) / 2.]
+        };
+
+        self.b = b.map(|b| b / a0);
+        self.a = [-2. * cos / a0, (1. - alpha) / a0];
+    }
+
+    fn process(&mut self, frame: &mut [f32; 2])
+    {
+        for (channel, sample) in frame.iter_mut().enumerate()
+        {
+            let [x1, x2] = self.x[channel];
+            let [y1, y2] = self.y[channel];
+
+            let y = self.b[0] * *sample + self.b[1] * x1 + self.b[2] * x2 - self.a[0] * y1 - self.a[1] * y2;
+
+            self.x[channel] = [*sample, x1];
+            self.y[channel] = [y, y1];
+
+            *sample = y
+        }
+    }
+}
+
+struct Comb
+{
+    buffer: Vec<f32>,
+    index: usize,
+    filtered: f32
+}
+
+impl Comb
+{
+    fn new(size: usize) -> Self
+    {
+        Self { buffer: vec![0.; size.max(1)], index: 0, filtered: 0. }
+    }
+
+    fn process(&mut self, input: f32, feedback: f32, damping: f32) -> f32
+    {
+        let output = self.buffer[self.index];
+
+        self.filtered = output * (1. - damping) + self.filtered * damping;
+        self.buffer[self.index] = input + self.filtered * feedback;
+
+        self.index = (self.index + 1) % self.buffer.len();
+
+        output
+    }
+}
+
+struct AllPass
+{
+    buffer: Vec<f32>,
+    index: usize
+}
+
+impl AllPass
+{
+    fn new(size: usize) -> Self
+    {
+        Self { buffer: vec![0.; size.max(1)], index: 0 }
+    }
+
+    fn process(&mut self, input: f32) -> f32
+    {
+        let buffered = self.buffer[self.index];
+
+        self.buffer[self.index] = input + buffered * 0.5;
+        self.index = (self.index + 1) % self.buffer.len();
+
+        buffered - input
+    }
+}
+
+/// a small freeverb, each channel has its own combs and allpasses
+struct Reverb
+{
+    combs: [[Comb; 4]; 2],
+    allpasses: [[AllPass; 2]; 2],
+    feedback: f32,
+    damping: f32,
+    mix: f32
+}
+
+impl Reverb
+{
+    /// the tunings of the original freeverb at 44100hz
+    const COMBS: [usize; 4] = [1116, 1188, 1277, 1356];
+    const ALLPASSES: [usize; 2] = [556, 441];
+    /// the right channel is slightly longer to make the reverb wider
+    const SPREAD: usize = 23;
+
+    fn new(sample_rate: f32) -> Self
+    {
+        let scale = |size: usize, channel: usize| ((size + Self::SPREAD * channel) as f32 * sample_rate / 44100.) as usize;
+
+        Self
+        {
+            combs: std::array::from_fn(|channel| Self::COMBS.map(|size| Comb::new(scale(size, channel)))),
+            allpasses: std::array::from_fn(|channel| Self::ALLPASSES.map(|size| AllPass::new(scale(size, channel)))),
+            feedback: 0.,
+            damping: 0.,
+            mix: 0.
+        }
+    }
+
+    fn configure(&mut self, room_size: f32, damping: f32, mix: f32)
+    {
+        self.feedback = room_size.clamp(0., 1.) * 0.28 + 0.7;
+        self.damping = damping.clamp(0., 1.) * 0.4;
+        self.mix = mix.clamp(0., 1.)
+    }
+
+    fn process(&mut self, frame: &mut [f32; 2])
+    {
+        const INPUT_GAIN: f32 = 0.03;
+        const WET_GAIN: f32 = 3.;
+
+        let input = (frame[0] + frame[1]) * INPUT_GAIN;
+
+        for (channel, sample) in frame.iter_mut().enumerate()
+        {
+            let mut wet = self.combs[channel]
+                .iter_mut()
+                .map(|comb| comb.process(input, self.feedback, self.damping))
+                .sum();
+
+            for allpass in &mut self.allpasses[channel]
+            {
+                wet = allpass.process(wet)
+            }
+
+            *sample = *sample * (1. - self.mix) + wet * WET_GAIN * self.mix
+        }
+    }
+
+    /// the frames the room keeps echoing after the sound stopped
+    fn tail(&self) -> usize
+    {
+        if self.mix <= 0.
+        {
+            return 0
+        }
+
+        // the echoes lose the feedback every time they go through a comb
+        let loops = (SILENCE.ln() / self.feedback.ln()).ceil() as usize;
+
+        let comb = self.combs.iter().flatten().map(|comb| comb.buffer.len()).max().unwrap_or(0);
+        let allpasses = self.allpasses.iter().map(|allpasses| allpasses.iter().map(|allpass| allpass.buffer.len()).sum()).max().unwrap_or(0);
+
+        comb * loops + allpasses
+    }
+}
+
+struct Delay
+{
+    buffer: Vec<[f32; 2]>,
+    index: usize,
+    /// the delay in frames
+    frames: usize,
+    feedback: f32,
+    mix: f32
+}
+
+impl Delay
+{
+    fn new(sample_rate: f32) -> Self
+    {
+        let size = (Effect::MAX_DELAY.as_secs_f32() * sample_rate) as usize + 1;
+
+        Self { buffer: vec![[0.; 2]; size], index: 0, frames: 1, feedback: 0., mix: 0. }
+    }
+
+    fn configure(&mut self, time: Duration, feedback: f32, mix: f32, sample_rate: f32)
+    {
+        self.frames = ((time.as_secs_f32() * sample_rate) as usize).clamp(1, self.buffer.len() - 1);
+        self.feedback = feedback.clamp(0., 0.99);
+        self.mix = mix.max(0.)
+    }
+
+    fn process(&mut self, frame: &mut [f32; 2])
+    {
+        let len = self.buffer.len();
+        let delayed = self.buffer[(self.index + len - self.frames) % len];
+
+        for channel in 0..2
+        {
+            self.buffer[self.index][channel] = frame[channel] + delayed[channel] * self.feedback;
+            frame[channel] += delayed[channel] * self.mix
+        }
+
+        self.index = (self.index + 1) % len
+    }
+
+    /// the frames until the repetitions of the sound that stopped can't be heard
+    fn tail(&self) -> usize
+    {
+        if self.mix <= 0.
+        {
+            return 0
+        }
+
+        // every repetition is quieter by the feedback
+        let repetitions = match self.feedback > 0.
+        {
+            true => ((SILENCE / self.mix).ln() / self.feedback.ln()).max(0.).ceil() as usize + 1,
+            false => 1
+        };
+
+        self.frames * repetitions
+    }
+}
+
+/// also used as a limiter with an infinite ratio
+#[derive(Default)]
+struct Compressor
+{
+    threshold: f32,
+    /// how much of the level above the threshold is removed
+    slope: f32,
+    attack: f32,
+    release: f32,
+    makeup: f32,
+    /// the level is clamped to this, used by the limiter
+    ceiling: Option<f32>,
+    envelope: f32
+}
+
+impl Compressor
+{
+    fn configure(&mut self, threshold: f32, ratio: f32, attack: Duration, release: Duration, makeup: f32, sample_rate: f32)
+    {
+        self.threshold = threshold;
+        self.slope = 1. - 1. / ratio.max(1.);
+        self.attack = time_coefficient(attack, sample_rate);
+        self.release = time_coefficient(release, sample_rate);
+        self.makeup = makeup;
+        self.ceiling = None
+    }
+
+    fn configure_limiter(&mut self, ceiling: f32, sample_rate: f32)
+    {
+        self.configure(ceiling, f32::INFINITY, Duration::from_millis(1), Duration::from_millis(50), 0., sample_rate);
+        self.ceiling = Some(db_to_gain(ceiling))
+    }
+
+    fn process(&mut self, frame: &mut [f32; 2])
+    {
+        let level = f32::max(frame[0].abs(), frame[1].abs());
+
+        let coefficient = match level > self.envelope
+        {
+            true => self.attack,
+            false => self.release
+        };
+
+        self.envelope = coefficient * self.envelope + (1. - coefficient) * level;
+
+        let over = 20. * (self.envelope + 1e-9).log10() - self.threshold;
+
+        let reduction = match over > 0.
+        {
+            true => -over * self.slope,
+            false => 0.
+        };
+
+        let gain = db_to_gain(reduction + self.makeup);
+
+        for sample in frame.iter_mut()
+        {
+            *sample *= gain;
+
+            // the envelope is a bit late on sudden peaks
+            if let Some(ceiling) = self.ceiling
+            {
+                *sample = sample.clamp(-ceiling, ceiling)
+            }
+        }
+    }
+}
+
+/// the state of an effect while processing a sound
+enum Processor
+{
+    Filter(Biquad),
+    Reverb(Box<Reverb>),
+    Delay(Delay),
+    Compressor(Compressor)
+}
+
+impl Processor
+{
+    fn new(effect: &Effect, sample_rate: f32) -> Self
+    {
+        let mut processor = match effect
+        {
+            Effect::LowPass { .. } | Effect::HighPass { .. } => Self::Filter(Biquad::default()),
+            Effect::Reverb { .. } => Self::Reverb(Box::new(Reverb::new(sample_rate))),
+            Effect::Delay { .. } => Self::Delay(Delay::new(sample_rate)),
+            Effect::Compressor { .. } | Effect::Limiter { .. } => Self::Compressor(Compressor::default())
+        };
+
+        processor.configure(effect, sample_rate);
+        processor
+    }
+
+    /// applies the parameters of the effect,
+    /// returns false if the effect needs a different processor
+    fn configure(&mut self, effect: &Effect, sample_rate: f32) -> bool
+    {
+        match (self, *effect)
+        {
+            (Self::Filter(filter), Effect::LowPass { cutoff }) => filter.configure(cutoff, false, sample_rate),
+            (Self::Filter(filter), Effect::HighPass { cutoff }) => filter.configure(cutoff, true, sample_rate),
+
+            (Self::Reverb(reverb), Effect::Reverb { room_size, damping, mix }) =>
+            {
+                reverb.configure(room_size, damping, mix)
+            }
+
+            (Self::Delay(delay), Effect::Delay { time, feedback, mix }) =>
+            {
+                delay.configure(time, feedback, mix, sample_rate)
+            }
+
+            (Self::Compressor(compressor), Effect::Compressor { threshold, ratio, attack, release, makeup }) =>
+            {
+                compressor.configure(threshold, ratio, attack, release, makeup, sample_rate)
+            }
+
+            (Self::Compressor(compressor), Effect::Limiter { ceiling }) =>
+            {
+                compressor.configure_limiter(ceiling, sample_rate)
+            }
+
+            _ => return false
+        }
+
+        true
+    }
+
+    fn process(&mut self, frame: &mut [f32; 2])
+    {
+        match self
+        {
+            Self::Filter(filter) => filter.process(frame),
+            Self::Reverb(reverb) => reverb.process(frame),
+            Self::Delay(delay) => delay.process(frame),
+            Self::Compressor(compressor) => compressor.process(frame)
+        }
+    }
+
+    /// the frames the effect keeps sounding after its input went silent
+    fn tail(&self) -> usize
+    {
+        match self
+        {
+            Self::Reverb(reverb) => reverb.tail(),
+            Self::Delay(delay) => delay.tail(),
+            Self::Filter(..) | Self::Compressor(..) => 0
+        }
+    }
+}
+
+/// the processors of an [EffectChain] for a single sound or bus
+struct ChainProcessor
+{
+    chain: EffectChain,
+    /// the version of the chain the processors were built from
+    version: u64,
+    processors: Vec<(EffectId, Processor)>
+}
+
+impl ChainProcessor
+{
+    fn new(chain: EffectChain) -> Self
+    {
+        Self { chain, version: u64::MAX, processors: vec![] }
+    }
+
+    /// reads the effects again if they changed
+    fn sync(&mut self, sample_rate: f32)
+    {
+        let version = self.chain.0.version.load(Ordering::Acquire);
+
+        if version == self.version
+        {
+            return
+        }
+
+        // don't block the audio thread, try again on the next check
+        let Ok(effects) = self.chain.0.effects.try_lock()
+        else
+        {
+            return
+        };
+
+        let mut old = std::mem::take(&mut self.processors);
+
+        // the effects keep their state when the ones around them are removed
+        for (id, effect) in effects.iter()
+        {
+            let mut kept = old.iter().position(|(old_id, _)| old_id == id).map(|index| old.swap_remove(index).1);
+
+            let processor = match kept.as_mut().is_some_and(|processor| processor.configure(effect, sample_rate))
+            {
+                true => kept.expect("the processor was kept"),
+                false => Processor::new(effect, sample_rate)
+            };
+
+            self.processors.push((*id, processor))
+        }
+
+        self.version = version
+    }
+}
+
+/// applies the effect chains to a stereo source,
+/// sources with a different amount of channels are not processed
+pub(crate) struct Effected<S>
+{
+    inner: S,
+    chains: Vec<ChainProcessor>,
+    /// the frame being output
+    frame: [f32; 2],
+    channel: u16,
+    /// frames left before checking if the effects changed
+    until_sync: u32,
+    /// the silent frames left to let the effects ring out once the inner source ended
+    tail: Option<usize>
+}
+
+impl<S> Effected<S>
+where
+    S: Source<Item = f32>
+{
+    /// how often the chains are checked for changes, in frames
+    const SYNC_INTERVAL: u32 = 64;
+
+    /// the chains are applied in order
+    pub fn new(inner: S, chains: impl IntoIterator<Item = EffectChain>) -> Self
+    {
+        let chains = chains.into_iter().map(ChainProcessor::new).collect();
+
+        Self { inner, chains, frame: [0.; 2], channel: 0, until_sync: 0, tail: None }
+    }
+
+    /// the next frame of the inner source, then silence until the reverbs and the delays are quiet
+    fn next_frame(&mut self) -> Option<[f32; 2]>
+    {
+        if self.tail.is_none()
+        {
+            if let Some(left) = self.inner.next()
+            {
+                return Some([left, self.inner.next().unwrap_or(0.)])
+            }
+
+            self.tail = Some(self.chains.iter().flat_map(|chain| &chain.processors).map(|(_, processor)| processor.tail()).sum())
+        }
+
+        let tail = self.tail.as_mut().expect("the tail started");
+
+        match *tail
+        {
+            0 => None,
+            _ =>
+            {
+                *tail -= 1;
+                Some([0.; 2])
+            }
+        }
+    }
+}
+
+impl<S> Iterator for Effected<S>
+where
+    S: Source<Item = f32>
+{
+    type Item = f32;
+
+    fn next(&mut self) -> Option<f32>
+    {
+        if self.inner.channels() != 2
+        {
+            return self.inner.next()
+        }
+
+        if self.channel == 0
+        {
+            self.frame = self.next_frame()?;
+
+            if self.until_sync == 0
+            {
+                let sample_rate = self.inner.sample_rate() as f32;
+
+                for chain in &mut self.chains
+                {
+                    chain.sync(sample_rate)
+                }
+
+                self.until_sync = Self::SYNC_INTERVAL
+            }
+
+            self.until_sync -= 1;
+
+            for (_, processor) in self.chains.iter_mut().flat_map(|chain| &mut chain.processors)
+            {
+                processor.process(&mut self.frame)
+            }
+        }
+
+        let sample = self.frame[self.channel as usize];
+        self.channel = (self.channel + 1) % 2;
+
+        Some(sample)
+    }
+}
+
+impl<S> Source for Effected<S>
+where
+    S: Source<Item = f32>
+{
+    fn current_frame_len(&self) -> Option<usize>
+    {
+        match self.tail
+        {
+            // the tail goes on until the effects are quiet
+            Some(..) => None,
+            None => self.inner.current_frame_len()
+        }
+    }
+
+    fn channels(&self) -> u16
+    {
+        self.inner.channels()
+    }
+
+    fn sample_rate(&self) -> u32
+    {
+        self.inner.sample_rate()
+    }
+
+    fn total_duration(&self) -> Option<Duration>
+    {
+        self.inner.total_duration()
+    }
+
+    fn try_seek(&mut self, position: Duration) -> Result<(), rodio::source::SeekError>
+    {
+        self.inner.try_seek(position)?;
+        self.channel = 0;
+        self.tail = None;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests
+{
+    use std::time::Duration;
+
+    use rodio::buffer::SamplesBuffer;
+
+    use super::*;
+
+    const SAMPLE_RATE: u32 = 1000;
+
+    /// a click followed by some silence, and how many frames come out of the effects
+    fn frames_out(effects: impl IntoIterator<Item = Effect>) -> (Vec<f32>, usize)
+    {
+        let chain = EffectChain::default();
+
+        for effect in effects
+        {
+            chain.push(effect);
+        }
+
+        let mut samples = vec![0.; 20];
+        samples[..2].copy_from_slice(&[1., 1.]);
+
+        let output: Vec<f32> = Effected::new(SamplesBuffer::new(2, SAMPLE_RATE, samples), [chain]).collect();
+        let frames = output.len() / 2;
+
+        (output, frames)
+    }
+
+    #[test]
+    fn ids_survive_removing_other_effects()
+    {
+        let chain = EffectChain::default();
+
+        let filter = chain.push(Effect::low_pass(800.));
+        let echo = chain.push(Effect::delay(Duration::from_millis(100), 0.5, 0.5));
+
+        assert_eq!(chain.remove(filter), Some(Effect::low_pass(800.)));
+        assert_eq!(chain.remove(filter), None);
+
+        assert!(chain.set(echo, Effect::delay(Duration::from_millis(200), 0.5, 0.5)));
+        assert_eq!(chain.get(echo), Some(Effect::delay(Duration::from_millis(200), 0.5, 0.5)));
+        assert!(!chain.set(filter, Effect::low_pass(400.)));
+
+        assert_eq!(chain.effects(), [Effect::delay(Duration::from_millis(200), 0.5, 0.5)]);
+    }
+
+    #[test]
+    fn processors_follow_their_effect()
+    {
+        let chain = EffectChain::default();
+        let mut processor = ChainProcessor::new(chain.clone());
+
+        let filter = chain.push(Effect::low_pass(800.));
+        let echo = chain.push(Effect::delay(Duration::from_millis(100), 0.5, 0.5));
+        processor.sync(SAMPLE_RATE as f32);
+
+        // fill the delay line
+        let mut frame = [1.; 2];
+        processor.processors[1].1.process(&mut frame);
+
+        chain.remove(filter);
+        processor.sync(SAMPLE_RATE as f32);
+
+        let ids: Vec<_> = processor.processors.iter().map(|(id, _)| *id).collect();
+        assert_eq!(ids, [echo]);
+
+        // the echo of the frame is still there
+        let Processor::Delay(delay) = &processor.processors[0].1
+        else
+        {
+            panic!("the delay was replaced")
+        };
+
+        assert!(delay.buffer.iter().any(|frame| frame[0] != 0.));
+    }
+
+    #[test]
+    fn no_tail_without_echoes()
+    {
+        assert_eq!(frames_out([]).1, 10);
+        assert_eq!(frames_out([Effect::low_pass(200.), Effect::limiter(-1.)]).1, 10);
+    }
+
+    #[test]
+    fn delay_rings_out()
+    {
+        let (output, frames) = frames_out([Effect::delay(Duration::from_millis(100), 0.5, 0.5)]);
+
+        // 0.5 * 0.5^9 is the first repetition under -60db
+        assert_eq!(frames, 10 + 100 * 10);
+
+        // the repetitions after the sound ended are heard
+        assert_eq!(output[200], 0.5);
+        assert_eq!(output[400], 0.25);
+        assert!(output[output.len() - 2].abs() < SILENCE);
+    }
+
+    #[test]
+    fn reverb_rings_out()
+    {
+        let (output, frames) = frames_out([Effect::reverb(0.5)]);
+
+        assert!(frames > 1000);
+        assert!(output[20..].iter().any(|sample| sample.abs() > SILENCE));
+
+        let (_, larger) = frames_out([Effect::Reverb { room_size: 1., damping: 0.5, mix: 0.5 }]);
+
+        assert!(larger > frames)
+    }
+}
@@ -0,0 +1,41 @@
+/// errors that can occur when loading or playing audio
+#[derive(Debug)]
+pub enum AudioError
+{
+    /// the file couldn't be read
+    Io(std::io::Error),
+    /// the data couldn't be decoded, probably because the format is not supported
+    Decode(rodio::decoder::DecoderError),
+    /// the output device couldn't be opened
+    Output(rodio::StreamError),
+}
+
+impl std::fmt::Display for AudioError
+{
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result
+    {
+        match self
+        {
+            AudioError::Io(err) => write!(f, "failed to read the audio file: {err}"),
+            AudioError::Decode(err) => write!(f, "failed to decode the audio: {err}"),
+            AudioError::Output(err) => write!(f, "failed to open the audio output: {err}"),
+        }
+    }
+}
+
+impl std::error::Error for AudioError {}
+
+impl From<std::io::Error> for AudioError
+{
+    fn from(err: std::io::Error) -> Self { Self::Io(err) }
+}
+
+impl From<rodio::decoder::DecoderError> for AudioError
+{
+    fn from(err: rodio::decoder::DecoderError) -> Self { Self::Decode(err) }
+}
+
+impl From<rodio::StreamError> for AudioError
+{
+    fn from(err: rodio::StreamError) -> Self { Self::Output(err) }
+}
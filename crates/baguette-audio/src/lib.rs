@@ -1,52 +1,439 @@
-unsafe impl<const MAX_PLAYABLE_CLIPS : usize> Sync for AudioPlayer<MAX_PLAYABLE_CLIPS>{}
+//! # baguette-audio
+//! baguette's audio module
 
-/// to change the max amount of playable clips simoultaneosly set a custom value for [MAX_PLAYABLE_CLIPS]
-pub struct AudioPlayer<const MAX_PLAYABLE_CLIPS : usize = 4>
+mod error;
+pub use error::*;
+
+pub mod clip;
+pub use clip::AudioClip;
+
+pub mod playback;
+pub use playback::PlaybackHandle;
+
+pub mod mixer;
+pub use mixer::{Bus, Ducking, Mixer};
+
+pub mod music;
+pub use music::{MusicPlayer, Track};
+
+pub mod beat;
+pub use beat::{BeatClock, BeatEvent, Tempo};
+
+pub mod effects;
+pub use effects::{Effect, EffectChain, EffectId};
+
+pub mod synth;
+pub use synth::{SfxParams, Waveform};
+
+pub mod spatial;
+pub use spatial::{Attenuation, Listener, Rolloff};
+
+mod output;
+pub use output::AudioBackend;
+
+mod stream;
+
+pub mod pool;
+pub use pool::{PlaySettings, VoiceStealing};
+
+use std::sync::{Arc, Mutex};
+use std::sync::atomic::Ordering;
+use std::time::{Duration, Instant};
+
+use rodio::Source;
+
+use baguette_math::Vec2;
+
+use effects::Effected;
+use playback::{Controlled, Emitter, PlaybackControls, Voice};
+use output::Output;
+use pool::VoicePool;
+use stream::Probed;
+
+/// plays sounds, allocating a voice for each one up to the voice limit.
+///
+/// once the limit is reached a sound with a lower or equal priority is stopped
+/// to make room for the new one, see [VoiceStealing]
+pub struct AudioPlayer
 {
-    clips : [rodio::Sink; MAX_PLAYABLE_CLIPS],
-    // these dudes need to stay here without dropping or audio playback will no longer work.
-    #[allow(dead_code)] stream : rodio::OutputStream,
-    #[allow(dead_code)] handle : rodio::OutputStreamHandle,
+    pool : Mutex<VoicePool>,
+    mixer : Mixer,
+    listener : Mutex<Listener>,
+    latency : Mutex<Duration>,
+    output : Output,
 }
 
-impl<const MAX_PLAYABLE_CLIPS : usize> Default for AudioPlayer<MAX_PLAYABLE_CLIPS>
+impl Default for AudioPlayer
 {
-    fn default() -> Self 
+    fn default() -> Self
     {
         Self::new()
     }
 }
 
-impl<const MAX_PLAYABLE_CLIPS : usize> AudioPlayer<MAX_PLAYABLE_CLIPS>
-{   
-    /// returns an audio player with the specified amount of simultaneously playable clips.
-    /// setting the value to zero will set it to one instead.
-    /// 
-    /// the max amount of playable clips simoultaneosly is set by the constant value on the struct
+impl AudioPlayer
+{
+    /// the default amount of sounds that can be played at the same time
+    pub const DEFAULT_VOICE_LIMIT: usize = 32;
+
+    /// plays on the default output device, or in silence if there's none
     pub fn new() -> Self
-    {  
-        let (stream, handle) = rodio::OutputStream::try_default().unwrap();
-        let clips = std::array::from_fn(|_| rodio::Sink::try_new(&handle).unwrap());
+    {
+        Self::with_backend(AudioBackend::Default)
+    }
+
+    pub fn with_backend(backend: AudioBackend) -> Self
+    {
+        let mixer = Mixer::default();
+        let output = Output::new(backend, &mixer);
+
+        Self::from_output(mixer, output, Self::DEFAULT_VOICE_LIMIT)
+    }
+
+    /// plays on the default output device,
+    /// returns an error instead of falling back to silence if it can't be opened
+    pub fn try_device() -> Result<Self, AudioError>
+    {
+        let mixer = Mixer::default();
+        let output = Output::device(&mixer)?;
 
-        Self { clips, stream, handle }
+        Ok(Self::from_output(mixer, output, Self::DEFAULT_VOICE_LIMIT))
     }
 
-    /// plays the given audio file,
-    /// if the player has reached the max amount of playable clips
-    /// it will replace the first in the slice.
-    /// 
-    /// panics if the given path doesn't exist
-    pub fn play<P: AsRef<std::path::Path>>(&self, path : P)
+    /// returns an audio player that can play up to `limit` sounds at the same time.
+    /// setting the value to zero will set it to one instead.
+    pub fn with_voice_limit(limit: usize) -> Self
     {
-        let source = rodio::Decoder::new(match std::fs::File::open(path)
+        let mixer = Mixer::default();
+        let output = Output::new(AudioBackend::Default, &mixer);
+
+        Self::from_output(mixer, output, limit)
+    }
+
+    /// the output plays the buses of the mixer
+    fn from_output(mixer: Mixer, output: Output, limit: usize) -> Self
+    {
+        Self
         {
-            Ok(file) => file,
-            Err(err) => panic!("{err}")
-        }).unwrap();
+            pool: Mutex::new(VoicePool::new(limit)),
+            mixer,
+            listener: Mutex::default(),
+            latency: Mutex::new(output.latency()),
+            output
+        }
+    }
 
-        if let Some(sink) = self.clips.iter().find(|sink| sink.empty())
+    /// returns true if the sounds are played in silence,
+    /// because [AudioBackend::Null] was requested or there's no output device
+    pub fn is_silent(&self) -> bool
+    {
+        self.output.is_null()
+    }
+
+    /// the time it takes for a sound to be heard once it's mixed,
+    /// used by the [BeatClock] to stay in sync with what the player hears.
+    ///
+    /// this is only an estimate for the output device,
+    /// use [AudioPlayer::set_output_latency] to calibrate it
+    pub fn output_latency(&self) -> Duration
+    {
+        *self.latency.lock().unwrap()
+    }
+
+    pub fn set_output_latency(&self, latency: Duration)
+    {
+        *self.latency.lock().unwrap() = latency
+    }
+
+    /// the volume of every [Bus]
+    pub fn mixer(&self) -> &Mixer
+    {
+        &self.mixer
+    }
+
+    /// pauses every sound, for example when the app is suspended
+    pub fn pause(&self)
+    {
+        self.mixer.state.paused.store(true, Ordering::Relaxed)
+    }
+
+    /// resumes the sounds after [AudioPlayer::pause],
+    /// sounds paused through their handle stay paused
+    pub fn resume(&self)
+    {
+        self.mixer.state.paused.store(false, Ordering::Relaxed)
+    }
+
+    pub fn is_paused(&self) -> bool
+    {
+        self.mixer.state.is_paused()
+    }
+
+    /// where the positional sounds are heard from
+    pub fn listener(&self) -> Listener
+    {
+        *self.listener.lock().unwrap()
+    }
+
+    /// the listener is applied to the positional sounds in [AudioPlayer::update]
+    pub fn set_listener(&self, listener: Listener)
+    {
+        *self.listener.lock().unwrap() = listener
+    }
+
+    /// moves the listener, usually to the camera position
+    pub fn set_listener_position(&self, position: Vec2)
+    {
+        self.listener.lock().unwrap().position = position
+    }
+
+    /// changes the amount of sounds that can be played at the same time,
+    /// the sounds past the limit are not stopped
+    pub fn set_voice_limit(&self, limit: usize)
+    {
+        self.pool.lock().unwrap().limit = limit.max(1)
+    }
+
+    pub fn voice_limit(&self) -> usize
+    {
+        self.pool.lock().unwrap().limit
+    }
+
+    /// how many sounds are being played
+    pub fn active_voices(&self) -> usize
+    {
+        let mut pool = self.pool.lock().unwrap();
+        pool.collect();
+
+        pool.voices.len()
+    }
+
+    /// chooses which sound is stopped when the voice limit is reached
+    pub fn set_voice_stealing(&self, stealing: VoiceStealing)
+    {
+        self.pool.lock().unwrap().stealing = stealing
+    }
+
+    /// should be called every frame, updates the ducking of the buses
+    /// and the positional sounds
+    pub fn update(&self, delta: Duration)
+    {
+        let mut pool = self.pool.lock().unwrap();
+        pool.collect();
+
+        let listener = self.listener();
+
+        for voice in &pool.voices
+        {
+            voice.listen(&listener)
+        }
+
+        self.mixer.update(delta, |bus| pool.voices.iter().any
+        (
+            |voice| voice.controls.bus == bus && !voice.sink.is_paused()
+        ))
+    }
+
+    /// plays the given clip on [Bus::Sfx] and returns a handle to control it,
+    /// see [AudioPlayer::play_with]
+    pub fn play(&self, clip: &AudioClip) -> PlaybackHandle
+    {
+        self.play_with(clip, PlaySettings::default())
+    }
+
+    /// plays the given clip routed through a [Bus], see [AudioPlayer::play_with]
+    pub fn play_on(&self, clip: &AudioClip, bus: Bus) -> PlaybackHandle
+    {
+        self.play_with(clip, PlaySettings::default().bus(bus))
+    }
+
+    /// plays the given clip at a world position on [Bus::Sfx],
+    /// see [AudioPlayer::play_with]
+    pub fn play_at(&self, clip: &AudioClip, position: Vec2) -> PlaybackHandle
+    {
+        self.play_with(clip, PlaySettings::default().at(position))
+    }
+
+    /// plays the given clip and returns a handle to control it.
+    ///
+    /// if there's no room for the sound, because every sound being played has
+    /// a higher priority or the clip reached its max instances,
+    /// the clip will not be played and the handle will report it as finished.
+    ///
+    /// # example
+    ///
+    /// ```
+    /// use baguette_audio::{AudioBackend, AudioClip, AudioPlayer, Bus, PlaySettings};
+    ///
+    /// let player = AudioPlayer::with_backend(AudioBackend::Null);
+    /// let theme = AudioClip::from_samples(2, 44100, vec![0.; 44100 * 2]);
+    ///
+    /// let handle = player.play_with(&theme, PlaySettings::default().bus(Bus::Music).priority(10).looping(true));
+    /// ```
+    pub fn play_with(&self, clip: &AudioClip, settings: PlaySettings) -> PlaybackHandle
+    {
+        let instances = clip.max_instances().map(|max| (clip.id(), max));
+
+        self.play_source(clip.source(), settings, Some(clip.id()), instances, Duration::ZERO)
+    }
+
+    /// plays an audio file while decoding it, instead of decoding it all at once like [AudioClip] does.
+    /// useful for long tracks that would take a lot of memory once decoded
+    pub fn stream(&self, path: impl AsRef<std::path::Path>, settings: PlaySettings) -> Result<PlaybackHandle, AudioError>
+    {
+        self.stream_from(path, settings, Duration::ZERO)
+    }
+
+    /// same as [AudioPlayer::stream] but when looping the sound restarts from `loop_start`
+    pub(crate) fn stream_from
+    (
+        &self, path: impl AsRef<std::path::Path>, settings: PlaySettings, loop_start: Duration
+    ) -> Result<PlaybackHandle, AudioError>
+    {
+        let file = std::io::BufReader::new(std::fs::File::open(&path)?);
+
+        // the ogg decoder doesn't know the duration, the music crossfades need it
+        let mut probe = std::fs::File::open(&path)?;
+        let decoder = Probed::new(rodio::Decoder::new(file)?.convert_samples::<f32>(), &mut probe);
+
+        Ok(self.play_source(decoder, settings, None, None, loop_start))
+    }
+
+    /// allocates a voice for the source and starts playing it,
+    /// `instances` is the id of the clip and its max instances
+    fn play_source<S>
+    (
+        &self,
+        source: S,
+        settings: PlaySettings,
+        clip: Option<usize>,
+        instances: Option<(usize, usize)>,
+        loop_start: Duration
+    ) -> PlaybackHandle
+    where
+        S: Source<Item = f32> + Send + 'static
+    {
+        let mut pool = self.pool.lock().unwrap();
+
+        let controls = Arc::new(PlaybackControls::new(source.sample_rate(), settings.bus));
+        controls.volume.store(settings.volume.max(0.));
+        controls.pan.store(settings.pan.clamp(-1., 1.));
+        controls.looping.store(settings.looping, Ordering::Relaxed);
+
+        let sink = match pool.reserve(settings.priority, instances, &self.mixer.state)
+        {
+            true => Some(self.output.sink(settings.bus)),
+            false => None
+        };
+
+        let mut voice = Voice
+        {
+            // a sink that is never played, so that the handle is still usable
+            sink: rodio::Sink::new_idle().0,
+            controls: controls.clone(),
+            duration: source.total_duration(),
+            priority: settings.priority,
+            started: Instant::now(),
+            clip,
+            emitter: settings.position.map(|position| Emitter
+            {
+                position: Mutex::new(position), attenuation: settings.attenuation
+            }),
+            effects: EffectChain::default()
+        };
+
+        // the sound needs to start with the right volume
+        voice.listen(&self.listener());
+
+        let Some(sink) = sink
+        else
         {
-            sink.append(source)
+            return PlaybackHandle::new(Arc::new(voice))
+        };
+
+        sink.set_speed(settings.speed.max(0.01));
+        // the effects of the buses are applied once the sounds are mixed, see Output
+        let source = Controlled::new(source, controls, self.mixer.state.clone()).loop_from(loop_start);
+        sink.append(Effected::new(source, [voice.effects.clone()]));
+
+        voice.sink = sink;
+
+        let voice = Arc::new(voice);
+        pool.voices.push(voice.clone());
+
+        PlaybackHandle::new(voice)
+    }
+
+    /// decodes and plays the given audio file,
+    /// prefer loading an [AudioClip] once if you need to play it more than once
+    pub fn play_file<P: AsRef<std::path::Path>>(&self, path : P) -> Result<PlaybackHandle, AudioError>
+    {
+        Ok(self.play(&AudioClip::from_path(path)?))
+    }
+
+    /// stops every sound being played
+    pub fn stop_all(&self)
+    {
+        for voice in self.pool.lock().unwrap().voices.drain(..)
+        {
+            voice.stop()
         }
     }
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests
+{
+    use super::*;
+
+    /// waits until the condition holds, the null output plays in real time
+    fn wait_until(mut condition: impl FnMut() -> bool) -> bool
+    {
+        let deadline = Instant::now() + Duration::from_secs(5);
+
+        while !condition()
+        {
+            if Instant::now() > deadline
+            {
+                return false
+            }
+
+            std::thread::sleep(Duration::from_millis(5))
+        }
+
+        true
+    }
+
+    #[test]
+    fn null_backend_plays_in_real_time()
+    {
+        let player = AudioPlayer::with_backend(AudioBackend::Null);
+        let clip = AudioClip::from_samples(2, 44100, vec![0.5; 44100 / 5 * 2]);
+
+        let started = Instant::now();
+        let handle = player.play(&clip);
+
+        assert_eq!(handle.duration(), Some(Duration::from_millis(200)));
+        assert!(!handle.is_finished());
+
+        assert!(wait_until(|| handle.position() > Duration::ZERO), "the playback never started");
+        assert!(wait_until(|| handle.is_finished()), "the playback never finished");
+
+        assert!(started.elapsed() >= Duration::from_millis(150));
+        assert_eq!(handle.position(), Duration::from_millis(200));
+    }
+
+    #[test]
+    fn null_backend_stops()
+    {
+        let player = AudioPlayer::with_backend(AudioBackend::Null);
+        let clip = AudioClip::from_samples(1, 44100, vec![0.5; 44100 * 10]);
+
+        let handle = player.play(&clip);
+        assert!(wait_until(|| handle.position() > Duration::ZERO));
+
+        handle.stop();
+        assert!(wait_until(|| handle.is_finished()));
+        assert!(handle.position() < Duration::from_secs(10));
+    }
+}
@@ -0,0 +1,288 @@
+use std::sync::{Arc, Mutex};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::Duration;
+
+use crate::effects::EffectChain;
+use crate::playback::AtomicF32;
+
+/// the category a sound is routed through,
+/// every bus is also routed through [Bus::Master]
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Bus
+{
+    Master,
+    Music,
+    #[default]
+    Sfx,
+    Voice,
+    Ui
+}
+
+impl Bus
+{
+    pub const ALL: [Bus; 5] = [Bus::Master, Bus::Music, Bus::Sfx, Bus::Voice, Bus::Ui];
+
+    pub(crate) fn index(self) -> usize
+    {
+        self as usize
+    }
+}
+
+/// lowers the volume of a bus while another one is playing something,
+/// like lowering the music while a character is talking
+#[derive(Debug, Clone, Copy)]
+pub struct Ducking
+{
+    /// the bus that causes the ducking when it's playing
+    pub trigger: Bus,
+    /// the bus being ducked
+    pub target: Bus,
+    /// the volume of the target while ducked
+    pub volume: f32,
+    /// how long it takes to lower the volume
+    pub attack: Duration,
+    /// how long it takes to restore the volume
+    pub release: Duration
+}
+
+impl Default for Ducking
+{
+    fn default() -> Self
+    {
+        Self
+        {
+            trigger: Bus::Voice,
+            target: Bus::Music,
+            volume: 0.3,
+            attack: Duration::from_millis(100),
+            release: Duration::from_millis(500)
+        }
+    }
+}
+
+/// the state of a bus that's read by the audio thread
+struct BusState
+{
+    volume: AtomicF32,
+    muted: AtomicBool,
+    /// the current volume multiplier caused by ducking
+    duck: AtomicF32
+}
+
+impl BusState
+{
+    fn new() -> Self
+    {
+        Self { volume: AtomicF32::new(1.), muted: AtomicBool::new(false), duck: AtomicF32::new(1.) }
+    }
+
+    fn gain(&self) -> f32
+    {
+        match self.muted.load(Ordering::Relaxed)
+        {
+            true => 0.,
+            false => self.volume.load() * self.duck.load()
+        }
+    }
+}
+
+/// the buses shared with the sounds being played
+pub(crate) struct MixerState
+{
+    buses: [BusState; Bus::ALL.len()],
+    /// pauses every sound, they output silence without advancing
+    pub paused: AtomicBool
+}
+
+impl MixerState
+{
+    pub fn is_paused(&self) -> bool
+    {
+        self.paused.load(Ordering::Relaxed)
+    }
+
+    /// the final volume multiplier of a sound routed through this bus
+    pub fn gain(&self, bus: Bus) -> f32
+    {
+        let master = self.buses[Bus::Master.index()].gain();
+
+        match bus
+        {
+            Bus::Master => master,
+            bus => master * self.buses[bus.index()].gain()
+        }
+    }
+}
+
+/// a ducking requested manually with [Mixer::duck]
+#[derive(Clone, Copy)]
+struct ManualDuck
+{
+    volume: f32,
+    fade: Duration
+}
+
+struct DuckingState
+{
+    rules: Vec<Ducking>,
+    manual: [ManualDuck; Bus::ALL.len()]
+}
+
+/// controls the volume of every [Bus]
+pub struct Mixer
+{
+    pub(crate) state: Arc<MixerState>,
+    ducking: Mutex<DuckingState>,
+    effects: [EffectChain; Bus::ALL.len()]
+}
+
+impl Default for Mixer
+{
+    fn default() -> Self
+    {
+        let unducked = ManualDuck { volume: 1., fade: Duration::ZERO };
+
+        Self
+        {
+            state: Arc::new(MixerState { buses: std::array::from_fn(|_| BusState::new()), paused: AtomicBool::new(false) }),
+            ducking: Mutex::new(DuckingState { rules: vec![], manual: [unducked; Bus::ALL.len()] }),
+            effects: Default::default()
+        }
+    }
+}
+
+impl Mixer
+{
+    fn bus(&self, bus: Bus) -> &BusState
+    {
+        &self.state.buses[bus.index()]
+    }
+
+    /// the volume of the bus, `1.` is the original volume
+    pub fn volume(&self, bus: Bus) -> f32
+    {
+        self.bus(bus).volume.load()
+    }
+
+    pub fn set_volume(&self, bus: Bus, volume: f32)
+    {
+        self.bus(bus).volume.store(volume.max(0.))
+    }
+
+    pub fn is_muted(&self, bus: Bus) -> bool
+    {
+        self.bus(bus).muted.load(Ordering::Relaxed)
+    }
+
+    /// silences the bus without changing its volume
+    pub fn set_muted(&self, bus: Bus, muted: bool)
+    {
+        self.bus(bus).muted.store(muted, Ordering::Relaxed)
+    }
+
+    /// the final volume multiplier of a sound routed through this bus,
+    /// taking into account the master volume, muting and ducking
+    pub fn gain(&self, bus: Bus) -> f32
+    {
+        self.state.gain(bus)
+    }
+
+    /// the effects applied to the sounds of the bus once they're mixed together,
+    /// the ones on [Bus::Master] are applied to the whole mix.
+    ///
+    /// a limiter on [Bus::Master] keeps overlapping sounds from clipping
+    pub fn effects(&self, bus: Bus) -> &EffectChain
+    {
+        &self.effects[bus.index()]
+    }
+
+    /// lowers the volume of the bus to `volume` over `fade`,
+    /// until [Mixer::unduck] is called
+    pub fn duck(&self, bus: Bus, volume: f32, fade: Duration)
+    {
+        self.ducking.lock().unwrap().manual[bus.index()] = ManualDuck { volume: volume.clamp(0., 1.), fade }
+    }
+
+    /// restores the volume of a bus ducked with [Mixer::duck] over `fade`
+    pub fn unduck(&self, bus: Bus, fade: Duration)
+    {
+        self.ducking.lock().unwrap().manual[bus.index()] = ManualDuck { volume: 1., fade }
+    }
+
+    /// adds a rule that ducks a bus whenever another bus is playing something
+    ///
+    /// # example
+    ///
+    /// ```
+    /// use baguette_audio::{AudioBackend, AudioPlayer, Bus, Ducking};
+    ///
+    /// let player = AudioPlayer::with_backend(AudioBackend::Null);
+    ///
+    /// // lower the music while a character is talking
+    /// player.mixer().add_ducking(Ducking { trigger: Bus::Voice, target: Bus::Music, ..Default::default() });
+    /// ```
+    pub fn add_ducking(&self, ducking: Ducking)
+    {
+        self.ducking.lock().unwrap().rules.push(ducking)
+    }
+
+    /// removes every ducking rule targeting this bus
+    pub fn clear_ducking(&self, bus: Bus)
+    {
+        self.ducking.lock().unwrap().rules.retain(|rule| rule.target != bus)
+    }
+
+    /// moves the ducking of every bus towards its target,
+    /// `playing` tells whether a bus is currently playing something
+    pub(crate) fn update(&self, delta: Duration, playing: impl Fn(Bus) -> bool)
+    {
+        let ducking = self.ducking.lock().unwrap();
+
+        for bus in Bus::ALL
+        {
+            let manual = ducking.manual[bus.index()];
+
+            let mut target = manual.volume;
+            let mut attack = manual.fade;
+            let mut release = manual.fade;
+
+            for rule in ducking.rules.iter().filter(|rule| rule.target == bus)
+            {
+                if playing(rule.trigger) && rule.volume < target
+                {
+                    target = rule.volume;
+                    attack = rule.attack;
+                }
+
+                release = release.max(rule.release)
+            }
+
+            let state = self.bus(bus);
+            let current = state.duck.load();
+
+            let fade = match target < current
+            {
+                true => attack,
+                false => release
+            };
+
+            // the fade is the time it takes to go from silent to the full volume
+            let next = match fade.is_zero()
+            {
+                true => target,
+                false =>
+                {
+                    let step = delta.as_secs_f32() / fade.as_secs_f32();
+
+                    match target < current
+                    {
+                        true => f32::max(current - step, target),
+                        false => f32::min(current + step, target)
+                    }
+                }
+            };
+
+            state.duck.store(next)
+        }
+    }
+}
@@ -0,0 +1,448 @@
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use crate::{AudioPlayer, BeatClock, BeatEvent, Bus, PlaySettings, PlaybackHandle, Tempo};
+
+/// a music track, streamed from its file while it's played
+#[derive(Debug, Clone, PartialEq)]
+pub struct Track
+{
+    path: PathBuf,
+    looping: bool,
+    /// where the track restarts when looping, the part before it is the intro
+    loop_start: Duration,
+    volume: f32,
+    tempo: Option<Tempo>
+}
+
+impl Track
+{
+    /// a track that is played once
+    pub fn new(path: impl Into<PathBuf>) -> Self
+    {
+        Self { path: path.into(), looping: false, loop_start: Duration::ZERO, volume: 1., tempo: None }
+    }
+
+    /// restarts the track once it ends
+    pub fn looping(mut self) -> Self
+    {
+        self.looping = true;
+        self
+    }
+
+    /// plays the intro once and then loops the rest of the track from `loop_start`
+    ///
+    /// # example
+    ///
+    /// ```
+    /// use std::time::Duration;
+    /// use baguette_audio::Track;
+    ///
+    /// let boss = Track::new("assets/boss.ogg").with_intro(Duration::from_secs_f32(12.5));
+    /// ```
+    pub fn with_intro(mut self, loop_start: Duration) -> Self
+    {
+        self.looping = true;
+        self.loop_start = loop_start;
+        self
+    }
+
+    pub fn volume(mut self, volume: f32) -> Self
+    {
+        self.volume = volume.max(0.);
+        self
+    }
+
+    /// the tempo of the track, the [MusicPlayer] emits its beats while it's played
+    ///
+    /// # example
+    ///
+    /// ```
+    /// use std::time::Duration;
+    /// use baguette_audio::{Tempo, Track};
+    ///
+    /// let track = Track::new("assets/dance.ogg").with_tempo(Tempo::new(120.).offset(Duration::from_millis(250)));
+    /// ```
+    pub fn with_tempo(mut self, tempo: Tempo) -> Self
+    {
+        self.tempo = Some(tempo);
+        self
+    }
+
+    pub fn tempo(&self) -> Option<Tempo>
+    {
+        self.tempo
+    }
+
+    pub fn path(&self) -> &Path
+    {
+        &self.path
+    }
+
+    pub fn is_looping(&self) -> bool
+    {
+        self.looping
+    }
+}
+
+/// moves the volume of a sound over time
+struct Fade
+{
+    handle: PlaybackHandle,
+    from: f32,
+    to: f32,
+    duration: Duration,
+    elapsed: Duration
+}
+
+impl Fade
+{
+    fn new(handle: PlaybackHandle, to: f32, duration: Duration) -> Self
+    {
+        Self { from: handle.volume(), handle, to, duration, elapsed: Duration::ZERO }
+    }
+
+    /// returns true once the fade is over
+    fn advance(&mut self, delta: Duration) -> bool
+    {
+        self.elapsed += delta;
+
+        let t = match self.duration.is_zero()
+        {
+            true => 1.,
+            false => f32::min(self.elapsed.as_secs_f32() / self.duration.as_secs_f32(), 1.)
+        };
+
+        self.handle.set_volume(self.from + (self.to - self.from) * t);
+
+        t >= 1.
+    }
+}
+
+enum MusicRequest
+{
+    Play(Track, Duration),
+    Stop(Duration)
+}
+
+/// plays music tracks on [Bus::Music], crossfading between them.
+///
+/// the player only applies the requests in [MusicPlayer::update],
+/// so it needs to be called every frame
+pub struct MusicPlayer
+{
+    current: Option<(Track, PlaybackHandle)>,
+    /// follows the current track if it has a tempo
+    beat_clock: Option<BeatClock>,
+    fading_in: Option<Fade>,
+    fading_out: Vec<Fade>,
+    request: Option<MusicRequest>,
+
+    playlist: Vec<Track>,
+    /// the order the playlist is played in, shuffled if [MusicPlayer::set_shuffle] is set
+    order: Vec<usize>,
+    /// the position in `order` of the next track
+    next: usize,
+    shuffle: bool,
+    /// how many tracks in a row failed to play
+    failures: usize,
+
+    crossfade: Duration
+}
+
+impl Default for MusicPlayer
+{
+    fn default() -> Self
+    {
+        Self
+        {
+            current: None,
+            beat_clock: None,
+            fading_in: None,
+            fading_out: vec![],
+            request: None,
+            playlist: vec![],
+            order: vec![],
+            next: 0,
+            shuffle: false,
+            failures: 0,
+            crossfade: Duration::from_secs(2)
+        }
+    }
+}
+
+impl MusicPlayer
+{
+    pub fn new() -> Self
+    {
+        Self::default()
+    }
+
+    /// the default crossfade used by [MusicPlayer::play] and the playlist
+    pub fn crossfade(&self) -> Duration
+    {
+        self.crossfade
+    }
+
+    pub fn set_crossfade(&mut self, crossfade: Duration)
+    {
+        self.crossfade = crossfade
+    }
+
+    /// crossfades to this track with the default crossfade,
+    /// nothing happens if the track is already playing.
+    ///
+    /// this stops the playlist
+    pub fn play(&mut self, track: Track)
+    {
+        self.crossfade_to(track, self.crossfade)
+    }
+
+    /// crossfades to this track over `duration`,
+    /// nothing happens if the track is already playing.
+    ///
+    /// this stops the playlist
+    pub fn crossfade_to(&mut self, track: Track, duration: Duration)
+    {
+        self.playlist.clear();
+        self.request = Some(MusicRequest::Play(track, duration))
+    }
+
+    /// fades out the music over `fade` and stops the playlist
+    pub fn stop(&mut self, fade: Duration)
+    {
+        self.playlist.clear();
+        self.request = Some(MusicRequest::Stop(fade))
+    }
+
+    /// the track being played
+    pub fn current(&self) -> Option<&Track>
+    {
+        self.current.as_ref().map(|(track, _)| track)
+    }
+
+    /// the handle of the track being played
+    pub fn handle(&self) -> Option<&PlaybackHandle>
+    {
+        self.current.as_ref().map(|(_, handle)| handle)
+    }
+
+    /// the beat clock of the current track, if it has a [Tempo]
+    pub fn beat_clock(&self) -> Option<&BeatClock>
+    {
+        self.beat_clock.as_ref()
+    }
+
+    /// the beats of the current track heard during the last update
+    pub fn beats(&self) -> &[BeatEvent]
+    {
+        self.beat_clock.as_ref().map_or(&[], |clock| clock.events())
+    }
+
+    /// plays these tracks one after the other, crossfading between them.
+    /// the playlist restarts once the last track ends.
+    ///
+    /// looping tracks are played only once when in a playlist.
+    ///
+    /// the next track starts `crossfade` before the end of the current one, which needs its duration,
+    /// ogg, wav and flac files know it. an mp3 track is only followed once it ended, without a crossfade
+    pub fn set_playlist(&mut self, tracks: impl IntoIterator<Item = Track>)
+    {
+        self.playlist = tracks.into_iter().map(|track| Track { looping: false, ..track }).collect();
+        self.failures = 0;
+        self.next = 0;
+        self.shuffle_order();
+
+        if let Some(track) = self.next_track()
+        {
+            self.request = Some(MusicRequest::Play(track, self.crossfade))
+        }
+    }
+
+    pub fn playlist(&self) -> &[Track]
+    {
+        &self.playlist
+    }
+
+    /// plays the playlist in a random order, reshuffled every time it restarts
+    pub fn set_shuffle(&mut self, shuffle: bool)
+    {
+        self.shuffle = shuffle;
+        self.shuffle_order()
+    }
+
+    /// crossfades to the next track of the playlist
+    pub fn skip(&mut self)
+    {
+        if let Some(track) = self.next_track()
+        {
+            self.request = Some(MusicRequest::Play(track, self.crossfade))
+        }
+    }
+
+    fn shuffle_order(&mut self)
+    {
+        self.order = (0..self.playlist.len()).collect();
+
+        if self.shuffle
+        {
+            let last = self.current().cloned();
+
+            baguette_math::rand::shuffle(&mut self.order);
+
+            // don't play the same track twice in a row when the playlist restarts
+            if self.order.len() > 1 && last.as_ref() == Some(&self.playlist[self.order[0]])
+            {
+                self.order.swap(0, 1)
+            }
+        }
+    }
+
+    fn next_track(&mut self) -> Option<Track>
+    {
+        if self.playlist.is_empty()
+        {
+            return None
+        }
+
+        if self.next >= self.order.len()
+        {
+            self.next = 0;
+            self.shuffle_order()
+        }
+
+        let track = self.playlist[self.order[self.next]].clone();
+        self.next += 1;
+
+        Some(track)
+    }
+
+    /// applies the requests and updates the fades,
+    /// should be called every frame
+    pub fn update(&mut self, player: &AudioPlayer, delta: Duration)
+    {
+        match self.request.take()
+        {
+            Some(MusicRequest::Play(track, fade)) if self.current() != Some(&track) || self.is_finished() =>
+            {
+                self.start(player, track, fade)
+            }
+            Some(MusicRequest::Stop(fade)) => self.fade_out_current(fade),
+            _ => ()
+        }
+
+        if self.fading_in.as_mut().is_some_and(|fade| fade.advance(delta))
+        {
+            self.fading_in = None
+        }
+
+        self.fading_out.retain_mut(|fade| match fade.advance(delta) || fade.handle.is_finished()
+        {
+            true =>
+            {
+                fade.handle.stop();
+                false
+            }
+            false => true
+        });
+
+        if let Some(clock) = &mut self.beat_clock
+        {
+            clock.set_latency(player.output_latency());
+            clock.update(delta)
+        }
+
+        if self.playlist.is_empty()
+        {
+            return
+        }
+
+        if self.current.is_none() || self.is_ending()
+        {
+            if let Some(track) = self.next_track()
+            {
+                self.start(player, track, self.crossfade)
+            }
+        }
+    }
+
+    /// returns true if there's no track or it ended
+    fn is_finished(&self) -> bool
+    {
+        self.handle().is_none_or(|handle| handle.is_finished())
+    }
+
+    /// returns true if the current track is close enough to its end to start the next one
+    fn is_ending(&self) -> bool
+    {
+        let Some((track, handle)) = &self.current
+        else
+        {
+            return false
+        };
+
+        if handle.is_finished()
+        {
+            return true
+        }
+
+        match (track.looping, handle.duration())
+        {
+            (false, Some(duration)) => duration.saturating_sub(handle.position()) <= self.crossfade,
+            _ => false
+        }
+    }
+
+    fn fade_out_current(&mut self, fade: Duration)
+    {
+        self.fading_in = None;
+        self.beat_clock = None;
+
+        if let Some((_, handle)) = self.current.take()
+        {
+            self.fading_out.push(Fade::new(handle, 0., fade))
+        }
+    }
+
+    fn start(&mut self, player: &AudioPlayer, track: Track, fade: Duration)
+    {
+        // music is never stopped to make room for other sounds
+        let settings = PlaySettings::default()
+            .bus(Bus::Music)
+            .priority(i32::MAX)
+            .volume(0.)
+            .looping(track.looping);
+
+        match player.stream_from(&track.path, settings, track.loop_start)
+        {
+            Ok(handle) =>
+            {
+                self.fade_out_current(fade);
+                self.failures = 0;
+
+                self.fading_in = Some(Fade::new(handle.clone(), track.volume, fade));
+
+                self.beat_clock = track.tempo.map(|tempo|
+                {
+                    let mut clock = BeatClock::new(tempo).with_latency(player.output_latency());
+                    clock.follow(handle.clone());
+                    clock
+                });
+
+                self.current = Some((track, handle))
+            }
+            Err(err) =>
+            {
+                log::error!("failed to play {}: {err}", track.path.display());
+
+                // skip the track, unless every track in the playlist failed
+                self.failures += 1;
+
+                if self.failures >= self.playlist.len()
+                {
+                    self.playlist.clear()
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,288 @@
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::{Duration, Instant};
+
+use rodio::dynamic_mixer::{self, DynamicMixerController};
+use rodio::Source;
+
+use crate::{AudioError, Bus, Mixer};
+use crate::effects::Effected;
+
+/// where the sounds are played
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum AudioBackend
+{
+    /// the default output device,
+    /// falls back to [AudioBackend::Null] if there's none or it can't be opened
+    #[default]
+    Default,
+    /// plays the sounds in silence, they still advance in real time and finish.
+    /// useful on servers, in tests and on machines without a sound card
+    Null
+}
+
+/// the output the sinks are played on.
+///
+/// the sounds of each [Bus] are mixed together and go through the effects of the bus,
+/// then every bus is mixed into [Bus::Master] and goes through its effects before being played
+pub(crate) struct Output
+{
+    /// where the sounds of each bus are mixed
+    buses: [Arc<DynamicMixerController<f32>>; Bus::ALL.len()],
+    device: Device
+}
+
+enum Device
+{
+    Stream
+    {
+        // this dude needs to stay here without dropping or audio playback will no longer work.
+        _stream: rodio::OutputStream
+    },
+    /// stops its thread once dropped
+    Null { _null: NullOutput }
+}
+
+impl Output
+{
+    /// the usual size of the device buffer
+    const DEVICE_LATENCY: Duration = Duration::from_millis(50);
+
+    const CHANNELS: u16 = 2;
+    /// used when the rate of the device is unknown
+    const SAMPLE_RATE: u32 = 44100;
+
+    pub fn new(backend: AudioBackend, mixer: &Mixer) -> Self
+    {
+        match backend
+        {
+            AudioBackend::Default => Self::device(mixer).unwrap_or_else(|err|
+            {
+                log::warn!("{err}, the audio will be silent");
+                Self::null(mixer)
+            }),
+            AudioBackend::Null => Self::null(mixer)
+        }
+    }
+
+    pub fn device(mixer: &Mixer) -> Result<Self, AudioError>
+    {
+        use rodio::cpal::traits::{DeviceTrait, HostTrait};
+
+        let (stream, handle) = rodio::OutputStream::try_default()?;
+
+        // mixing the buses at the rate of the device avoids resampling them again
+        let sample_rate = rodio::cpal::default_host()
+            .default_output_device()
+            .and_then(|device| device.default_output_config().ok())
+            .map_or(Self::SAMPLE_RATE, |config| config.sample_rate().0);
+
+        let (buses, master) = Self::route(mixer, sample_rate);
+
+        handle.play_raw(master).map_err(|_| rodio::StreamError::NoDevice)?;
+
+        Ok(Self { buses, device: Device::Stream { _stream: stream } })
+    }
+
+    fn null(mixer: &Mixer) -> Self
+    {
+        let (buses, master) = Self::route(mixer, Self::SAMPLE_RATE);
+
+        Self { buses, device: Device::Null { _null: NullOutput::new(master) } }
+    }
+
+    /// creates the mixers of the buses, returns them and the output of the master bus
+    fn route(mixer: &Mixer, sample_rate: u32) -> ([Arc<DynamicMixerController<f32>>; Bus::ALL.len()], impl Source<Item = f32> + Send)
+    {
+        let (master, master_output) = dynamic_mixer::mixer::<f32>(Self::CHANNELS, sample_rate);
+
+        let buses = Bus::ALL.map(|bus| match bus
+        {
+            Bus::Master => master.clone(),
+            bus =>
+            {
+                let (input, output) = dynamic_mixer::mixer::<f32>(Self::CHANNELS, sample_rate);
+                master.add(Effected::new(Endless(output), [mixer.effects(bus).clone()]));
+
+                input
+            }
+        });
+
+        (buses, Effected::new(Endless(master_output), [mixer.effects(Bus::Master).clone()]))
+    }
+
+    pub fn is_null(&self) -> bool
+    {
+        matches!(self.device, Device::Null { .. })
+    }
+
+    /// an estimate of the time it takes for a sample to be heard once it's mixed,
+    /// rodio doesn't expose the size of the device buffer
+    pub fn latency(&self) -> Duration
+    {
+        match self.device
+        {
+            Device::Stream { .. } => Self::DEVICE_LATENCY,
+            Device::Null { .. } => Duration::ZERO
+        }
+    }
+
+    /// returns a sink that plays on this bus
+    pub fn sink(&self, bus: Bus) -> rodio::Sink
+    {
+        let (sink, output) = rodio::Sink::new_idle();
+        self.buses[bus.index()].add(output);
+
+        sink
+    }
+}
+
+/// keeps a bus playing silence while it has no sounds,
+/// the mixer ends when it's empty and would be dropped by the master bus
+struct Endless<S>(S);
+
+impl<S> Iterator for Endless<S>
+where
+    S: Source<Item = f32>
+{
+    type Item = f32;
+
+    fn next(&mut self) -> Option<f32>
+    {
+        Some(self.0.next().unwrap_or(0.))
+    }
+}
+
+impl<S> Source for Endless<S>
+where
+    S: Source<Item = f32>
+{
+    fn current_frame_len(&self) -> Option<usize>
+    {
+        None
+    }
+
+    fn channels(&self) -> u16
+    {
+        self.0.channels()
+    }
+
+    fn sample_rate(&self) -> u32
+    {
+        self.0.sample_rate()
+    }
+
+    fn total_duration(&self) -> Option<Duration>
+    {
+        None
+    }
+}
+
+/// consumes the sounds in real time without playing them
+pub(crate) struct NullOutput
+{
+    running: Arc<AtomicBool>
+}
+
+impl NullOutput
+{
+    /// how often the sounds are advanced
+    const TICK: Duration = Duration::from_millis(10);
+
+    fn new(mut master: impl Source<Item = f32> + Send + 'static) -> Self
+    {
+        let running = Arc::new(AtomicBool::new(true));
+
+        let thread_running = running.clone();
+
+        std::thread::Builder::new()
+            .name("baguette null audio".into())
+            .spawn(move ||
+            {
+                let samples_per_second = master.sample_rate() as f64 * master.channels() as f64;
+
+                let start = Instant::now();
+                let mut consumed = 0u64;
+
+                while thread_running.load(Ordering::Relaxed)
+                {
+                    std::thread::sleep(Self::TICK);
+
+                    // keep up with the real time even if the thread slept longer than requested
+                    let due = (start.elapsed().as_secs_f64() * samples_per_second) as u64;
+
+                    for _ in consumed..due
+                    {
+                        master.next();
+                    }
+
+                    consumed = due
+                }
+            })
+            .expect("failed to spawn the null audio thread");
+
+        Self { running }
+    }
+}
+
+impl Drop for NullOutput
+{
+    fn drop(&mut self)
+    {
+        self.running.store(false, Ordering::Relaxed)
+    }
+}
+
+#[cfg(test)]
+mod tests
+{
+    use rodio::buffer::SamplesBuffer;
+
+    use crate::Effect;
+    use super::*;
+
+    /// plays two loud sounds at the same time on the sfx bus and returns the mix
+    fn overlapping(mixer: &Mixer) -> Vec<f32>
+    {
+        let (buses, master) = Output::route(mixer, 1000);
+
+        for _ in 0..2
+        {
+            buses[Bus::Sfx.index()].add(SamplesBuffer::new(2, 1000, vec![0.8; 400]));
+        }
+
+        master.take(400).collect()
+    }
+
+    #[test]
+    fn buses_are_mixed_into_the_master()
+    {
+        let output = overlapping(&Mixer::default());
+
+        assert!(output.iter().any(|sample| (sample - 1.6).abs() < 1e-6));
+    }
+
+    #[test]
+    fn master_limiter_applies_to_the_mix()
+    {
+        let mixer = Mixer::default();
+        mixer.effects(Bus::Master).push(Effect::limiter(-6.));
+
+        let ceiling = 10f32.powf(-6. / 20.);
+        let output = overlapping(&mixer);
+
+        assert!(output.iter().all(|sample| sample.abs() <= ceiling + 1e-6));
+        assert!(output.iter().any(|sample| sample.abs() > ceiling * 0.9));
+    }
+
+    #[test]
+    fn bus_effects_only_apply_to_their_bus()
+    {
+        let mixer = Mixer::default();
+        mixer.effects(Bus::Music).push(Effect::limiter(-6.));
+
+        let output = overlapping(&mixer);
+
+        assert!(output.iter().any(|sample| (sample - 1.6).abs() < 1e-6));
+    }
+}
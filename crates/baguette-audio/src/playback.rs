@@ -0,0 +1,478 @@
+use std::sync::{Arc, Mutex};
+use std::sync::atomic::{AtomicBool, AtomicU32, AtomicU64, Ordering};
+use std::time::{Duration, Instant};
+
+use rodio::Source;
+
+use baguette_math::Vec2;
+
+use crate::effects::EffectChain;
+use crate::mixer::{Bus, MixerState};
+use crate::spatial::Attenuation;
+
+/// an `f32` that can be shared with the audio thread
+#[derive(Default)]
+pub(crate) struct AtomicF32(AtomicU32);
+
+impl AtomicF32
+{
+    pub fn new(value: f32) -> Self
+    {
+        Self(AtomicU32::new(value.to_bits()))
+    }
+
+    #[inline]
+    pub fn load(&self) -> f32
+    {
+        f32::from_bits(self.0.load(Ordering::Relaxed))
+    }
+
+    #[inline]
+    pub fn store(&self, value: f32)
+    {
+        self.0.store(value.to_bits(), Ordering::Relaxed)
+    }
+}
+
+/// the values shared between a [PlaybackHandle] and the source being played
+pub(crate) struct PlaybackControls
+{
+    pub volume: AtomicF32,
+    /// stereo balance between `-1.` (left) and `1.` (right)
+    pub pan: AtomicF32,
+    pub looping: AtomicBool,
+    pub stopped: AtomicBool,
+    /// how many frames have been sent to the output since the start of the source
+    pub frames_played: AtomicU64,
+    pub sample_rate: u32,
+    pub bus: Bus,
+    /// the volume multiplier caused by the distance from the listener
+    pub spatial_gain: AtomicF32,
+    /// the stereo balance caused by the position relative to the listener
+    pub spatial_pan: AtomicF32
+}
+
+impl PlaybackControls
+{
+    pub fn new(sample_rate: u32, bus: Bus) -> Self
+    {
+        Self
+        {
+            volume: AtomicF32::new(1.),
+            pan: AtomicF32::new(0.),
+            looping: AtomicBool::new(false),
+            stopped: AtomicBool::new(false),
+            frames_played: AtomicU64::new(0),
+            sample_rate,
+            bus,
+            spatial_gain: AtomicF32::new(1.),
+            spatial_pan: AtomicF32::new(0.)
+        }
+    }
+}
+
+/// a sound being played by the [crate::AudioPlayer]
+pub(crate) struct Voice
+{
+    pub sink: rodio::Sink,
+    pub controls: Arc<PlaybackControls>,
+    /// the length of the source, [None] if it's unknown
+    pub duration: Option<Duration>,
+    pub priority: i32,
+    pub started: Instant,
+    /// the id of the clip being played, used to limit its instances
+    pub clip: Option<usize>,
+    /// the world position of the sound, if it's positional
+    pub emitter: Option<Emitter>,
+    pub effects: EffectChain
+}
+
+/// a positional sound, its volume and panning are updated every frame
+/// from the position of the listener
+pub(crate) struct Emitter
+{
+    pub position: Mutex<Vec2>,
+    pub attenuation: Attenuation
+}
+
+impl Voice
+{
+    /// returns true once the source ended or was stopped
+    pub fn is_finished(&self) -> bool
+    {
+        self.sink.empty()
+    }
+
+    pub fn stop(&self)
+    {
+        self.controls.stopped.store(true, Ordering::Relaxed);
+        self.sink.stop()
+    }
+
+    /// how loud the sound is heard
+    pub fn loudness(&self, mixer: &MixerState) -> f32
+    {
+        self.controls.volume.load() * self.controls.spatial_gain.load() * mixer.gain(self.controls.bus)
+    }
+
+    /// updates the volume and panning of a positional sound
+    pub fn listen(&self, listener: &crate::Listener)
+    {
+        let Some(emitter) = &self.emitter
+        else
+        {
+            return
+        };
+
+        let (gain, pan) = listener.hear(*emitter.position.lock().unwrap(), &emitter.attenuation);
+
+        self.controls.spatial_gain.store(gain);
+        self.controls.spatial_pan.store(pan)
+    }
+}
+
+/// controls a sound after it started playing,
+/// dropping the handle doesn't stop the sound
+#[derive(Clone)]
+pub struct PlaybackHandle
+{
+    pub(crate) voice: Arc<Voice>
+}
+
+impl PlaybackHandle
+{
+    pub(crate) fn new(voice: Arc<Voice>) -> Self
+    {
+        Self { voice }
+    }
+
+    /// the volume of this sound, `1.` is the original volume
+    pub fn volume(&self) -> f32
+    {
+        self.voice.controls.volume.load()
+    }
+
+    pub fn set_volume(&self, volume: f32)
+    {
+        self.voice.controls.volume.store(volume.max(0.))
+    }
+
+    /// the playback speed, changing it changes the pitch too.
+    /// `1.` is the original speed
+    pub fn speed(&self) -> f32
+    {
+        self.voice.sink.speed()
+    }
+
+    pub fn set_speed(&self, speed: f32)
+    {
+        self.voice.sink.set_speed(speed.max(0.01))
+    }
+
+    /// the stereo balance between `-1.` (left) and `1.` (right)
+    pub fn pan(&self) -> f32
+    {
+        self.voice.controls.pan.load()
+    }
+
+    pub fn set_pan(&self, pan: f32)
+    {
+        self.voice.controls.pan.store(pan.clamp(-1., 1.))
+    }
+
+    pub fn pause(&self)
+    {
+        self.voice.sink.pause()
+    }
+
+    pub fn resume(&self)
+    {
+        self.voice.sink.play()
+    }
+
+    pub fn is_paused(&self) -> bool
+    {
+        self.voice.sink.is_paused()
+    }
+
+    /// stops the sound, it can't be resumed afterwards
+    pub fn stop(&self)
+    {
+        self.voice.stop()
+    }
+
+    /// moves the playback to this position, saturating at the end of the sound
+    pub fn seek(&self, position: Duration) -> Result<(), rodio::source::SeekError>
+    {
+        self.voice.sink.try_seek(position)
+    }
+
+    /// when looping the sound restarts once it reaches the end,
+    /// until looping is disabled or it's stopped
+    pub fn set_looping(&self, looping: bool)
+    {
+        self.voice.controls.looping.store(looping, Ordering::Relaxed)
+    }
+
+    pub fn is_looping(&self) -> bool
+    {
+        self.voice.controls.looping.load(Ordering::Relaxed)
+    }
+
+    /// returns true once the sound ended or was stopped
+    pub fn is_finished(&self) -> bool
+    {
+        self.voice.is_finished()
+    }
+
+    /// the position of the playback from the start of the sound,
+    /// counted from the samples that were mixed so it's exact but moves in steps.
+    ///
+    /// the samples are heard a bit later, see [crate::AudioPlayer::output_latency],
+    /// use a [crate::BeatClock] to follow the music smoothly
+    pub fn position(&self) -> Duration
+    {
+        let controls = &self.voice.controls;
+        let frames = controls.frames_played.load(Ordering::Relaxed);
+
+        Duration::from_secs_f64(frames as f64 / controls.sample_rate as f64)
+    }
+
+    /// the length of the sound, [None] if it's unknown
+    pub fn duration(&self) -> Option<Duration>
+    {
+        self.voice.duration
+    }
+
+    /// the world position of a positional sound, [None] if it isn't positional
+    pub fn world_position(&self) -> Option<Vec2>
+    {
+        self.voice.emitter.as_ref().map(|emitter| *emitter.position.lock().unwrap())
+    }
+
+    /// moves a positional sound, its volume and panning are updated in [crate::AudioPlayer::update].
+    ///
+    /// does nothing if the sound wasn't played at a position
+    pub fn set_world_position(&self, position: Vec2)
+    {
+        if let Some(emitter) = &self.voice.emitter
+        {
+            *emitter.position.lock().unwrap() = position
+        }
+    }
+
+    /// the effects applied only to this sound, before the ones of its bus
+    pub fn effects(&self) -> &EffectChain
+    {
+        &self.voice.effects
+    }
+
+    /// the bus this sound is routed through
+    pub fn bus(&self) -> Bus
+    {
+        self.voice.controls.bus
+    }
+}
+
+/// how much the gain moves towards its target every frame,
+/// avoids clicks when the volume changes suddenly
+const GAIN_SMOOTHING: f32 = 0.005;
+
+/// applies the [PlaybackControls] and the volume of its bus to a source.
+///
+/// mono sources are turned into stereo so that they can be panned
+pub(crate) struct Controlled<S>
+{
+    inner: S,
+    controls: Arc<PlaybackControls>,
+    mixer: Arc<MixerState>,
+    /// the smoothed volume multiplier
+    gain: f32,
+    /// the channels of the inner source
+    channels: u16,
+    /// the channel of the next sample we output
+    channel: u16,
+    /// the frame being output, a frame holds one sample per channel
+    frame: [f32; 2],
+    /// how many silent samples are left to complete the current frame while paused
+    silence: u16,
+    /// where the source restarts when looping
+    loop_start: Duration
+}
+
+impl<S> Controlled<S>
+where
+    S: Source<Item = f32>
+{
+    pub fn new(inner: S, controls: Arc<PlaybackControls>, mixer: Arc<MixerState>) -> Self
+    {
+        let channels = inner.channels();
+        let gain = controls.volume.load() * controls.spatial_gain.load() * mixer.gain(controls.bus);
+
+        Self { inner, controls, mixer, gain, channels, channel: 0, frame: [0.; 2], silence: 0, loop_start: Duration::ZERO }
+    }
+
+    /// moves the gain towards the current volume, should be called once per frame
+    fn next_gain(&mut self) -> f32
+    {
+        let controls = &self.controls;
+        let target = controls.volume.load() * controls.spatial_gain.load() * self.mixer.gain(controls.bus);
+        self.gain += (target - self.gain) * GAIN_SMOOTHING;
+
+        self.gain
+    }
+
+    /// sets where the source restarts when looping
+    pub fn loop_from(mut self, loop_start: Duration) -> Self
+    {
+        self.loop_start = loop_start;
+        self
+    }
+
+    /// returns the next sample of the inner source, rewinding it if it ended and we are looping
+    fn next_inner(&mut self) -> Option<f32>
+    {
+        match self.inner.next()
+        {
+            Some(sample) => Some(sample),
+            None if self.controls.looping.load(Ordering::Relaxed) =>
+            {
+                self.inner.try_seek(self.loop_start).ok()?;
+
+                let rate = self.controls.sample_rate as f64;
+                self.controls.frames_played.store((self.loop_start.as_secs_f64() * rate) as u64, Ordering::Relaxed);
+
+                self.inner.next()
+            }
+            None => None
+        }
+    }
+
+    fn output_channels(&self) -> u16
+    {
+        match self.channels
+        {
+            1 => 2,
+            channels => channels
+        }
+    }
+}
+
+impl<S> Iterator for Controlled<S>
+where
+    S: Source<Item = f32>
+{
+    type Item = f32;
+
+    fn next(&mut self) -> Option<f32>
+    {
+        if self.controls.stopped.load(Ordering::Relaxed)
+        {
+            return None
+        }
+
+        if self.silence > 0
+        {
+            self.silence -= 1;
+            return Some(0.)
+        }
+
+        // the player is paused, output whole silent frames so that the channels stay aligned
+        if self.channel == 0 && self.mixer.is_paused()
+        {
+            self.silence = self.output_channels() - 1;
+            return Some(0.)
+        }
+
+        // sources with more than two channels are not panned
+        if self.channels > 2
+        {
+            if self.channel == 0
+            {
+                self.next_gain();
+            }
+
+            let sample = self.next_inner()? * self.gain;
+
+            self.channel = (self.channel + 1) % self.channels;
+
+            if self.channel == 0
+            {
+                self.controls.frames_played.fetch_add(1, Ordering::Relaxed);
+            }
+
+            return Some(sample)
+        }
+
+        if self.channel == 0
+        {
+            let left = self.next_inner()?;
+            let right = match self.channels
+            {
+                1 => left,
+                _ => self.next_inner()?
+            };
+
+            let pan = (self.controls.pan.load() + self.controls.spatial_pan.load()).clamp(-1., 1.);
+            let volume = self.next_gain();
+
+            // balance panning, the centered sound keeps its original volume
+            self.frame =
+            [
+                left * volume * f32::min(1., 1. - pan),
+                right * volume * f32::min(1., 1. + pan)
+            ];
+
+            self.controls.frames_played.fetch_add(1, Ordering::Relaxed);
+        }
+
+        let sample = self.frame[self.channel as usize];
+        self.channel = (self.channel + 1) % 2;
+
+        Some(sample)
+    }
+}
+
+impl<S> Source for Controlled<S>
+where
+    S: Source<Item = f32>
+{
+    fn current_frame_len(&self) -> Option<usize>
+    {
+        // we change the amount of channels, so we can't forward the inner frame length
+        // without also converting it, and looping makes the length unknown anyway
+        None
+    }
+
+    fn channels(&self) -> u16
+    {
+        self.output_channels()
+    }
+
+    fn sample_rate(&self) -> u32
+    {
+        self.inner.sample_rate()
+    }
+
+    fn total_duration(&self) -> Option<Duration>
+    {
+        match self.controls.looping.load(Ordering::Relaxed)
+        {
+            true => None,
+            false => self.inner.total_duration()
+        }
+    }
+
+    fn try_seek(&mut self, position: Duration) -> Result<(), rodio::source::SeekError>
+    {
+        self.inner.try_seek(position)?;
+
+        let rate = self.controls.sample_rate as f64;
+        self.controls.frames_played.store((position.as_secs_f64() * rate) as u64, Ordering::Relaxed);
+
+        // don't output the rest of the old frame
+        self.channel = 0;
+
+        Ok(())
+    }
+}
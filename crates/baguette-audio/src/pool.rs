@@ -0,0 +1,200 @@
+use std::sync::Arc;
+
+use baguette_math::Vec2;
+
+use crate::mixer::{Bus, MixerState};
+use crate::spatial::Attenuation;
+use crate::playback::Voice;
+
+/// which sound is stopped to make room for a new one when the voice limit is reached,
+/// only sounds with a priority lower or equal to the new one can be stopped
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum VoiceStealing
+{
+    /// stops the sound with the lowest priority, the oldest one if there's more than one
+    #[default]
+    LowestPriority,
+    /// stops the sound that started playing first
+    Oldest,
+    /// stops the sound with the lowest volume
+    Quietest,
+    /// never stops a sound, the new one is not played instead
+    None
+}
+
+/// how a sound is played
+#[derive(Debug, Clone, Copy)]
+pub struct PlaySettings
+{
+    pub bus: Bus,
+    /// sounds with a higher priority are the last to be stopped when the voice limit is reached
+    pub priority: i32,
+    pub volume: f32,
+    /// the stereo balance between `-1.` (left) and `1.` (right)
+    pub pan: f32,
+    /// the playback speed, changing it changes the pitch too
+    pub speed: f32,
+    pub looping: bool,
+    /// plays the sound at this world position,
+    /// its volume and panning then depend on the position of the [crate::Listener]
+    pub position: Option<Vec2>,
+    pub attenuation: Attenuation
+}
+
+impl Default for PlaySettings
+{
+    fn default() -> Self
+    {
+        Self
+        {
+            bus: Bus::Sfx,
+            priority: 0,
+            volume: 1.,
+            pan: 0.,
+            speed: 1.,
+            looping: false,
+            position: None,
+            attenuation: Attenuation::default()
+        }
+    }
+}
+
+impl PlaySettings
+{
+    pub fn bus(mut self, bus: Bus) -> Self
+    {
+        self.bus = bus;
+        self
+    }
+
+    pub fn priority(mut self, priority: i32) -> Self
+    {
+        self.priority = priority;
+        self
+    }
+
+    pub fn volume(mut self, volume: f32) -> Self
+    {
+        self.volume = volume;
+        self
+    }
+
+    pub fn pan(mut self, pan: f32) -> Self
+    {
+        self.pan = pan;
+        self
+    }
+
+    pub fn speed(mut self, speed: f32) -> Self
+    {
+        self.speed = speed;
+        self
+    }
+
+    pub fn looping(mut self, looping: bool) -> Self
+    {
+        self.looping = looping;
+        self
+    }
+
+    /// plays the sound at this world position
+    pub fn at(mut self, position: Vec2) -> Self
+    {
+        self.position = Some(position);
+        self
+    }
+
+    pub fn attenuation(mut self, attenuation: Attenuation) -> Self
+    {
+        self.attenuation = attenuation;
+        self
+    }
+}
+
+/// keeps track of the sounds being played and chooses which one to stop when there are too many
+pub(crate) struct VoicePool
+{
+    pub voices: Vec<Arc<Voice>>,
+    pub limit: usize,
+    pub stealing: VoiceStealing
+}
+
+impl VoicePool
+{
+    pub fn new(limit: usize) -> Self
+    {
+        Self { voices: Vec::with_capacity(limit), limit: limit.max(1), stealing: VoiceStealing::default() }
+    }
+
+    /// removes the sounds that ended
+    pub fn collect(&mut self)
+    {
+        self.voices.retain(|voice| !voice.is_finished())
+    }
+
+    /// makes room for a new sound,
+    /// returns false if it can't be played
+    pub fn reserve(&mut self, priority: i32, clip: Option<(usize, usize)>, mixer: &MixerState) -> bool
+    {
+        self.collect();
+
+        // too many instances of the same clip, replace the oldest one
+        if let Some((id, max_instances)) = clip
+        {
+            let instances = || self.voices.iter().enumerate().filter(|(_, voice)| voice.clip == Some(id));
+
+            if instances().count() >= max_instances
+            {
+                let oldest = instances()
+                    .filter(|(_, voice)| voice.priority <= priority)
+                    .min_by_key(|(_, voice)| voice.started)
+                    .map(|(index, _)| index);
+
+                match oldest
+                {
+                    Some(index) => self.steal(index),
+                    None => return false
+                }
+            }
+        }
+
+        if self.voices.len() < self.limit
+        {
+            return true
+        }
+
+        let candidates = || self.voices.iter().enumerate().filter(|(_, voice)| voice.priority <= priority);
+
+        let victim = match self.stealing
+        {
+            VoiceStealing::LowestPriority => candidates()
+                .min_by_key(|(_, voice)| (voice.priority, voice.started))
+                .map(|(index, _)| index),
+
+            VoiceStealing::Oldest => candidates()
+                .min_by_key(|(_, voice)| voice.started)
+                .map(|(index, _)| index),
+
+            VoiceStealing::Quietest => candidates()
+                .min_by(|(_, a), (_, b)| a.loudness(mixer).total_cmp(&b.loudness(mixer)))
+                .map(|(index, _)| index),
+
+            VoiceStealing::None => None
+        };
+
+        match victim
+        {
+            Some(index) =>
+            {
+                self.steal(index);
+                true
+            }
+            None => false
+        }
+    }
+
+    fn steal(&mut self, index: usize)
+    {
+        self.voices.swap_remove(index).stop()
+    }
+}
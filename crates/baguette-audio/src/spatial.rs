@@ -0,0 +1,94 @@
+use baguette_math::Vec2;
+
+/// how the volume of a positional sound decreases with the distance from the listener
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum Rolloff
+{
+    /// the volume decreases linearly until it reaches zero at the max distance
+    #[default]
+    Linear,
+    /// the volume halves every time the distance doubles, like in the real world,
+    /// the sound is cut at the max distance
+    Inverse
+}
+
+/// describes how a positional sound is heard from the distance
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Attenuation
+{
+    /// the sound is played at full volume within this distance
+    pub min_distance: f32,
+    /// the sound is silent past this distance
+    pub max_distance: f32,
+    pub rolloff: Rolloff
+}
+
+impl Default for Attenuation
+{
+    fn default() -> Self
+    {
+        Self { min_distance: 1., max_distance: 20., rolloff: Rolloff::default() }
+    }
+}
+
+impl Attenuation
+{
+    /// the volume multiplier of a sound at this distance
+    pub fn gain(&self, distance: f32) -> f32
+    {
+        let min = self.min_distance.max(0.);
+        let max = self.max_distance.max(min);
+
+        if distance <= min
+        {
+            return 1.
+        }
+
+        if distance >= max
+        {
+            return 0.
+        }
+
+        match self.rolloff
+        {
+            Rolloff::Linear => 1. - (distance - min) / (max - min),
+            Rolloff::Inverse => min.max(f32::EPSILON) / distance
+        }
+    }
+}
+
+/// where the positional sounds are heard from, usually the camera
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Listener
+{
+    pub position: Vec2,
+    /// the horizontal distance at which a sound is heard only from one side
+    pub pan_range: f32
+}
+
+impl Default for Listener
+{
+    fn default() -> Self
+    {
+        Self { position: Vec2::ZERO, pan_range: 10. }
+    }
+}
+
+impl Listener
+{
+    /// the volume multiplier and the stereo balance of a sound played at `emitter`
+    pub fn hear(&self, emitter: Vec2, attenuation: &Attenuation) -> (f32, f32)
+    {
+        let offset = emitter - self.position;
+
+        let gain = attenuation.gain(offset.length());
+
+        let pan = match self.pan_range > 0.
+        {
+            true => (offset.x / self.pan_range).clamp(-1., 1.),
+            false => 0.
+        };
+
+        (gain, pan)
+    }
+}
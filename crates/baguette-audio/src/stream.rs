@@ -0,0 +1,143 @@
+//! the length of streamed files, rodio doesn't know it for every format
+
+use std::io::{Read, Seek, SeekFrom};
+use std::time::Duration;
+
+use rodio::Source;
+
+/// how much of the end of an ogg file is searched for its last page,
+/// a page is at most 64kB
+const OGG_TAIL: u64 = 65_307;
+
+/// a source with a known duration, for the decoders that can't tell it
+pub(crate) struct Probed<S>
+{
+    inner: S,
+    duration: Option<Duration>
+}
+
+impl<S> Probed<S>
+where
+    S: Source<Item = f32>
+{
+    /// asks the decoder first, then looks at the file itself
+    pub fn new(inner: S, file: &mut (impl Read + Seek)) -> Self
+    {
+        let duration = inner.total_duration().or_else(|| ogg_duration(file, inner.sample_rate()));
+
+        Self { inner, duration }
+    }
+}
+
+impl<S> Iterator for Probed<S>
+where
+    S: Source<Item = f32>
+{
+    type Item = f32;
+
+    fn next(&mut self) -> Option<f32>
+    {
+        self.inner.next()
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>)
+    {
+        self.inner.size_hint()
+    }
+}
+
+impl<S> Source for Probed<S>
+where
+    S: Source<Item = f32>
+{
+    fn current_frame_len(&self) -> Option<usize>
+    {
+        self.inner.current_frame_len()
+    }
+
+    fn channels(&self) -> u16
+    {
+        self.inner.channels()
+    }
+
+    fn sample_rate(&self) -> u32
+    {
+        self.inner.sample_rate()
+    }
+
+    fn total_duration(&self) -> Option<Duration>
+    {
+        self.duration
+    }
+
+    fn try_seek(&mut self, position: Duration) -> Result<(), rodio::source::SeekError>
+    {
+        self.inner.try_seek(position)
+    }
+}
+
+/// the duration of an ogg vorbis file, read from the granule position of its last page,
+/// which is the number of frames decoded once the page is played
+pub(crate) fn ogg_duration(file: &mut (impl Read + Seek), sample_rate: u32) -> Option<Duration>
+{
+    let len = file.seek(SeekFrom::End(0)).ok()?;
+    file.seek(SeekFrom::Start(len.saturating_sub(OGG_TAIL))).ok()?;
+
+    let mut tail = vec![];
+    file.read_to_end(&mut tail).ok()?;
+
+    // the pages that don't end a packet have no granule position
+    let frames = (0..tail.len().saturating_sub(13))
+        .rev()
+        .filter(|&start| &tail[start..start + 4] == b"OggS" && tail[start + 4] == 0)
+        .map(|start| u64::from_le_bytes(tail[start + 6..start + 14].try_into().expect("8 bytes")))
+        .find(|&granule| granule != u64::MAX)?;
+
+    match sample_rate
+    {
+        0 => None,
+        rate => Some(Duration::from_secs_f64(frames as f64 / rate as f64))
+    }
+}
+
+#[cfg(test)]
+mod tests
+{
+    use std::io::Cursor;
+
+    use super::*;
+
+    /// the header of an ogg page with this granule position, without its segments
+    fn page(granule: u64) -> Vec<u8>
+    {
+        let mut page = b"OggS".to_vec();
+        page.extend([0, 0]);
+        page.extend(granule.to_le_bytes());
+        page.extend([0; 13]);
+        page
+    }
+
+    #[test]
+    fn reads_the_granule_of_the_last_page()
+    {
+        let mut file = [page(1000), vec![7; 300], page(88_200), vec![7; 300]].concat();
+
+        assert_eq!(ogg_duration(&mut Cursor::new(&mut file), 44100), Some(Duration::from_secs(2)));
+    }
+
+    #[test]
+    fn skips_the_pages_without_a_granule()
+    {
+        let mut file = [page(44_100), vec![7; 300], page(u64::MAX), vec![7; 300]].concat();
+
+        assert_eq!(ogg_duration(&mut Cursor::new(&mut file), 44100), Some(Duration::from_secs(1)));
+    }
+
+    #[test]
+    fn other_formats_have_no_duration()
+    {
+        let mut file = b"RIFF....WAVEfmt ".repeat(10);
+
+        assert_eq!(ogg_duration(&mut Cursor::new(&mut file), 44100), None);
+    }
+}
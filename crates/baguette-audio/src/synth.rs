@@ -0,0 +1,438 @@
+//! retro sound effects generated from a few parameters, like sfxr
+
+use baguette_math::Random;
+
+use crate::AudioClip;
+
+/// the shape of the generated wave
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+#[derive(serde::Serialize, serde::Deserialize)]
+pub enum Waveform
+{
+    #[default]
+    Square,
+    Sawtooth,
+    Sine,
+    Triangle,
+    /// random values, the frequency sets how often they change
+    Noise
+}
+
+/// describes a sound effect, use [SfxParams::to_clip] to generate it.
+///
+/// the times are in seconds and the frequencies in hertz
+///
+/// # example
+///
+/// ```
+/// use baguette_audio::{AudioBackend, AudioPlayer, SfxParams};
+///
+/// let player = AudioPlayer::with_backend(AudioBackend::Null);
+///
+/// let coin = SfxParams::coin(42).to_clip();
+/// player.play(&coin);
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+#[derive(serde::Serialize, serde::Deserialize)]
+#[serde(default)]
+pub struct SfxParams
+{
+    pub waveform: Waveform,
+
+    /// how long it takes to reach the full volume
+    pub attack: f32,
+    /// how long the full volume is held
+    pub sustain: f32,
+    /// makes the sustain start louder, between `0.` and `1.`
+    pub punch: f32,
+    /// how long it takes to fade out
+    pub decay: f32,
+
+    /// the starting frequency
+    pub frequency: f32,
+    /// the sound is cut once the frequency slides below this
+    pub min_frequency: f32,
+    /// how fast the frequency changes, in octaves per second
+    pub slide: f32,
+    /// how fast the slide changes, in octaves per second squared
+    pub delta_slide: f32,
+
+    /// how much the vibrato changes the frequency, `0.1` is 10%
+    pub vibrato_depth: f32,
+    /// how many times per second the vibrato oscillates
+    pub vibrato_speed: f32,
+
+    /// the frequency is multiplied by this once [SfxParams::arpeggio_time] passes,
+    /// `1.` disables the arpeggio
+    pub arpeggio: f32,
+    pub arpeggio_time: f32,
+
+    /// the portion of the period a square wave is high, between `0.` and `1.`
+    pub duty: f32,
+    /// how fast the duty changes, per second
+    pub duty_sweep: f32,
+
+    /// how much noise is mixed with the wave, between `0.` and `1.`
+    pub noise: f32,
+
+    pub volume: f32,
+    /// the seed of the noise, the same parameters always generate the same sound
+    pub seed: u64
+}
+
+impl Default for SfxParams
+{
+    fn default() -> Self
+    {
+        Self
+        {
+            waveform: Waveform::Square,
+            attack: 0.,
+            sustain: 0.1,
+            punch: 0.,
+            decay: 0.2,
+            frequency: 440.,
+            min_frequency: 0.,
+            slide: 0.,
+            delta_slide: 0.,
+            vibrato_depth: 0.,
+            vibrato_speed: 0.,
+            arpeggio: 1.,
+            arpeggio_time: 0.,
+            duty: 0.5,
+            duty_sweep: 0.,
+            noise: 0.,
+            volume: 0.5,
+            seed: 0
+        }
+    }
+}
+
+impl SfxParams
+{
+    /// the sample rate of the clips made with [SfxParams::to_clip]
+    pub const SAMPLE_RATE: u32 = 44100;
+
+    /// a short rising blip, the seed randomizes it a bit
+    pub fn jump(seed: u64) -> Self
+    {
+        let mut rng = Random::new(seed);
+
+        Self
+        {
+            waveform: Waveform::Square,
+            frequency: rng.range(250., 500.),
+            slide: rng.range(1.5, 3.5),
+            sustain: rng.range(0.05, 0.12),
+            decay: rng.range(0.08, 0.2),
+            duty: rng.range(0.3, 0.6),
+            seed,
+            ..Default::default()
+        }
+    }
+
+    /// a bright sound with a jump in pitch, for pickups
+    pub fn coin(seed: u64) -> Self
+    {
+        let mut rng = Random::new(seed);
+
+        Self
+        {
+            waveform: Waveform::Square,
+            frequency: rng.range(800., 1600.),
+            sustain: rng.range(0.04, 0.1),
+            punch: rng.range(0.3, 0.6),
+            decay: rng.range(0.1, 0.3),
+            arpeggio: rng.range(1.3, 1.7),
+            arpeggio_time: rng.range(0.03, 0.08),
+            seed,
+            ..Default::default()
+        }
+    }
+
+    /// a noisy burst that rumbles lower over time
+    pub fn explosion(seed: u64) -> Self
+    {
+        let mut rng = Random::new(seed);
+
+        Self
+        {
+            waveform: Waveform::Noise,
+            frequency: rng.range(40., 150.),
+            slide: rng.range(-2., -0.5),
+            sustain: rng.range(0.1, 0.3),
+            punch: rng.range(0.2, 0.6),
+            decay: rng.range(0.3, 0.6),
+            vibrato_depth: rng.range(0., 0.3),
+            vibrato_speed: rng.range(5., 20.),
+            volume: 0.6,
+            seed,
+            ..Default::default()
+        }
+    }
+
+    /// a fast falling zap
+    pub fn laser(seed: u64) -> Self
+    {
+        let mut rng = Random::new(seed);
+
+        let waveform = [Waveform::Square, Waveform::Sawtooth, Waveform::Sine][rng.usize(..3)];
+
+        Self
+        {
+            waveform,
+            frequency: rng.range(700., 2000.),
+            min_frequency: rng.range(100., 250.),
+            slide: rng.range(-6., -2.),
+            sustain: rng.range(0.05, 0.15),
+            decay: rng.range(0.05, 0.2),
+            duty: rng.range(0.2, 0.5),
+            duty_sweep: rng.range(0., 1.),
+            seed,
+            ..Default::default()
+        }
+    }
+
+    /// a short punchy hit, for damage
+    pub fn hit(seed: u64) -> Self
+    {
+        let mut rng = Random::new(seed);
+
+        Self
+        {
+            waveform: [Waveform::Noise, Waveform::Sawtooth, Waveform::Square][rng.usize(..3)],
+            frequency: rng.range(200., 800.),
+            slide: rng.range(-4., -1.),
+            sustain: rng.range(0.02, 0.06),
+            punch: rng.range(0., 0.4),
+            decay: rng.range(0.05, 0.15),
+            seed,
+            ..Default::default()
+        }
+    }
+
+    /// a rising sound with vibrato
+    pub fn power_up(seed: u64) -> Self
+    {
+        let mut rng = Random::new(seed);
+
+        Self
+        {
+            waveform: [Waveform::Square, Waveform::Sawtooth][rng.usize(..2)],
+            frequency: rng.range(250., 500.),
+            slide: rng.range(0.8, 2.),
+            vibrato_depth: rng.range(0.05, 0.2),
+            vibrato_speed: rng.range(8., 16.),
+            sustain: rng.range(0.2, 0.4),
+            decay: rng.range(0.1, 0.3),
+            seed,
+            ..Default::default()
+        }
+    }
+
+    /// a very short tone, for menus
+    pub fn blip(seed: u64) -> Self
+    {
+        let mut rng = Random::new(seed);
+
+        Self
+        {
+            waveform: [Waveform::Square, Waveform::Sine][rng.usize(..2)],
+            frequency: rng.range(400., 1200.),
+            sustain: rng.range(0.03, 0.08),
+            decay: rng.range(0.01, 0.05),
+            seed,
+            ..Default::default()
+        }
+    }
+
+    /// the length of the sound, it can be shorter if the frequency
+    /// slides below [SfxParams::min_frequency]
+    pub fn duration(&self) -> f32
+    {
+        self.attack.max(0.) + self.sustain.max(0.) + self.decay.max(0.)
+    }
+
+    /// generates the mono samples of the sound
+    pub fn render(&self, sample_rate: u32) -> Vec<f32>
+    {
+        let rate = sample_rate.max(1) as f32;
+        let length = (self.duration() * rate) as usize;
+
+        let mut rng = Random::new(self.seed);
+        let mut noise: [f32; 32] = std::array::from_fn(|_| rng.f32() * 2. - 1.);
+
+        let mut samples = Vec::with_capacity(length);
+        let mut phase = 0f32;
+
+        for index in 0..length
+        {
+            let t = index as f32 / rate;
+
+            let mut frequency = self.frequency * 2f32.powf(self.slide * t + 0.5 * self.delta_slide * t * t);
+
+            if self.min_frequency > 0. && frequency < self.min_frequency
+            {
+                break
+            }
+
+            if self.arpeggio_time > 0. && t >= self.arpeggio_time
+            {
+                frequency *= self.arpeggio
+            }
+
+            frequency *= 1. + self.vibrato_depth * (std::f32::consts::TAU * self.vibrato_speed * t).sin();
+
+            phase += frequency.max(0.) / rate;
+
+            if phase >= 1.
+            {
+                phase %= 1.;
+
+                // new noise for every period
+                noise = std::array::from_fn(|_| rng.f32() * 2. - 1.)
+            }
+
+            let random = noise[(phase * noise.len() as f32) as usize % noise.len()];
+
+            let wave = match self.waveform
+            {
+                Waveform::Square =>
+                {
+                    let duty = (self.duty + self.duty_sweep * t).clamp(0.05, 0.95);
+
+                    match phase < duty
+                    {
+                        true => 1.,
+                        false => -1.
+                    }
+                }
+                Waveform::Sawtooth => 1. - 2. * phase,
+                Waveform::Sine => (std::f32::consts::TAU * phase).sin(),
+                Waveform::Triangle => 1. - 4. * (phase - 0.5).abs(),
+                Waveform::Noise => random
+            };
+
+            let noise_mix = self.noise.clamp(0., 1.);
+            let sample = wave * (1. - noise_mix) + random * noise_mix;
+
+            samples.push(sample * self.envelope(t) * self.volume)
+        }
+
+        samples
+    }
+
+    /// the volume multiplier of the envelope at this time
+    fn envelope(&self, t: f32) -> f32
+    {
+        let attack = self.attack.max(0.);
+        let sustain = self.sustain.max(0.);
+        let decay = self.decay.max(0.);
+
+        if t < attack
+        {
+            return t / attack
+        }
+
+        let t = t - attack;
+
+        if t < sustain
+        {
+            return 1. + self.punch * (1. - t / sustain)
+        }
+
+        let t = t - sustain;
+
+        match decay > 0.
+        {
+            true => f32::max(1. - t / decay, 0.),
+            false => 0.
+        }
+    }
+
+    /// generates the sound as a clip that can be played like any other
+    pub fn to_clip(&self) -> AudioClip
+    {
+        AudioClip::from_samples(1, Self::SAMPLE_RATE, self.render(Self::SAMPLE_RATE))
+    }
+}
+
+#[cfg(test)]
+mod tests
+{
+    use super::*;
+
+    const RATE: u32 = 8000;
+
+    fn presets(seed: u64) -> [SfxParams; 7]
+    {
+        [
+            SfxParams::jump(seed),
+            SfxParams::coin(seed),
+            SfxParams::explosion(seed),
+            SfxParams::laser(seed),
+            SfxParams::hit(seed),
+            SfxParams::power_up(seed),
+            SfxParams::blip(seed)
+        ]
+    }
+
+    #[test]
+    fn length_matches_the_duration()
+    {
+        let params = SfxParams { attack: 0.05, sustain: 0.2, decay: 0.25, ..Default::default() };
+
+        assert_eq!(params.render(RATE).len(), (0.5 * RATE as f32) as usize);
+        assert_eq!(params.render(44100).len(), (0.5 * 44100.) as usize);
+    }
+
+    #[test]
+    fn cut_once_below_the_min_frequency()
+    {
+        // two octaves down in half a second
+        let params = SfxParams { frequency: 1000., min_frequency: 250., slide: -4., sustain: 1., decay: 0., ..Default::default() };
+
+        let length = params.render(RATE).len() as f32;
+
+        assert!((length - 0.5 * RATE as f32).abs() <= 1., "{length}");
+    }
+
+    #[test]
+    fn same_parameters_same_sound()
+    {
+        for seed in [0, 1, 42]
+        {
+            for params in presets(seed)
+            {
+                assert_eq!(params.render(RATE), params.clone().render(RATE));
+            }
+        }
+
+        let noise = |seed| SfxParams { waveform: Waveform::Noise, seed, ..Default::default() }.render(RATE);
+
+        assert_eq!(noise(3), noise(3));
+        assert_ne!(noise(3), noise(4));
+    }
+
+    #[test]
+    fn presets_dont_change_between_versions()
+    {
+        // the seeds go through the generator of baguette-math, not through a dependency that could change it
+        assert_eq!(SfxParams::jump(7).frequency, 470.80814);
+    }
+
+    #[test]
+    fn peak_stays_under_the_punch()
+    {
+        for seed in 0..20
+        {
+            for params in presets(seed)
+            {
+                let peak = params.render(RATE).iter().fold(0f32, |peak, sample| peak.max(sample.abs()));
+
+                assert!(peak <= (1. + params.punch) * params.volume + 1e-6, "{params:?} peaks at {peak}");
+                assert!(peak > 0.);
+            }
+        }
+    }
+}
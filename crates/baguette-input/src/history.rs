@@ -0,0 +1,479 @@
+//! timestamped input history, used for input buffering and combos
+
+use std::collections::VecDeque;
+use std::time::{Duration, Instant};
+
+use winit::event::MouseButton;
+use winit::keyboard::KeyCode;
+
+/// how many presses and releases are remembered by default
+const DEFAULT_HISTORY_CAPACITY: usize = 64;
+
+/// anything that can be pressed and released
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Button
+{
+    Key(KeyCode),
+    Mouse(MouseButton)
+}
+
+impl From<KeyCode> for Button
+{
+    fn from(keycode: KeyCode) -> Self { Self::Key(keycode) }
+}
+
+impl From<MouseButton> for Button
+{
+    fn from(button: MouseButton) -> Self { Self::Mouse(button) }
+}
+
+/// a single press or release
+#[derive(Debug, Clone, Copy)]
+pub struct InputRecord
+{
+    pub button: Button,
+    /// true if the button was pressed, false if it was released
+    pub pressed: bool,
+    pub time: Instant,
+    /// the frame the record was received in
+    pub frame: u64
+}
+
+/// a ring buffer of the latest presses and releases, oldest first.
+///
+/// key repeats are not recorded
+pub struct InputHistory
+{
+    records: VecDeque<InputRecord>,
+    capacity: usize,
+    /// the current frame, incremented every time the input is flushed
+    frame: u64
+}
+
+impl Default for InputHistory
+{
+    fn default() -> Self
+    {
+        Self::with_capacity(DEFAULT_HISTORY_CAPACITY)
+    }
+}
+
+impl InputHistory
+{
+    pub fn with_capacity(capacity: usize) -> Self
+    {
+        let capacity = capacity.max(1);
+
+        Self { records: VecDeque::with_capacity(capacity), capacity, frame: 0 }
+    }
+
+    pub(crate) fn push(&mut self, button: Button, pressed: bool, time: Instant)
+    {
+        if self.records.len() == self.capacity
+        {
+            self.records.pop_front();
+        }
+
+        self.records.push_back(InputRecord { button, pressed, time, frame: self.frame })
+    }
+
+    pub(crate) fn next_frame(&mut self)
+    {
+        self.frame += 1
+    }
+
+    pub(crate) fn set_capacity(&mut self, capacity: usize)
+    {
+        self.capacity = capacity.max(1);
+
+        while self.records.len() > self.capacity
+        {
+            self.records.pop_front();
+        }
+    }
+
+    /// iters the records, oldest first
+    pub fn iter(&self) -> impl DoubleEndedIterator<Item = &InputRecord>
+    {
+        self.records.iter()
+    }
+
+    /// the current frame
+    pub fn frame(&self) -> u64
+    {
+        self.frame
+    }
+
+    /// returns the time of the latest press of this button
+    pub fn last_press(&self, button: impl Into<Button>) -> Option<Instant>
+    {
+        let button = button.into();
+
+        self.records.iter()
+            .rev()
+            .find(|record| record.pressed && record.button == button)
+            .map(|record| record.time)
+    }
+
+    /// returns true if the button was pressed between `now - window` and `now`
+    pub fn pressed_within(&self, button: impl Into<Button>, window: Duration, now: Instant) -> bool
+    {
+        self.last_press(button)
+            .is_some_and(|time| now.saturating_duration_since(time) <= window)
+    }
+
+    /// returns true if the combo was completed by a press received this frame
+    pub fn matches(&self, combo: &Combo) -> bool
+    {
+        let Some(last_step) = combo.steps.len().checked_sub(1)
+        else
+        {
+            return false
+        };
+
+        (0..self.records.len())
+            .rev()
+            .take_while(|&idx| self.records[idx].frame == self.frame)
+            .any(|idx| self.matches_from(combo, last_step, idx, self.records[idx].time))
+    }
+
+    /// returns true if the record at `idx` completes the step at `step_idx`
+    /// and all the steps before it were completed in time
+    fn matches_from(&self, combo: &Combo, step_idx: usize, idx: usize, end: Instant) -> bool
+    {
+        if !self.completes(&combo.steps[step_idx], idx)
+        {
+            return false
+        }
+
+        let time = self.records[idx].time;
+
+        if step_idx == 0
+        {
+            return combo.total_window.is_none_or(|window| end.saturating_duration_since(time) <= window)
+        }
+
+        (0..idx)
+            .rev()
+            .take_while(|&prev| time.saturating_duration_since(self.records[prev].time) <= combo.step_window)
+            .any(|prev| self.matches_from(combo, step_idx - 1, prev, end))
+    }
+
+    /// a step is completed when one of its buttons is pressed while the others are held
+    fn completes(&self, step: &Step, idx: usize) -> bool
+    {
+        let record = &self.records[idx];
+
+        record.pressed
+        && step.0.contains(&record.button)
+        && step.0.iter()
+            .filter(|&&button| button != record.button)
+            .all(|&button| self.held_before(button, idx))
+    }
+
+    /// returns true if the button was being held when the record at `idx` was received
+    fn held_before(&self, button: Button, idx: usize) -> bool
+    {
+        self.records.range(..idx)
+            .rev()
+            .find(|record| record.button == button)
+            .is_some_and(|record| record.pressed)
+    }
+}
+
+/// one or more buttons that have to be pressed together,
+/// the step is completed when the last of them is pressed while the others are held
+#[derive(Debug, Clone)]
+pub struct Step(Vec<Button>);
+
+impl<T: Into<Button>> From<T> for Step
+{
+    fn from(button: T) -> Self { Self(vec![button.into()]) }
+}
+
+impl<T: Into<Button>, const LEN: usize> From<[T; LEN]> for Step
+{
+    fn from(buttons: [T; LEN]) -> Self { Self(buttons.into_iter().map(Into::into).collect()) }
+}
+
+/// a sequence of steps that has to be performed in order and in time.
+///
+/// other presses between the steps are tolerated
+/// # example
+/// ```
+/// use baguette_input::*;
+/// use std::time::Duration;
+///
+/// // down, down-forward, forward + punch
+/// let fireball = Combo::new()
+///     .then(KeyCode::KeyS)
+///     .then([KeyCode::KeyS, KeyCode::KeyD])
+///     .then([KeyCode::KeyD, KeyCode::KeyJ])
+///     .step_window(Duration::from_millis(150));
+/// ```
+#[derive(Debug, Clone)]
+pub struct Combo
+{
+    steps: Vec<Step>,
+    /// the max time between two consecutive steps
+    step_window: Duration,
+    /// the max time between the first and the last step
+    total_window: Option<Duration>
+}
+
+impl Default for Combo
+{
+    fn default() -> Self
+    {
+        Self::new()
+    }
+}
+
+impl Combo
+{
+    pub fn new() -> Self
+    {
+        Self { steps: vec![], step_window: Duration::from_millis(200), total_window: None }
+    }
+
+    /// appends a step to the sequence
+    pub fn then(mut self, step: impl Into<Step>) -> Self
+    {
+        self.steps.push(step.into());
+        self
+    }
+
+    /// sets the max time between two consecutive steps, defaults to 200ms
+    pub fn step_window(mut self, window: Duration) -> Self
+    {
+        self.step_window = window;
+        self
+    }
+
+    /// sets the max time between the first and the last step
+    pub fn total_window(mut self, window: Duration) -> Self
+    {
+        self.total_window = Some(window);
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests
+{
+    use super::*;
+
+    const MS: Duration = Duration::from_millis(1);
+
+    /// a history fed with presses and releases at times relative to `start`
+    struct Feed
+    {
+        history: InputHistory,
+        start: Instant
+    }
+
+    impl Feed
+    {
+        fn new() -> Self
+        {
+            Self { history: InputHistory::default(), start: Instant::now() }
+        }
+
+        fn at(&self, ms: u64) -> Instant
+        {
+            self.start + MS * ms as u32
+        }
+
+        fn press(&mut self, key: KeyCode, ms: u64)
+        {
+            let time = self.at(ms);
+            self.history.push(key.into(), true, time);
+            self.history.next_frame()
+        }
+
+        fn release(&mut self, key: KeyCode, ms: u64)
+        {
+            let time = self.at(ms);
+            self.history.push(key.into(), false, time);
+            self.history.next_frame()
+        }
+
+        /// presses the key in a frame of its own and checks the combo in that same frame
+        fn press_matches(&mut self, key: KeyCode, ms: u64, combo: &Combo) -> bool
+        {
+            let time = self.at(ms);
+            self.history.push(key.into(), true, time);
+
+            let matches = self.history.matches(combo);
+            self.history.next_frame();
+
+            matches
+        }
+    }
+
+    fn fireball() -> Combo
+    {
+        Combo::new()
+            .then(KeyCode::KeyS)
+            .then([KeyCode::KeyS, KeyCode::KeyD])
+            .then([KeyCode::KeyD, KeyCode::KeyJ])
+            .step_window(MS * 150)
+    }
+
+    #[test]
+    fn pressed_within_includes_the_window_boundary()
+    {
+        let mut feed = Feed::new();
+        feed.press(KeyCode::Space, 0);
+
+        let window = MS * 100;
+
+        assert!(feed.history.pressed_within(KeyCode::Space, window, feed.at(0)));
+        assert!(feed.history.pressed_within(KeyCode::Space, window, feed.at(100)));
+        assert!(!feed.history.pressed_within(KeyCode::Space, window, feed.at(101)));
+        assert!(!feed.history.pressed_within(KeyCode::Enter, window, feed.at(0)));
+    }
+
+    #[test]
+    fn pressed_within_ignores_releases_and_uses_the_latest_press()
+    {
+        let mut feed = Feed::new();
+        feed.press(KeyCode::Space, 0);
+        feed.release(KeyCode::Space, 50);
+
+        assert!(!feed.history.pressed_within(KeyCode::Space, MS * 100, feed.at(150)));
+
+        feed.press(KeyCode::Space, 120);
+        assert!(feed.history.pressed_within(KeyCode::Space, MS * 100, feed.at(150)));
+        assert_eq!(feed.history.last_press(KeyCode::Space), Some(feed.at(120)));
+    }
+
+    #[test]
+    fn combo_matches_in_order_and_in_time()
+    {
+        let mut feed = Feed::new();
+        let combo = fireball();
+
+        feed.press(KeyCode::KeyS, 0);
+        feed.press(KeyCode::KeyD, 100);
+        feed.release(KeyCode::KeyS, 120);
+
+        assert!(feed.press_matches(KeyCode::KeyJ, 200, &combo));
+    }
+
+    #[test]
+    fn combo_matches_only_in_the_frame_it_is_completed()
+    {
+        let mut feed = Feed::new();
+        let combo = fireball();
+
+        feed.press(KeyCode::KeyS, 0);
+        feed.press(KeyCode::KeyD, 100);
+        assert!(feed.press_matches(KeyCode::KeyJ, 200, &combo));
+
+        assert!(!feed.history.matches(&combo));
+    }
+
+    #[test]
+    fn combo_fails_when_a_step_is_too_late()
+    {
+        let mut feed = Feed::new();
+        let combo = fireball();
+
+        feed.press(KeyCode::KeyS, 0);
+        feed.press(KeyCode::KeyD, 151);
+
+        assert!(!feed.press_matches(KeyCode::KeyJ, 200, &combo));
+    }
+
+    #[test]
+    fn combo_step_window_is_inclusive()
+    {
+        let mut feed = Feed::new();
+        let combo = fireball();
+
+        feed.press(KeyCode::KeyS, 0);
+        feed.press(KeyCode::KeyD, 150);
+
+        assert!(feed.press_matches(KeyCode::KeyJ, 300, &combo));
+    }
+
+    #[test]
+    fn combo_fails_out_of_order()
+    {
+        let mut feed = Feed::new();
+        let combo = fireball();
+
+        feed.press(KeyCode::KeyD, 0);
+        feed.press(KeyCode::KeyS, 50);
+
+        assert!(!feed.press_matches(KeyCode::KeyJ, 100, &combo));
+    }
+
+    #[test]
+    fn combo_step_needs_the_other_buttons_held()
+    {
+        let mut feed = Feed::new();
+        let combo = fireball();
+
+        // S is released before D, so down-forward never happens
+        feed.press(KeyCode::KeyS, 0);
+        feed.release(KeyCode::KeyS, 50);
+        feed.press(KeyCode::KeyD, 100);
+
+        assert!(!feed.press_matches(KeyCode::KeyJ, 200, &combo));
+    }
+
+    #[test]
+    fn combo_tolerates_other_presses_between_the_steps()
+    {
+        let mut feed = Feed::new();
+        let combo = fireball();
+
+        feed.press(KeyCode::KeyS, 0);
+        feed.press(KeyCode::KeyW, 40);
+        feed.press(KeyCode::KeyD, 100);
+        feed.press(KeyCode::KeyK, 140);
+
+        assert!(feed.press_matches(KeyCode::KeyJ, 200, &combo));
+    }
+
+    #[test]
+    fn combo_total_window()
+    {
+        let combo = fireball().total_window(MS * 250);
+
+        let mut feed = Feed::new();
+        feed.press(KeyCode::KeyS, 0);
+        feed.press(KeyCode::KeyD, 140);
+        assert!(!feed.press_matches(KeyCode::KeyJ, 280, &combo));
+
+        let mut feed = Feed::new();
+        feed.press(KeyCode::KeyS, 0);
+        feed.press(KeyCode::KeyD, 120);
+        assert!(feed.press_matches(KeyCode::KeyJ, 250, &combo));
+    }
+
+    #[test]
+    fn empty_combo_never_matches()
+    {
+        let mut feed = Feed::new();
+
+        assert!(!feed.press_matches(KeyCode::KeyJ, 0, &Combo::new()));
+    }
+
+    #[test]
+    fn capacity_drops_the_oldest_records()
+    {
+        let mut history = InputHistory::with_capacity(2);
+        let now = Instant::now();
+
+        history.push(KeyCode::KeyA.into(), true, now);
+        history.push(KeyCode::KeyB.into(), true, now);
+        history.push(KeyCode::KeyC.into(), true, now);
+
+        let buttons: Vec<_> = history.iter().map(|record| record.button).collect();
+        assert_eq!(buttons, [Button::Key(KeyCode::KeyB), Button::Key(KeyCode::KeyC)]);
+        assert_eq!(history.last_press(KeyCode::KeyA), None);
+    }
+}
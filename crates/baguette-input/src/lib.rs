@@ -10,13 +10,97 @@ pub use baguette_math;
 
 pub use winit;
 
+pub mod touch;
+pub use touch::*;
+
+pub mod history;
+pub use history::*;
+
+pub mod replay;
+pub use replay::*;
+
+use std::time::{Duration, Instant};
+
 #[derive(Default)]
 /// the input system of the engine, this is managed by the engine
 pub struct InputHandler
 {
     current_pressed_keys: ahash::AHashMap<PhysicalKey, InputState>,
     pressed_mouse_buttons: ahash::AHashMap<MouseButton, InputState>,
-    cursor_position: baguette_math::Vec2
+    cursor_position: baguette_math::Vec2,
+    touch: touch::TouchState,
+    history: InputHistory,
+    source: InputSource,
+    clock: FrameClock
+}
+
+/// the input events the [InputHandler] cares about,
+/// unlike [WindowEvent] these can be serialized
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[derive(serde::Serialize, serde::Deserialize)]
+pub enum InputEvent
+{
+    Key { keycode: KeyCode, pressed: bool, repeat: bool },
+    MouseButton { button: MouseButton, pressed: bool },
+    CursorMoved(baguette_math::Vec2),
+    Touch
+    {
+        id: u64,
+        phase: TouchPhase,
+        position: baguette_math::Vec2,
+        /// the normalized pressure of the touch, if supported by the platform
+        force: Option<f32>
+    },
+    /// the magnification delta of a touchpad pinch
+    Pinch(f64),
+    /// the delta in degrees of a touchpad rotation, positive is counterclockwise
+    Rotation(f32),
+    /// the delta in pixels of a touchpad pan
+    Pan(baguette_math::Vec2),
+    DoubleTap
+}
+
+impl InputEvent
+{
+    /// returns [None] if the event is not an input or the key is not identified
+    pub fn from_window_event(event: &WindowEvent) -> Option<Self>
+    {
+        Some(match event
+        {
+            WindowEvent::KeyboardInput{ event: KeyEvent { physical_key: PhysicalKey::Code(keycode), state, repeat, .. }, .. } =>
+            {
+                Self::Key { keycode: *keycode, pressed: state.is_pressed(), repeat: *repeat }
+            }
+
+            WindowEvent::MouseInput { state, button, .. } =>
+            {
+                Self::MouseButton { button: *button, pressed: state.is_pressed() }
+            }
+
+            WindowEvent::CursorMoved { position, .. } => Self::CursorMoved
+            (
+                baguette_math::Vec2::new(position.x as f32, position.y as f32)
+            ),
+
+            WindowEvent::Touch(Touch { id, phase, location, force, .. }) => Self::Touch
+            {
+                id: *id,
+                phase: *phase,
+                position: baguette_math::Vec2::new(location.x as f32, location.y as f32),
+                force: force.map(|force| force.normalized() as f32)
+            },
+
+            WindowEvent::PinchGesture { delta, .. } => Self::Pinch(*delta),
+
+            WindowEvent::RotationGesture { delta, .. } => Self::Rotation(*delta),
+
+            WindowEvent::PanGesture { delta, .. } => Self::Pan(baguette_math::Vec2::new(delta.x, delta.y)),
+
+            WindowEvent::DoubleTapGesture { .. } => Self::DoubleTap,
+
+            _ => return None
+        })
+    }
 }
 
 /// holds the current state of an active input
@@ -29,63 +113,106 @@ struct InputState
 impl InputHandler
 {
     pub fn check(&mut self, event: &WindowEvent)
+    {
+        self.check_at(event, Instant::now())
+    }
+
+    /// same as [InputHandler::check] but with the time the event was received,
+    /// useful to feed events that didn't come from the event loop.
+    ///
+    /// live events are ignored while a recording is being played back
+    pub fn check_at(&mut self, event: &WindowEvent, now: Instant)
+    {
+        let Some(event) = InputEvent::from_window_event(event)
+        else
+        {
+            return
+        };
+
+        match &mut self.source
+        {
+            InputSource::Live => (),
+            InputSource::Recording(recorder) => recorder.record(event, now),
+            InputSource::Playback(..) => return
+        }
+
+        self.handle(event, now)
+    }
+
+    /// updates the state of the inputs with this event
+    fn handle(&mut self, event: InputEvent, now: Instant)
     {
         match event
         {
-            WindowEvent::KeyboardInput{ event: KeyEvent { physical_key, state,.. }, .. } =>
+            InputEvent::Key { keycode, pressed, repeat } =>
             {
-                if let ElementState::Pressed = state
+                let physical_key = PhysicalKey::Code(keycode);
+
+                if !repeat
                 {
-                    match self.current_pressed_keys.get_mut(physical_key)
+                    self.history.push(keycode.into(), pressed, now)
+                }
+
+                if pressed
+                {
+                    match self.current_pressed_keys.get_mut(&physical_key)
                     {
                         Some(state) => state.pressed_this_frame = false,
                         None => 
                         {
                             self.current_pressed_keys.insert
                             (
-                                *physical_key,
+                                physical_key,
                                 InputState { pressed_this_frame: true, released: false }
                             );
                         }
                     }
                 }
-                else if let Some(InputState { released, .. }) = self.current_pressed_keys.get_mut(physical_key)
+                else if let Some(InputState { released, .. }) = self.current_pressed_keys.get_mut(&physical_key)
                 {
                     *released = true;
                 }
             }
 
-            WindowEvent::MouseInput { state, button, .. } =>
+            InputEvent::MouseButton { button, pressed } =>
             {
-                match state
+                self.history.push(button.into(), pressed, now);
+
+                match pressed
                 {
-                    ElementState::Pressed =>
+                    true =>
                     
-                        if self.pressed_mouse_buttons.get(button).is_none()
+                        if self.pressed_mouse_buttons.get(&button).is_none()
                         {
                             self.pressed_mouse_buttons.insert
                             (
-                                *button, InputState { pressed_this_frame: true, released: false }
+                                button, InputState { pressed_this_frame: true, released: false }
                             );
                         }
                     
-                    ElementState::Released =>
+                    false =>
                     
-                        if let Some(InputState { released, .. }) = self.pressed_mouse_buttons.get_mut(button)
+                        if let Some(InputState { released, .. }) = self.pressed_mouse_buttons.get_mut(&button)
                         {
                             *released = true;
                         }
                     
                 }
             }
-            WindowEvent::CursorMoved { position, .. } =>
+            InputEvent::CursorMoved(position) => self.cursor_position = position,
+
+            InputEvent::Touch { id, phase, position, force } =>
             {
-                self.cursor_position = baguette_math::Vec2::new
-                (
-                    position.x as f32, position.y  as f32
-                )
+                self.touch.touch(id, phase, position, force, now)
             }
-            _ => (/*ignore other events*/)
+
+            InputEvent::Pinch(delta) => self.touch.pinch(delta, self.cursor_position),
+
+            InputEvent::Rotation(delta) => self.touch.rotate(delta, self.cursor_position),
+
+            InputEvent::Pan(delta) => self.touch.pan(delta),
+
+            InputEvent::DoubleTap => self.touch.double_tap(self.cursor_position),
         }
     }
     
@@ -113,6 +240,141 @@ impl InputHandler
                 state.pressed_this_frame = false
             }
         );
+
+        self.touch.flush();
+        self.history.next_frame();
+
+        self.next_source_frame()
+    }
+
+    /// closes the recorded frame or feeds the next frame of the playback
+    fn next_source_frame(&mut self)
+    {
+        let now = Instant::now();
+
+        match &mut self.source
+        {
+            InputSource::Live => self.clock.next_frame(now),
+            InputSource::Recording(recorder) =>
+            {
+                recorder.next_frame(now);
+                self.clock.next_frame(now)
+            }
+            InputSource::Playback(playback) =>
+            {
+                // the recorded frame began when the previous one ended
+                let previous = playback.frame_time().unwrap_or_default();
+
+                let Some((events, start)) = playback.next_frame()
+                else
+                {
+                    log::debug!("input playback ended");
+                    self.source = InputSource::Live;
+                    self.clock.next_frame(now);
+                    return
+                };
+
+                self.clock.next_frame(start + previous);
+
+                for TimedEvent { time, event } in events
+                {
+                    self.handle(event, start + time)
+                }
+            }
+        }
+    }
+
+    /// starts recording the events received from now on,
+    /// stops any recording or playback in progress
+    pub fn start_recording(&mut self)
+    {
+        let now = Instant::now();
+
+        self.source = InputSource::Recording(InputRecorder::new(now));
+        self.clock = FrameClock::new(now)
+    }
+
+    /// stops the recording and returns it, returns [None] if nothing was being recorded
+    pub fn stop_recording(&mut self) -> Option<InputRecording>
+    {
+        match std::mem::replace(&mut self.source, InputSource::Live)
+        {
+            InputSource::Recording(recorder) => Some(recorder.finish(Instant::now())),
+            source =>
+            {
+                self.source = source;
+                None
+            }
+        }
+    }
+
+    /// replaces the live events with the recorded ones, one recorded frame each frame,
+    /// starting from the next frame.
+    ///
+    /// the inputs go back to be live once the recording ends
+    pub fn start_playback(&mut self, recording: InputRecording)
+    {
+        let now = Instant::now();
+
+        self.source = InputSource::Playback(InputPlayback::new(recording, now));
+        self.clock = FrameClock::new(now)
+    }
+
+    /// stops the playback and goes back to live events
+    pub fn stop_playback(&mut self)
+    {
+        if let InputSource::Playback(..) = self.source
+        {
+            self.source = InputSource::Live
+        }
+    }
+
+    pub fn is_recording(&self) -> bool
+    {
+        matches!(self.source, InputSource::Recording(..))
+    }
+
+    pub fn is_playing_back(&self) -> bool
+    {
+        matches!(self.source, InputSource::Playback(..))
+    }
+
+    /// the time since the start of the recording of the frame being played back,
+    /// [None] if nothing is being played back
+    pub fn playback_frame_time(&self) -> Option<Duration>
+    {
+        match &self.source
+        {
+            InputSource::Playback(playback) => playback.frame_time(),
+            _ => None
+        }
+    }
+
+    /// when the current frame began, the time the timed queries are measured from.
+    ///
+    /// during a playback this is the recorded time offset by the start of the playback,
+    /// so that the replayed frames see the same times they were recorded with
+    pub fn frame_start(&self) -> Instant
+    {
+        self.clock.start
+    }
+
+    /// how long the previous frame lasted, the recorded one during a playback
+    pub fn frame_delta(&self) -> Duration
+    {
+        self.clock.delta
+    }
+
+    /// sets how many presses and releases are remembered, defaults to 64
+    pub fn set_history_capacity(&mut self, capacity: usize)
+    {
+        self.history.set_capacity(capacity)
+    }
+
+    /// sets the thresholds used to recognize touch gestures
+    pub fn set_gesture_config(&mut self, config: GestureConfig)
+    {
+        self.touch.config = config
     }
 }
 
@@ -195,11 +457,56 @@ impl Input<'_>
     {
         baguette_math::Vec2::new(self.horizontal_axis(), self.vertical_axis())
     }
+
+    /// returns true if the key was pressed in the last `window` of time,
+    /// even if it was already released.
+    ///
+    /// the time is measured from the start of the frame, see [InputHandler::frame_start]
+    /// ```ignore
+    /// // jump buffering
+    /// if app.input.get_key_pressed_within(KeyCode::Space, Duration::from_millis(100)) && grounded
+    /// ```
+    pub fn get_key_pressed_within(&self, keycode: KeyCode, window: Duration) -> bool
+    {
+        self.handler.history.pressed_within(keycode, window, self.handler.frame_start())
+    }
+}
+
+// history
+impl Input<'_>
+{
+    /// the latest presses and releases
+    pub fn history(&self) -> &InputHistory
+    {
+        &self.handler.history
+    }
+
+    /// returns true the frame the combo is completed
+    pub fn combo_performed(&self, combo: &Combo) -> bool
+    {
+        self.handler.history.matches(combo)
+    }
+}
+
+// time
+impl Input<'_>
+{
+    /// how long the previous frame lasted, the recorded one during a playback
+    pub fn frame_delta(&self) -> Duration
+    {
+        self.handler.frame_delta()
+    }
 }
 
 // mouse
 impl Input<'_>
 {
+    /// the position of the cursor in pixels, relative to the top left corner of the window
+    pub fn cursor_position(&self) -> baguette_math::Vec2
+    {
+        self.handler.cursor_position
+    }
+
     // returns true the first frame the mouse button is pressed
     pub fn get_mouse_button_down(&self, click: MouseButton) -> bool
     {
@@ -236,4 +543,71 @@ impl Input<'_>
             None => false
         }  
     }
+
+    /// returns true if the mouse button was pressed in the last `window` of time,
+    /// even if it was already released.
+    ///
+    /// the time is measured from the start of the frame, see [InputHandler::frame_start]
+    pub fn get_mouse_button_pressed_within(&self, click: MouseButton, window: Duration) -> bool
+    {
+        self.handler.history.pressed_within(click, window, self.handler.frame_start())
+    }
+}
+
+// touch
+impl Input<'_>
+{
+    /// iters the fingers touching the screen, in the order they touched it
+    pub fn touches(&self) -> impl Iterator<Item = &Finger>
+    {
+        self.handler.touch.fingers.iter()
+    }
+
+    /// returns the finger with this id, if it's touching the screen or was lifted this frame
+    pub fn get_touch(&self, id: u64) -> Option<&Finger>
+    {
+        self.handler.touch.fingers.iter().find(|finger| finger.id == id)
+    }
+
+    /// returns how many fingers are currently touching the screen
+    pub fn touch_count(&self) -> usize
+    {
+        self.handler.touch.fingers.iter().filter(|finger| finger.is_active()).count()
+    }
+
+    /// the gestures recognized this frame
+    pub fn gestures(&self) -> &[Gesture]
+    {
+        &self.handler.touch.gestures
+    }
+
+    /// the combined pinch of this frame, `1.` if there was no pinch
+    pub fn pinch_scale(&self) -> f32
+    {
+        self.gestures().iter().fold(1., |scale, gesture| match gesture
+        {
+            Gesture::Pinch { scale: pinch, .. } => scale * pinch,
+            _ => scale
+        })
+    }
+
+    /// the combined rotation in radians of this frame
+    pub fn rotation_delta(&self) -> f32
+    {
+        self.gestures().iter().fold(0., |angle, gesture| match gesture
+        {
+            Gesture::Rotate { angle: rotation, .. } => angle + rotation,
+            _ => angle
+        })
+    }
+
+    /// the combined pan of this frame
+    pub fn pan_delta(&self) -> baguette_math::Vec2
+    {
+        self.gestures().iter().fold(baguette_math::Vec2::ZERO, |delta, gesture| match gesture
+        {
+            Gesture::Pan { delta: pan } => delta + *pan,
+            _ => delta
+        })
+    }
 }
\ No newline at end of file
@@ -0,0 +1,307 @@
+//! recording and deterministic playback of the input
+
+use std::time::{Duration, Instant};
+
+use crate::InputEvent;
+
+/// an [InputEvent] with the time it was received, relative to the start of the recording
+#[derive(Debug, Clone, Copy)]
+#[derive(serde::Serialize, serde::Deserialize)]
+pub struct TimedEvent
+{
+    pub time: Duration,
+    pub event: InputEvent
+}
+
+/// the events received during a single frame
+#[derive(Debug, Clone, Default)]
+#[derive(serde::Serialize, serde::Deserialize)]
+pub struct RecordedFrame
+{
+    /// when the frame ended, relative to the start of the recording
+    pub time: Duration,
+    pub events: Vec<TimedEvent>
+}
+
+/// a sequence of recorded frames that can be saved to a file and played back
+/// with [crate::InputHandler::start_playback]
+#[derive(Debug, Clone, Default)]
+#[derive(serde::Serialize, serde::Deserialize)]
+pub struct InputRecording
+{
+    pub frames: Vec<RecordedFrame>
+}
+
+impl InputRecording
+{
+    /// the length of the recording
+    pub fn duration(&self) -> Duration
+    {
+        self.frames.last().map(|frame| frame.time).unwrap_or_default()
+    }
+
+    pub fn to_bytes(&self) -> Result<Vec<u8>, Box<bincode::ErrorKind>>
+    {
+        bincode::serialize(self)
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, Box<bincode::ErrorKind>>
+    {
+        bincode::deserialize::<Self>(bytes)
+    }
+
+    /// writes the recording to a file, creating it if it doesn't exist
+    pub fn save(&self, path: impl AsRef<std::path::Path>) -> Result<(), ReplayError>
+    {
+        std::fs::write(path, self.to_bytes().map_err(ReplayError::Encode)?)?;
+
+        Ok(())
+    }
+
+    pub fn from_path(path: impl AsRef<std::path::Path>) -> Result<Self, ReplayError>
+    {
+        Self::from_bytes(&std::fs::read(path)?).map_err(ReplayError::Decode)
+    }
+}
+
+/// errors that can occur when saving or loading an [InputRecording]
+#[derive(Debug)]
+pub enum ReplayError
+{
+    /// the file couldn't be read or written
+    Io(std::io::Error),
+    /// the recording couldn't be encoded
+    Encode(Box<bincode::ErrorKind>),
+    /// the file isn't a recording, or was made by an incompatible version
+    Decode(Box<bincode::ErrorKind>)
+}
+
+impl std::fmt::Display for ReplayError
+{
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result
+    {
+        match self
+        {
+            ReplayError::Io(err) => write!(f, "failed to access the input recording: {err}"),
+            ReplayError::Encode(err) => write!(f, "failed to encode the input recording: {err}"),
+            ReplayError::Decode(err) => write!(f, "failed to decode the input recording: {err}")
+        }
+    }
+}
+
+impl std::error::Error for ReplayError {}
+
+impl From<std::io::Error> for ReplayError
+{
+    fn from(err: std::io::Error) -> Self { Self::Io(err) }
+}
+
+/// where the [crate::InputHandler] takes its events from
+#[derive(Default)]
+pub(crate) enum InputSource
+{
+    #[default] Live,
+    /// live events that are also being recorded
+    Recording(InputRecorder),
+    /// live events are ignored and replaced by the recorded ones
+    Playback(InputPlayback)
+}
+
+pub(crate) struct InputRecorder
+{
+    start: Instant,
+    current: RecordedFrame,
+    recording: InputRecording
+}
+
+impl InputRecorder
+{
+    pub fn new(start: Instant) -> Self
+    {
+        Self { start, current: Default::default(), recording: Default::default() }
+    }
+
+    pub fn record(&mut self, event: InputEvent, now: Instant)
+    {
+        self.current.events.push(TimedEvent { time: now.saturating_duration_since(self.start), event })
+    }
+
+    /// closes the current frame
+    pub fn next_frame(&mut self, now: Instant)
+    {
+        let mut frame = std::mem::take(&mut self.current);
+        frame.time = now.saturating_duration_since(self.start);
+
+        self.recording.frames.push(frame)
+    }
+
+    /// closes the current frame and returns the recording
+    pub fn finish(mut self, now: Instant) -> InputRecording
+    {
+        if !self.current.events.is_empty()
+        {
+            self.next_frame(now)
+        }
+
+        self.recording
+    }
+}
+
+pub(crate) struct InputPlayback
+{
+    /// when the playback started, the recorded times are offset by this
+    start: Instant,
+    frames: std::vec::IntoIter<RecordedFrame>,
+    /// the end time of the frame being played back
+    frame_time: Option<Duration>
+}
+
+impl InputPlayback
+{
+    pub fn new(recording: InputRecording, start: Instant) -> Self
+    {
+        Self { start, frames: recording.frames.into_iter(), frame_time: None }
+    }
+
+    /// returns the events of the next frame and the instant their time is relative to,
+    /// [None] if the recording ended
+    pub fn next_frame(&mut self) -> Option<(Vec<TimedEvent>, Instant)>
+    {
+        let frame = self.frames.next()?;
+        self.frame_time = Some(frame.time);
+
+        Some((frame.events, self.start))
+    }
+
+    pub fn frame_time(&self) -> Option<Duration>
+    {
+        self.frame_time
+    }
+}
+
+/// when the current frame began and how long the previous one lasted
+pub(crate) struct FrameClock
+{
+    pub start: Instant,
+    pub delta: Duration
+}
+
+impl Default for FrameClock
+{
+    fn default() -> Self
+    {
+        Self::new(Instant::now())
+    }
+}
+
+impl FrameClock
+{
+    pub fn new(start: Instant) -> Self
+    {
+        Self { start, delta: Duration::ZERO }
+    }
+
+    pub fn next_frame(&mut self, start: Instant)
+    {
+        self.delta = start.saturating_duration_since(self.start);
+        self.start = start
+    }
+}
+
+#[cfg(test)]
+mod tests
+{
+    use std::time::Duration;
+
+    use winit::event::{DeviceId, ElementState, MouseButton, WindowEvent};
+
+    use crate::{Input, InputHandler, InputRecording, RecordedFrame, ReplayError};
+
+    fn click(input: &mut InputHandler, state: ElementState)
+    {
+        input.check(&WindowEvent::MouseInput { device_id: DeviceId::dummy(), state, button: MouseButton::Left })
+    }
+
+    /// what a state could ask during a frame
+    fn queries(input: &InputHandler) -> (bool, bool, bool, Duration)
+    {
+        let input_ref = Input::from(input);
+
+        (
+            input_ref.get_mouse_button_holding(MouseButton::Left),
+            input_ref.get_mouse_button_pressed_within(MouseButton::Left, Duration::from_millis(20)),
+            input_ref.get_mouse_button_pressed_within(MouseButton::Left, Duration::from_secs(10)),
+            input.frame_delta()
+        )
+    }
+
+    #[test]
+    fn replay_answers_like_the_recording()
+    {
+        let mut input = InputHandler::default();
+        let mut recorded = vec![];
+
+        input.start_recording();
+
+        click(&mut input, ElementState::Pressed);
+        recorded.push(queries(&input));
+        input.flush_released_keys();
+
+        std::thread::sleep(Duration::from_millis(30));
+
+        recorded.push(queries(&input));
+        input.flush_released_keys();
+
+        click(&mut input, ElementState::Released);
+        recorded.push(queries(&input));
+        input.flush_released_keys();
+
+        let recording = input.stop_recording().expect("it was recording");
+        let recording = InputRecording::from_bytes(&recording.to_bytes().unwrap()).unwrap();
+
+        assert_eq!(recording.frames.len(), 3);
+
+        // the press is buffered for 20ms only in the frame it happened
+        assert_eq!(recorded.iter().map(|(_, recent, ..)| *recent).collect::<Vec<_>>(), [true, true, false]);
+        assert!(recorded[2].3 >= Duration::from_millis(30));
+
+        // replayed without waiting, only the recorded times matter
+        let mut replay = InputHandler::default();
+        let mut replayed = vec![];
+
+        replay.start_playback(recording);
+
+        for _ in 0..3
+        {
+            replay.flush_released_keys();
+            replayed.push(queries(&replay));
+        }
+
+        assert_eq!(recorded, replayed);
+
+        replay.flush_released_keys();
+        assert!(!replay.is_playing_back());
+    }
+
+    #[test]
+    fn loading_reports_missing_and_corrupt_files()
+    {
+        let dir = std::env::temp_dir().join(format!("baguette-replay-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+
+        let missing = InputRecording::from_path(dir.join("missing.replay"));
+        assert!(matches!(missing, Err(ReplayError::Io(_))));
+
+        let corrupt = dir.join("corrupt.replay");
+        std::fs::write(&corrupt, [0xff; 3]).unwrap();
+        assert!(matches!(InputRecording::from_path(&corrupt), Err(ReplayError::Decode(_))));
+
+        let saved = dir.join("saved.replay");
+        InputRecording { frames: vec![RecordedFrame { time: Duration::from_millis(16), events: vec![] }] }
+            .save(&saved)
+            .unwrap();
+        assert_eq!(InputRecording::from_path(&saved).unwrap().duration(), Duration::from_millis(16));
+
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+}
@@ -0,0 +1,535 @@
+//! multi-touch tracking and gesture recognition
+
+use std::time::{Duration, Instant};
+
+use baguette_math::Vec2;
+use winit::event::TouchPhase;
+
+/// the lifetime of a finger touching the screen
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FingerPhase
+{
+    /// the finger touched the screen this frame
+    Began,
+    /// the finger moved this frame
+    Moved,
+    /// the finger is touching the screen but didn't move this frame
+    Stationary,
+    /// the finger was lifted this frame
+    Ended,
+    /// the system cancelled the touch this frame, no gesture will be recognized for it
+    Cancelled
+}
+
+/// a finger currently touching the screen
+#[derive(Debug, Clone, Copy)]
+pub struct Finger
+{
+    /// unique identifier of this finger, stays the same until the finger is lifted
+    pub id: u64,
+    pub phase: FingerPhase,
+    /// the position in pixels of the finger
+    pub position: Vec2,
+    /// the position in pixels of the finger on the previous event
+    pub previous_position: Vec2,
+    /// where the finger first touched the screen
+    pub start_position: Vec2,
+    /// the pressure of the touch between `0.` and `1.`,
+    /// [None] if the platform doesn't support pressure sensitivity
+    pub force: Option<f32>,
+    /// when the finger first touched the screen
+    pub started_at: Instant
+}
+
+impl Finger
+{
+    /// how much the finger moved since the previous event
+    pub fn delta(&self) -> Vec2
+    {
+        self.position - self.previous_position
+    }
+
+    /// how far the finger moved from where it started
+    pub fn travel(&self) -> Vec2
+    {
+        self.position - self.start_position
+    }
+
+    /// returns true if the finger is still on the screen
+    pub fn is_active(&self) -> bool
+    {
+        !matches!(self.phase, FingerPhase::Ended | FingerPhase::Cancelled)
+    }
+}
+
+/// the direction of a [Gesture::Swipe], in screen space
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SwipeDirection
+{
+    Left,
+    Right,
+    Up,
+    Down
+}
+
+impl SwipeDirection
+{
+    fn from_travel(travel: Vec2) -> Self
+    {
+        match travel.x.abs() >= travel.y.abs()
+        {
+            true => match travel.x >= 0.
+            {
+                true => Self::Right,
+                false => Self::Left
+            },
+            // winit's y axis grows downward
+            false => match travel.y >= 0.
+            {
+                true => Self::Down,
+                false => Self::Up
+            }
+        }
+    }
+}
+
+/// a recognized gesture, these only last for the frame they were recognized in
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Gesture
+{
+    /// a finger was quickly pressed and lifted without moving
+    Tap { position: Vec2 },
+    /// two taps close in both time and space, the second one is also reported as a [Gesture::Tap]
+    DoubleTap { position: Vec2 },
+    /// a finger was quickly dragged across the screen and lifted
+    Swipe
+    {
+        direction: SwipeDirection,
+        start: Vec2,
+        end: Vec2,
+        /// the average speed of the swipe in pixels per second
+        velocity: f32
+    },
+    /// two fingers moved apart or closer, or the touchpad reported a pinch.
+    ///
+    /// `scale` is the multiplicative change since the last event, above `1.` when zooming in
+    Pinch { scale: f32, center: Vec2 },
+    /// two fingers rotated around each other, or the touchpad reported a rotation.
+    ///
+    /// `angle` is in radians since the last event, positive is counterclockwise
+    Rotate { angle: f32, center: Vec2 },
+    /// two fingers moved together, or the touchpad reported a pan
+    Pan { delta: Vec2 }
+}
+
+/// thresholds used to recognize gestures, the distances are in pixels
+#[derive(Debug, Clone, Copy)]
+pub struct GestureConfig
+{
+    /// the max time a finger can stay down to be considered a tap
+    pub tap_max_duration: Duration,
+    /// the max distance a finger can move to be considered a tap
+    pub tap_max_distance: f32,
+    /// the max time between two taps to be considered a double tap
+    pub double_tap_interval: Duration,
+    /// the max distance between two taps to be considered a double tap
+    pub double_tap_max_distance: f32,
+    /// the min distance a finger has to travel to be considered a swipe
+    pub swipe_min_distance: f32,
+    /// the max time a swipe can last
+    pub swipe_max_duration: Duration
+}
+
+impl Default for GestureConfig
+{
+    fn default() -> Self
+    {
+        Self
+        {
+            tap_max_duration: Duration::from_millis(250),
+            tap_max_distance: 20.,
+            double_tap_interval: Duration::from_millis(300),
+            double_tap_max_distance: 40.,
+            swipe_min_distance: 50.,
+            swipe_max_duration: Duration::from_millis(500),
+        }
+    }
+}
+
+/// tracks the fingers on the screen and recognizes gestures
+#[derive(Default)]
+pub(crate) struct TouchState
+{
+    /// fingers in the order they touched the screen
+    pub fingers: Vec<Finger>,
+    /// gestures recognized this frame
+    pub gestures: Vec<Gesture>,
+    pub config: GestureConfig,
+    /// the time and position of the last tap, used to detect double taps
+    last_tap: Option<(Instant, Vec2)>
+}
+
+impl TouchState
+{
+    pub fn touch(&mut self, id: u64, phase: TouchPhase, position: Vec2, force: Option<f32>, now: Instant)
+    {
+        match phase
+        {
+            TouchPhase::Started =>
+            {
+                // a finger can't start twice, if the platform lost an end event we just replace it
+                self.fingers.retain(|finger| finger.id != id);
+
+                self.fingers.push(Finger
+                {
+                    id,
+                    phase: FingerPhase::Began,
+                    position,
+                    previous_position: position,
+                    start_position: position,
+                    force,
+                    started_at: now,
+                })
+            }
+
+            TouchPhase::Moved =>
+            {
+                let before = self.pair();
+
+                if let Some(finger) = self.finger_mut(id)
+                {
+                    finger.previous_position = finger.position;
+                    finger.position = position;
+                    finger.force = force;
+
+                    if finger.phase != FingerPhase::Began
+                    {
+                        finger.phase = FingerPhase::Moved
+                    }
+                }
+
+                if let (Some(before), Some(after)) = (before, self.pair())
+                {
+                    if before.ids.contains(&id)
+                    {
+                        self.recognize_two_fingers(before, after)
+                    }
+                }
+            }
+
+            TouchPhase::Ended =>
+            {
+                if let Some(finger) = self.finger_mut(id)
+                {
+                    finger.previous_position = finger.position;
+                    finger.position = position;
+                    finger.phase = FingerPhase::Ended;
+
+                    let finger = *finger;
+                    self.recognize_release(finger, now)
+                }
+            }
+
+            TouchPhase::Cancelled =>
+            {
+                if let Some(finger) = self.finger_mut(id)
+                {
+                    finger.phase = FingerPhase::Cancelled
+                }
+            }
+        }
+    }
+
+    /// handles the gesture events reported by touchpads
+    pub fn pinch(&mut self, delta: f64, center: Vec2)
+    {
+        self.gestures.push(Gesture::Pinch { scale: 1. + delta as f32, center })
+    }
+
+    pub fn rotate(&mut self, degrees: f32, center: Vec2)
+    {
+        self.gestures.push(Gesture::Rotate { angle: degrees.to_radians(), center })
+    }
+
+    pub fn pan(&mut self, delta: Vec2)
+    {
+        self.gestures.push(Gesture::Pan { delta })
+    }
+
+    pub fn double_tap(&mut self, position: Vec2)
+    {
+        self.gestures.push(Gesture::DoubleTap { position })
+    }
+
+    /// removes the lifted fingers and clears the gestures of this frame
+    pub fn flush(&mut self)
+    {
+        self.fingers.retain(Finger::is_active);
+
+        self.fingers.iter_mut().for_each(|finger|
+        {
+            finger.phase = FingerPhase::Stationary;
+            finger.previous_position = finger.position
+        });
+
+        self.gestures.clear()
+    }
+
+    fn finger_mut(&mut self, id: u64) -> Option<&mut Finger>
+    {
+        self.fingers.iter_mut().find(|finger| finger.id == id)
+    }
+
+    /// the first two fingers still on the screen
+    fn pair(&self) -> Option<FingerPair>
+    {
+        let mut active = self.fingers.iter().filter(|finger| finger.is_active());
+
+        let (a, b) = (active.next()?, active.next()?);
+        let offset = b.position - a.position;
+
+        Some(FingerPair
+        {
+            ids: [a.id, b.id],
+            center: (a.position + b.position) * 0.5,
+            distance: offset.length(),
+            // flip the y so that positive angles are counterclockwise on screen
+            angle: f32::atan2(-offset.y, offset.x)
+        })
+    }
+
+    fn recognize_two_fingers(&mut self, before: FingerPair, after: FingerPair)
+    {
+        // the pair changed, ignore the jump
+        if before.ids != after.ids
+        {
+            return
+        }
+
+        if before.distance > f32::EPSILON
+        {
+            let scale = after.distance / before.distance;
+
+            if scale != 1.
+            {
+                self.gestures.push(Gesture::Pinch { scale, center: after.center })
+            }
+        }
+
+        let angle = wrap_angle(after.angle - before.angle);
+
+        if angle != 0.
+        {
+            self.gestures.push(Gesture::Rotate { angle, center: after.center })
+        }
+
+        let delta = after.center - before.center;
+
+        if delta != Vec2::ZERO
+        {
+            self.gestures.push(Gesture::Pan { delta })
+        }
+    }
+
+    fn recognize_release(&mut self, finger: Finger, now: Instant)
+    {
+        let config = &self.config;
+
+        let duration = now.saturating_duration_since(finger.started_at);
+        let distance = finger.travel().length();
+
+        if duration <= config.tap_max_duration && distance <= config.tap_max_distance
+        {
+            let position = finger.position;
+
+            self.gestures.push(Gesture::Tap { position });
+
+            match self.last_tap.take()
+            {
+                Some((time, last))
+                if now.saturating_duration_since(time) <= config.double_tap_interval
+                && last.distance(position) <= config.double_tap_max_distance =>
+                {
+                    self.gestures.push(Gesture::DoubleTap { position })
+                }
+                _ => self.last_tap = Some((now, position))
+            }
+        }
+        else if duration <= config.swipe_max_duration && distance >= config.swipe_min_distance
+        {
+            self.gestures.push(Gesture::Swipe
+            {
+                direction: SwipeDirection::from_travel(finger.travel()),
+                start: finger.start_position,
+                end: finger.position,
+                velocity: distance / duration.as_secs_f32().max(f32::EPSILON)
+            })
+        }
+    }
+}
+
+/// the state of the first two fingers on the screen
+#[derive(Clone, Copy)]
+struct FingerPair
+{
+    ids: [u64; 2],
+    center: Vec2,
+    distance: f32,
+    angle: f32
+}
+
+/// wraps the angle between `-PI` and `PI`
+fn wrap_angle(angle: f32) -> f32
+{
+    use std::f32::consts::{PI, TAU};
+
+    (angle + PI).rem_euclid(TAU) - PI
+}
+
+#[cfg(test)]
+mod tests
+{
+    use winit::dpi::PhysicalPosition;
+    use winit::event::{DeviceId, Touch, WindowEvent};
+
+    use crate::{Input, InputHandler};
+    use super::*;
+
+    fn touch(input: &mut InputHandler, id: u64, phase: TouchPhase, x: f64, y: f64)
+    {
+        input.check(&WindowEvent::Touch(Touch
+        {
+            device_id: DeviceId::dummy(),
+            phase,
+            location: PhysicalPosition::new(x, y),
+            force: None,
+            id
+        }))
+    }
+
+    fn tap(input: &mut InputHandler, x: f64, y: f64)
+    {
+        touch(input, 0, TouchPhase::Started, x, y);
+        touch(input, 0, TouchPhase::Ended, x, y);
+    }
+
+    fn gestures(input: &InputHandler) -> Vec<Gesture>
+    {
+        Input::from(input).gestures().to_vec()
+    }
+
+    #[test]
+    fn tap_is_recognized()
+    {
+        let mut input = InputHandler::default();
+
+        touch(&mut input, 0, TouchPhase::Started, 100., 100.);
+        assert_eq!(Input::from(&input).touch_count(), 1);
+
+        touch(&mut input, 0, TouchPhase::Ended, 102., 101.);
+        assert_eq!(gestures(&input), [Gesture::Tap { position: Vec2::new(102., 101.) }]);
+
+        input.flush_released_keys();
+        assert!(gestures(&input).is_empty());
+        assert_eq!(Input::from(&input).touch_count(), 0);
+    }
+
+    #[test]
+    fn double_tap_is_recognized()
+    {
+        let mut input = InputHandler::default();
+
+        tap(&mut input, 100., 100.);
+        input.flush_released_keys();
+        tap(&mut input, 110., 105.);
+
+        let position = Vec2::new(110., 105.);
+        assert_eq!(gestures(&input), [Gesture::Tap { position }, Gesture::DoubleTap { position }]);
+
+        // a third tap starts a new pair
+        input.flush_released_keys();
+        tap(&mut input, 110., 105.);
+        assert_eq!(gestures(&input), [Gesture::Tap { position }]);
+    }
+
+    #[test]
+    fn far_taps_are_not_a_double_tap()
+    {
+        let mut input = InputHandler::default();
+
+        tap(&mut input, 0., 0.);
+        input.flush_released_keys();
+        tap(&mut input, 300., 0.);
+
+        assert_eq!(gestures(&input), [Gesture::Tap { position: Vec2::new(300., 0.) }]);
+    }
+
+    #[test]
+    fn swipe_is_recognized()
+    {
+        let mut input = InputHandler::default();
+
+        touch(&mut input, 0, TouchPhase::Started, 200., 300.);
+        touch(&mut input, 0, TouchPhase::Moved, 200., 200.);
+        touch(&mut input, 0, TouchPhase::Ended, 210., 100.);
+
+        let [Gesture::Swipe { direction, start, end, velocity }] = gestures(&input)[..]
+        else
+        {
+            panic!("expected a swipe, got {:?}", gestures(&input))
+        };
+
+        // winit's y axis grows downward
+        assert_eq!(direction, SwipeDirection::Up);
+        assert_eq!(start, Vec2::new(200., 300.));
+        assert_eq!(end, Vec2::new(210., 100.));
+        assert!(velocity > 0.);
+    }
+
+    #[test]
+    fn cancelled_touch_is_not_a_gesture()
+    {
+        let mut input = InputHandler::default();
+
+        touch(&mut input, 0, TouchPhase::Started, 0., 0.);
+        touch(&mut input, 0, TouchPhase::Cancelled, 0., 0.);
+
+        assert!(gestures(&input).is_empty());
+    }
+
+    #[test]
+    fn two_fingers_pinch()
+    {
+        let mut input = InputHandler::default();
+
+        touch(&mut input, 0, TouchPhase::Started, 100., 100.);
+        touch(&mut input, 1, TouchPhase::Started, 200., 100.);
+        input.flush_released_keys();
+
+        touch(&mut input, 1, TouchPhase::Moved, 300., 100.);
+
+        assert_eq!(gestures(&input), [Gesture::Pinch { scale: 2., center: Vec2::new(200., 100.) }, Gesture::Pan { delta: Vec2::new(50., 0.) }]);
+        assert_eq!(Input::from(&input).pinch_scale(), 2.);
+        assert_eq!(Input::from(&input).get_touch(1).map(Finger::delta), Some(Vec2::new(100., 0.)));
+    }
+
+    #[test]
+    fn touchpad_gestures()
+    {
+        let mut input = InputHandler::default();
+
+        input.check(&WindowEvent::PinchGesture { device_id: DeviceId::dummy(), delta: 0.5, phase: TouchPhase::Moved });
+        input.check(&WindowEvent::PanGesture
+        {
+            device_id: DeviceId::dummy(),
+            delta: PhysicalPosition::new(3., -4.),
+            phase: TouchPhase::Moved
+        });
+        input.check(&WindowEvent::PinchGesture { device_id: DeviceId::dummy(), delta: 0.2, phase: TouchPhase::Moved });
+
+        let input = Input::from(&input);
+
+        assert!((input.pinch_scale() - 1.8).abs() < 1e-5);
+        assert_eq!(input.pan_delta(), Vec2::new(3., -4.));
+    }
+}
@@ -0,0 +1,28 @@
+//! 2d collision shapes and the queries between them
+//!
+//! every shape is convex, so the queries work between any pair of them
+//!
+//! # example
+//!
+//! ```
+//! use baguette_math::{vec2, Aabb, Capsule, Shape};
+//!
+//! let mut position = vec2(0., 0.);
+//!
+//! let player = Shape::from(Capsule::new(vec2(0., 0.), vec2(0., 1.), 0.4));
+//! let wall = Shape::from(Aabb::new(vec2(0.2, -1.), vec2(2., 3.)));
+//!
+//! if let Some(contact) = player.contact(&wall)
+//! {
+//!     // push the player out of the wall
+//!     position -= contact.normal * contact.depth()
+//! }
+//! ```
+
+mod hull;
+
+mod shapes;
+pub use shapes::*;
+
+mod query;
+pub use query::*;
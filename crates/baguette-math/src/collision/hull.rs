@@ -0,0 +1,207 @@
+use glam::{vec2, Vec2};
+
+enum Points<'a>
+{
+    Inline([Vec2; 4], usize),
+    Slice(&'a [Vec2])
+}
+
+/// a convex set of points with a radius around it, every shape can be described by one:
+/// a circle is a point, a capsule is a segment and the boxes are polygons without a radius.
+///
+/// the points are counterclockwise, a segment has an edge for each side
+pub(crate) struct Hull<'a>
+{
+    points: Points<'a>,
+    pub radius: f32
+}
+
+impl<'a> Hull<'a>
+{
+    pub fn inline(points: [Vec2; 4], len: usize, radius: f32) -> Self
+    {
+        Self { points: Points::Inline(points, len), radius }
+    }
+
+    pub fn slice(points: &'a [Vec2], radius: f32) -> Self
+    {
+        Self { points: Points::Slice(points), radius }
+    }
+
+    pub fn points(&self) -> &[Vec2]
+    {
+        match &self.points
+        {
+            Points::Inline(points, len) => &points[..*len],
+            Points::Slice(points) => points
+        }
+    }
+
+    /// a point doesn't have edges, a segment has two
+    pub fn edges(&self) -> usize
+    {
+        match self.points().len()
+        {
+            0 | 1 => 0,
+            len => len
+        }
+    }
+
+    pub fn edge(&self, index: usize) -> (Vec2, Vec2)
+    {
+        let points = self.points();
+        (points[index], points[(index + 1) % points.len()])
+    }
+
+    /// the outward normal of the edge
+    pub fn normal(&self, index: usize) -> Vec2
+    {
+        let (a, b) = self.edge(index);
+        let edge = b - a;
+
+        vec2(edge.y, -edge.x).normalize_or_zero()
+    }
+
+    /// the edge of this hull that separates it the most from `other` when it's offset,
+    /// a negative separation is a penetration. [None] if this hull has no edges
+    pub fn max_separation(&self, offset: Vec2, other: &Hull, other_offset: Vec2) -> Option<(f32, usize)>
+    {
+        (0..self.edges())
+            .map(|index|
+            {
+                let normal = self.normal(index);
+                let vertex = self.points()[index] + offset;
+
+                let separation = other.points()
+                    .iter()
+                    .map(|point| normal.dot(*point + other_offset - vertex))
+                    .fold(f32::INFINITY, f32::min);
+
+                (separation, index)
+            })
+            .max_by(|(a, _), (b, _)| a.total_cmp(b))
+    }
+
+    /// the distance from the point to the core of the hull, zero if it's inside
+    pub fn point_distance(&self, point: Vec2) -> f32
+    {
+        let points = self.points();
+
+        if self.edges() >= 3 && (0..self.edges()).all(|index| self.normal(index).dot(point - points[index]) <= 0.)
+        {
+            return 0.
+        }
+
+        match self.edges()
+        {
+            0 => point.distance(points[0]),
+            edges => (0..edges)
+                .map(|index|
+                {
+                    let (a, b) = self.edge(index);
+                    point.distance(closest_on_segment(point, a, b))
+                })
+                .fold(f32::INFINITY, f32::min)
+        }
+    }
+
+    /// the distance between the cores and their closest points, when this hull is offset.
+    /// [None] if the cores overlap
+    pub fn closest_points(&self, offset: Vec2, other: &Hull) -> Option<(f32, Vec2, Vec2)>
+    {
+        // the edge normals separate two polygons, but between points and segments they miss
+        // the axis along the segments, like a circle right above the end of a capsule
+        let degenerate = self.points().len() < 3 && other.points().len() < 3;
+
+        if !degenerate && !self.separated(offset, other)
+        {
+            return None
+        }
+
+        let mut closest = (f32::INFINITY, Vec2::ZERO, Vec2::ZERO);
+
+        // the closest points are always a vertex and a point on an edge, or two vertices
+        let mut check = |from: &Hull, from_offset: Vec2, to: &Hull, to_offset: Vec2, flip: bool|
+        {
+            for point in from.points().iter().map(|point| *point + from_offset)
+            {
+                let on_other = match to.edges()
+                {
+                    0 => to.points()
+                        .iter()
+                        .map(|other| *other + to_offset)
+                        .min_by(|a, b| a.distance_squared(point).total_cmp(&b.distance_squared(point)))
+                        .expect("a hull always has a point"),
+                    edges => (0..edges)
+                        .map(|index|
+                        {
+                            let (a, b) = to.edge(index);
+                            closest_on_segment(point, a + to_offset, b + to_offset)
+                        })
+                        .min_by(|a, b| a.distance_squared(point).total_cmp(&b.distance_squared(point)))
+                        .expect("a hull with edges has points")
+                };
+
+                let distance = point.distance(on_other);
+
+                if distance < closest.0
+                {
+                    closest = match flip
+                    {
+                        false => (distance, point, on_other),
+                        true => (distance, on_other, point)
+                    }
+                }
+            }
+        };
+
+        check(self, offset, other, Vec2::ZERO, false);
+        check(other, Vec2::ZERO, self, offset, true);
+
+        // points and segments overlap when they touch or cross, two points are never overlapping
+        // so that there's always an edge to build a contact from
+        if degenerate && self.edges() + other.edges() > 0 && (closest.0 <= f32::EPSILON || self.crosses(offset, other))
+        {
+            return None
+        }
+
+        Some(closest)
+    }
+
+    /// returns true if an edge normal of either hull separates their cores, when this hull is offset
+    fn separated(&self, offset: Vec2, other: &Hull) -> bool
+    {
+        match (self.max_separation(offset, other, Vec2::ZERO), other.max_separation(Vec2::ZERO, self, offset))
+        {
+            (None, None) => true,
+            (a, b) => a.into_iter().chain(b).any(|(separation, _)| separation > 0.)
+        }
+    }
+
+    /// returns true if both hulls are segments crossing each other, when this hull is offset
+    fn crosses(&self, offset: Vec2, other: &Hull) -> bool
+    {
+        let (&[a1, b1], &[a2, b2]) = (self.points(), other.points())
+        else
+        {
+            return false
+        };
+
+        let (a1, b1) = (a1 + offset, b1 + offset);
+        let side = |a: Vec2, b: Vec2, point: Vec2| (b - a).perp_dot(point - a);
+
+        side(a1, b1, a2) * side(a1, b1, b2) < 0. && side(a2, b2, a1) * side(a2, b2, b1) < 0.
+    }
+}
+
+pub(crate) fn closest_on_segment(point: Vec2, a: Vec2, b: Vec2) -> Vec2
+{
+    let edge = b - a;
+    let length = edge.length_squared();
+
+    match length > f32::EPSILON
+    {
+        true => a + edge * ((point - a).dot(edge) / length).clamp(0., 1.),
+        false => a
+    }
+}
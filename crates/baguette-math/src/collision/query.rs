@@ -0,0 +1,626 @@
+use glam::Vec2;
+
+use super::hull::Hull;
+use super::Shape;
+
+/// the features closer than this are considered parallel when building a contact
+const PARALLEL_TOLERANCE: f32 = 1e-3;
+/// the maximum steps of a shape cast
+const MAX_CAST_ITERATIONS: usize = 32;
+/// a shape cast stops once the shapes are closer than this
+const CAST_TOLERANCE: f32 = 1e-4;
+
+/// a point where two shapes touch
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub struct ContactPoint
+{
+    /// halfway between the surfaces of the shapes
+    pub position: Vec2,
+    /// how much the shapes overlap at this point, along the normal
+    pub depth: f32
+}
+
+/// how two shapes overlap, with up to two points when their edges are touching
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Contact
+{
+    /// points from the first shape to the second,
+    /// moving the second shape along it by the depth separates them
+    pub normal: Vec2,
+    points: [ContactPoint; 2],
+    len: usize
+}
+
+impl Contact
+{
+    fn new(normal: Vec2) -> Self
+    {
+        Self { normal, points: Default::default(), len: 0 }
+    }
+
+    fn push(&mut self, point: ContactPoint)
+    {
+        self.points[self.len] = point;
+        self.len += 1
+    }
+
+    pub fn points(&self) -> &[ContactPoint]
+    {
+        &self.points[..self.len]
+    }
+
+    /// the deepest penetration among the points
+    pub fn depth(&self) -> f32
+    {
+        self.points().iter().map(|point| point.depth).fold(0., f32::max)
+    }
+
+    /// the same contact seen from the other shape
+    pub fn flipped(&self) -> Self
+    {
+        Self { normal: -self.normal, ..*self }
+    }
+}
+
+/// a half line, the direction is normalized
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Ray
+{
+    pub origin: Vec2,
+    pub direction: Vec2
+}
+
+impl Ray
+{
+    pub fn new(origin: Vec2, direction: Vec2) -> Self
+    {
+        Self { origin, direction: direction.normalize_or_zero() }
+    }
+
+    /// the point at this distance from the origin
+    pub fn at(&self, distance: f32) -> Vec2
+    {
+        self.origin + self.direction * distance
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RayHit
+{
+    /// the distance from the origin of the ray
+    pub distance: f32,
+    pub point: Vec2,
+    /// the normal of the surface that was hit
+    pub normal: Vec2
+}
+
+/// where a moving shape first touches another
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ShapeHit
+{
+    /// the portion of the motion done before touching, between `0.` and `1.`
+    pub time: f32,
+    pub point: Vec2,
+    /// the normal of the surface that was hit, pointing towards the moving shape
+    pub normal: Vec2
+}
+
+impl Shape
+{
+    /// returns true if the shapes overlap or touch
+    pub fn intersects(&self, other: &Shape) -> bool
+    {
+        self.aabb().intersects(&other.aabb()) && self.contact(other).is_some()
+    }
+
+    /// how the shapes overlap, [None] if they don't
+    pub fn contact(&self, other: &Shape) -> Option<Contact>
+    {
+        contact(&self.hull(), &other.hull())
+    }
+
+    /// the distance between the surfaces of the shapes, zero if they overlap
+    pub fn distance(&self, other: &Shape) -> f32
+    {
+        let (a, b) = (self.hull(), other.hull());
+
+        a.closest_points(Vec2::ZERO, &b).map_or(0., |(distance, ..)| (distance - a.radius - b.radius).max(0.))
+    }
+
+    /// the first point of the shape hit by the ray within `max_distance`.
+    /// a ray starting inside the shape doesn't hit it
+    pub fn raycast(&self, ray: Ray, max_distance: f32) -> Option<RayHit>
+    {
+        let hull = self.hull();
+
+        if hull.point_distance(ray.origin) <= hull.radius || ray.direction == Vec2::ZERO
+        {
+            return None
+        }
+
+        match hull.radius == 0. && hull.edges() >= 3
+        {
+            true => raycast_polygon(&hull, ray, max_distance),
+            false => raycast_rounded(&hull, ray, max_distance)
+        }
+    }
+
+    /// moves this shape by `motion` and returns where it first touches `other`.
+    /// the time is zero if they already overlap
+    pub fn cast(&self, motion: Vec2, other: &Shape) -> Option<ShapeHit>
+    {
+        let (a, b) = (self.hull(), other.hull());
+        let radius = a.radius + b.radius;
+
+        let mut time = 0f32;
+        let mut last = None;
+
+        for _ in 0..MAX_CAST_ITERATIONS
+        {
+            let Some((distance, on_a, on_b)) = a.closest_points(motion * time, &b)
+            else
+            {
+                // the cores overlap, at the start or because of rounding errors right after the last step
+                return match last
+                {
+                    Some(hit) => Some(ShapeHit { time, ..hit }),
+                    None => contact(&a, &b).map(|contact| ShapeHit
+                    {
+                        time,
+                        point: contact.points()[0].position,
+                        normal: -contact.normal
+                    })
+                }
+            };
+
+            let normal = match distance > f32::EPSILON
+            {
+                true => (on_b - on_a) / distance,
+                false => motion.normalize_or_zero()
+            };
+
+            let hit = ShapeHit { time, point: on_b - normal * b.radius, normal: -normal };
+            let gap = distance - radius;
+
+            if gap <= CAST_TOLERANCE
+            {
+                return Some(hit)
+            }
+
+            // conservative advancement, the shapes can't get closer faster than this
+            let approach = motion.dot(normal);
+
+            if approach <= f32::EPSILON
+            {
+                return None
+            }
+
+            // stop a bit before touching, so that the cores never overlap
+            time += (gap - CAST_TOLERANCE * 0.5) / approach;
+            last = Some(hit);
+
+            if time > 1.
+            {
+                return None
+            }
+        }
+
+        None
+    }
+}
+
+fn contact(a: &Hull, b: &Hull) -> Option<Contact>
+{
+    let radius = a.radius + b.radius;
+
+    let separation_a = a.max_separation(Vec2::ZERO, b, Vec2::ZERO);
+    let separation_b = b.max_separation(Vec2::ZERO, a, Vec2::ZERO);
+
+    // the face that separates the shapes the most, preferring the first shape when they're close
+    let reference = match (separation_a, separation_b)
+    {
+        (Some(sa), Some(sb)) if sb.0 > sa.0 + PARALLEL_TOLERANCE * 0.1 => Some((sb, true)),
+        (Some(sa), _) => Some((sa, false)),
+        (None, Some(sb)) => Some((sb, true)),
+        (None, None) => None
+    };
+
+    let Some((distance, on_a, on_b)) = a.closest_points(Vec2::ZERO, b)
+    else
+    {
+        // the cores overlap, clip against the face of least penetration
+        let ((_, face), flip) = reference.expect("only hulls with edges can overlap");
+
+        return match flip
+        {
+            false => clip(a, face, b, false).or_else(|| deepest(a, face, b, false)),
+            true => clip(b, face, a, true).or_else(|| deepest(b, face, a, true))
+        }
+    };
+
+    if distance > radius
+    {
+        return None
+    }
+
+    // the closest features are parallel edges, they touch on a segment
+    if let Some(((separation, face), flip)) = reference
+    {
+        if a.edges() > 0 && b.edges() > 0 && (separation - distance).abs() <= PARALLEL_TOLERANCE
+        {
+            let contact = match flip
+            {
+                false => clip(a, face, b, false),
+                true => clip(b, face, a, true)
+            };
+
+            if contact.is_some()
+            {
+                return contact
+            }
+        }
+    }
+
+    let normal = match distance > f32::EPSILON
+    {
+        true => (on_b - on_a) / distance,
+        false => Vec2::Y
+    };
+
+    let mut contact = Contact::new(normal);
+
+    contact.push(ContactPoint
+    {
+        position: ((on_a + normal * a.radius) + (on_b - normal * b.radius)) * 0.5,
+        depth: radius - distance
+    });
+
+    Some(contact)
+}
+
+/// the contact points of the incident hull against an edge of the reference hull,
+/// `flip` is set when the reference is the second shape
+fn clip(reference: &Hull, face: usize, incident: &Hull, flip: bool) -> Option<Contact>
+{
+    let radius = reference.radius + incident.radius;
+
+    let (v1, v2) = reference.edge(face);
+    let normal = reference.normal(face);
+    let tangent = (v2 - v1).normalize_or_zero();
+
+    // the edge of the incident hull facing the reference edge the most
+    let segment = match incident.edges()
+    {
+        0 => [incident.points()[0]; 2],
+        edges =>
+        {
+            let index = (0..edges)
+                .min_by(|a, b| incident.normal(*a).dot(normal).total_cmp(&incident.normal(*b).dot(normal)))
+                .expect("the hull has edges");
+
+            let (a, b) = incident.edge(index);
+            [a, b]
+        }
+    };
+
+    let segment = clip_segment(segment, -tangent, -tangent.dot(v1))?;
+    let segment = clip_segment(segment, tangent, tangent.dot(v2))?;
+
+    let points = match incident.edges()
+    {
+        0 => &segment[..1],
+        _ => &segment[..]
+    };
+
+    let mut contact = Contact::new(match flip
+    {
+        false => normal,
+        true => -normal
+    });
+
+    for point in points
+    {
+        let separation = normal.dot(*point - v1);
+
+        if separation <= radius
+        {
+            let on_reference = *point - normal * (separation - reference.radius);
+            let on_incident = *point - normal * incident.radius;
+
+            contact.push(ContactPoint { position: (on_reference + on_incident) * 0.5, depth: radius - separation })
+        }
+    }
+
+    match contact.len > 0
+    {
+        true => Some(contact),
+        false => None
+    }
+}
+
+/// a single contact on the point of the incident hull that goes the deepest past the reference edge,
+/// used when the incident edge is outside the sides of the reference one
+fn deepest(reference: &Hull, face: usize, incident: &Hull, flip: bool) -> Option<Contact>
+{
+    let (v1, _) = reference.edge(face);
+    let normal = reference.normal(face);
+
+    let (separation, point) = incident.points()
+        .iter()
+        .map(|point| (normal.dot(*point - v1), *point))
+        .min_by(|(a, _), (b, _)| a.total_cmp(b))?;
+
+    let mut contact = Contact::new(match flip
+    {
+        false => normal,
+        true => -normal
+    });
+
+    let on_reference = point - normal * (separation - reference.radius);
+    let on_incident = point - normal * incident.radius;
+
+    contact.push(ContactPoint
+    {
+        position: (on_reference + on_incident) * 0.5,
+        depth: reference.radius + incident.radius - separation
+    });
+
+    Some(contact)
+}
+
+/// keeps the part of the segment where `normal.dot(point) <= offset`
+fn clip_segment(segment: [Vec2; 2], normal: Vec2, offset: f32) -> Option<[Vec2; 2]>
+{
+    let [a, b] = segment;
+    let (da, db) = (normal.dot(a) - offset, normal.dot(b) - offset);
+
+    match (da <= 0., db <= 0.)
+    {
+        (true, true) => Some(segment),
+        (false, false) => None,
+        (inside_a, _) =>
+        {
+            let crossing = a + (b - a) * (da / (da - db));
+
+            match inside_a
+            {
+                true => Some([a, crossing]),
+                false => Some([crossing, b])
+            }
+        }
+    }
+}
+
+/// clips the ray against the half planes of the edges
+fn raycast_polygon(hull: &Hull, ray: Ray, max_distance: f32) -> Option<RayHit>
+{
+    let (mut lower, mut upper) = (0f32, max_distance);
+    let mut hit_edge = None;
+
+    for index in 0..hull.edges()
+    {
+        let normal = hull.normal(index);
+
+        let numerator = normal.dot(hull.points()[index] - ray.origin);
+        let denominator = normal.dot(ray.direction);
+
+        if denominator == 0.
+        {
+            // parallel to the edge and outside of it
+            if numerator < 0.
+            {
+                return None
+            }
+        }
+        else
+        {
+            let distance = numerator / denominator;
+
+            if denominator < 0. && distance > lower
+            {
+                lower = distance;
+                hit_edge = Some(index)
+            }
+            else if denominator > 0. && distance < upper
+            {
+                upper = distance
+            }
+        }
+
+        if upper < lower
+        {
+            return None
+        }
+    }
+
+    hit_edge.map(|index| RayHit { distance: lower, point: ray.at(lower), normal: hull.normal(index) })
+}
+
+/// tests the ray against a circle on every point and the edges pushed out by the radius
+fn raycast_rounded(hull: &Hull, ray: Ray, max_distance: f32) -> Option<RayHit>
+{
+    let circles = hull.points().iter().filter_map(|center| raycast_circle(*center, hull.radius, ray));
+
+    let edges = (0..hull.edges()).filter_map(|index|
+    {
+        let normal = hull.normal(index);
+
+        if normal.dot(ray.direction) >= 0.
+        {
+            return None
+        }
+
+        let (a, b) = hull.edge(index);
+        let (a, b) = (a + normal * hull.radius, b + normal * hull.radius);
+
+        let edge = b - a;
+        let denominator = ray.direction.perp_dot(edge);
+
+        if denominator.abs() <= f32::EPSILON
+        {
+            return None
+        }
+
+        let distance = (a - ray.origin).perp_dot(edge) / denominator;
+        let along = (a - ray.origin).perp_dot(ray.direction) / denominator;
+
+        match distance >= 0. && (0. ..=1.).contains(&along)
+        {
+            true => Some(RayHit { distance, point: ray.at(distance), normal }),
+            false => None
+        }
+    });
+
+    circles
+        .chain(edges)
+        .filter(|hit| hit.distance <= max_distance)
+        .min_by(|a, b| a.distance.total_cmp(&b.distance))
+}
+
+fn raycast_circle(center: Vec2, radius: f32, ray: Ray) -> Option<RayHit>
+{
+    if radius <= 0.
+    {
+        return None
+    }
+
+    let offset = ray.origin - center;
+
+    let b = offset.dot(ray.direction);
+    let c = offset.length_squared() - radius * radius;
+
+    let discriminant = b * b - c;
+
+    if (c > 0. && b > 0.) || discriminant < 0.
+    {
+        return None
+    }
+
+    let distance = (-b - discriminant.sqrt()).max(0.);
+    let point = ray.at(distance);
+
+    Some(RayHit { distance, point, normal: (point - center) / radius })
+}
+
+#[cfg(test)]
+mod tests
+{
+    use glam::{vec2, Vec2};
+
+    use crate::{Aabb, Capsule, Circle, ConvexPolygon, Obb};
+    use super::*;
+
+    /// every kind of shape, centered at the origin and reaching half a unit above and below it
+    fn shapes() -> [(&'static str, Shape); 5]
+    {
+        [
+            ("aabb", Aabb::from_center(Vec2::ZERO, vec2(0.5, 0.5)).into()),
+            ("circle", Circle::new(Vec2::ZERO, 0.5).into()),
+            ("capsule", Capsule::vertical(Vec2::ZERO, 1., 0.25).into()),
+            ("obb", Obb::new(Vec2::ZERO, vec2(0.5, 0.3), std::f32::consts::FRAC_PI_2).into()),
+            ("polygon", ConvexPolygon::new([vec2(-0.5, -0.5), vec2(0.5, -0.5), vec2(0.3, 0.5), vec2(-0.3, 0.5)]).unwrap().into())
+        ]
+    }
+
+    /// every pair of shapes, the second one `gap` above the first one
+    fn pairs(gap: f32) -> impl Iterator<Item = (String, Shape, Shape)>
+    {
+        shapes().into_iter().flat_map(move |(name_a, a)| shapes().into_iter().map(move |(name_b, b)|
+        {
+            (format!("{name_a} under {name_b}"), a.clone(), b.translated(vec2(0., 1. + gap)))
+        }))
+    }
+
+    fn close(a: f32, b: f32) -> bool
+    {
+        (a - b).abs() < 1e-3
+    }
+
+    #[test]
+    fn overlapping_pairs_are_pushed_apart_vertically()
+    {
+        for (name, a, b) in pairs(-0.1)
+        {
+            let contact = a.contact(&b).unwrap_or_else(|| panic!("{name}: no contact"));
+
+            assert!(contact.normal.abs_diff_eq(Vec2::Y, 1e-3), "{name}: normal {}", contact.normal);
+            assert!(close(contact.depth(), 0.1), "{name}: depth {}", contact.depth());
+            assert!(a.intersects(&b), "{name}");
+            assert_eq!(a.distance(&b), 0., "{name}");
+
+            let flipped = b.contact(&a).unwrap_or_else(|| panic!("{name}: no flipped contact"));
+            assert!(flipped.normal.abs_diff_eq(-Vec2::Y, 1e-3), "{name}: flipped normal {}", flipped.normal);
+        }
+    }
+
+    #[test]
+    fn separated_pairs_have_no_contact()
+    {
+        for (name, a, b) in pairs(0.1)
+        {
+            assert_eq!(a.contact(&b), None, "{name}");
+            assert!(!a.intersects(&b), "{name}");
+            assert!(close(a.distance(&b), 0.1), "{name}: distance {}", a.distance(&b));
+        }
+
+        for (name, a, b) in pairs(4.)
+        {
+            assert!(close(a.distance(&b), 4.), "{name}: distance {}", a.distance(&b));
+        }
+    }
+
+    #[test]
+    fn casts_stop_on_the_surface()
+    {
+        for (name, a, b) in pairs(0.3)
+        {
+            let hit = a.cast(vec2(0., 1.), &b).unwrap_or_else(|| panic!("{name}: no hit"));
+
+            assert!(close(hit.time, 0.3), "{name}: time {}", hit.time);
+            assert!(hit.normal.abs_diff_eq(-Vec2::Y, 1e-3), "{name}: normal {}", hit.normal);
+
+            assert_eq!(a.cast(vec2(0., 0.2), &b), None, "{name}: too short");
+            assert_eq!(a.cast(vec2(1., 0.), &b), None, "{name}: sideways");
+        }
+
+        for (name, a, b) in pairs(-0.1)
+        {
+            assert_eq!(a.cast(vec2(0., 1.), &b).map(|hit| hit.time), Some(0.), "{name}: overlapping");
+        }
+    }
+
+    #[test]
+    fn circle_over_the_end_of_a_capsule()
+    {
+        let capsule = Shape::from(Capsule::new(Vec2::ZERO, Vec2::Y, 0.4));
+        let circle = |y: f32| Shape::from(Circle::new(vec2(0., y), 0.5));
+
+        let contact = capsule.contact(&circle(1.7)).expect("they overlap");
+
+        assert!(contact.normal.abs_diff_eq(Vec2::Y, 1e-5));
+        assert!(close(contact.depth(), 0.2));
+
+        assert_eq!(capsule.contact(&circle(2.)), None);
+        assert!(close(capsule.distance(&circle(5.)), 3.1));
+
+        let hit = circle(5.).cast(vec2(0., -4.), &capsule).expect("it falls on the capsule");
+        assert!(close(hit.time, 3.1 / 4.));
+        assert!(hit.normal.abs_diff_eq(Vec2::Y, 1e-3));
+    }
+
+    #[test]
+    fn crossing_capsules_overlap()
+    {
+        let vertical = Shape::from(Capsule::new(vec2(0., -1.), vec2(0., 1.), 0.1));
+        let horizontal = Shape::from(Capsule::new(vec2(-1., 0.), vec2(1., 0.), 0.1));
+
+        assert!(vertical.intersects(&horizontal));
+        assert_eq!(vertical.distance(&horizontal), 0.);
+
+        // side by side, along the same line
+        let above = Shape::from(Capsule::new(vec2(0., 1.5), vec2(0., 3.), 0.1));
+
+        assert!(!vertical.intersects(&above));
+        assert!(close(vertical.distance(&above), 0.3));
+    }
+}
@@ -0,0 +1,443 @@
+use glam::{vec2, Vec2};
+
+use crate::Transform2D;
+use super::hull::Hull;
+use super::Ray;
+
+/// an axis aligned box
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Aabb
+{
+    pub min: Vec2,
+    pub max: Vec2
+}
+
+impl Aabb
+{
+    /// the corners can be passed in any order
+    pub fn new(a: Vec2, b: Vec2) -> Self
+    {
+        Self { min: a.min(b), max: a.max(b) }
+    }
+
+    pub fn from_center(center: Vec2, half_extents: Vec2) -> Self
+    {
+        Self::new(center - half_extents, center + half_extents)
+    }
+
+    /// the smallest box that contains all the points, [None] if there are none
+    pub fn from_points(points: impl IntoIterator<Item = Vec2>) -> Option<Self>
+    {
+        let mut points = points.into_iter();
+        let first = points.next()?;
+
+        Some(points.fold(Self { min: first, max: first }, |aabb, point| Self
+        {
+            min: aabb.min.min(point),
+            max: aabb.max.max(point)
+        }))
+    }
+
+    pub fn center(&self) -> Vec2
+    {
+        (self.min + self.max) * 0.5
+    }
+
+    pub fn half_extents(&self) -> Vec2
+    {
+        (self.max - self.min) * 0.5
+    }
+
+    pub fn size(&self) -> Vec2
+    {
+        self.max - self.min
+    }
+
+    pub fn area(&self) -> f32
+    {
+        let size = self.size();
+        size.x * size.y
+    }
+
+    pub fn perimeter(&self) -> f32
+    {
+        let size = self.size();
+        2. * (size.x + size.y)
+    }
+
+    pub fn contains(&self, point: Vec2) -> bool
+    {
+        point.cmpge(self.min).all() && point.cmple(self.max).all()
+    }
+
+    /// returns true if `other` is completely inside this box
+    pub fn contains_aabb(&self, other: &Aabb) -> bool
+    {
+        other.min.cmpge(self.min).all() && other.max.cmple(self.max).all()
+    }
+
+    /// returns true if the boxes overlap or touch
+    pub fn intersects(&self, other: &Aabb) -> bool
+    {
+        self.min.cmple(other.max).all() && other.min.cmple(self.max).all()
+    }
+
+    /// the smallest box that contains both
+    pub fn merge(&self, other: &Aabb) -> Self
+    {
+        Self { min: self.min.min(other.min), max: self.max.max(other.max) }
+    }
+
+    /// grows the box by `margin` on every side
+    pub fn expand(&self, margin: f32) -> Self
+    {
+        Self { min: self.min - Vec2::splat(margin), max: self.max + Vec2::splat(margin) }
+    }
+
+    pub fn translate(&self, offset: Vec2) -> Self
+    {
+        Self { min: self.min + offset, max: self.max + offset }
+    }
+
+    /// the point of the box closest to `point`, the point itself if it's inside
+    pub fn closest_point(&self, point: Vec2) -> Vec2
+    {
+        point.clamp(self.min, self.max)
+    }
+
+    /// the distance from the point to the box, zero if it's inside
+    pub fn distance_to_point(&self, point: Vec2) -> f32
+    {
+        self.closest_point(point).distance(point)
+    }
+
+    /// the distance along the ray where it enters the box, zero if it starts inside
+    pub fn raycast(&self, ray: Ray, max_distance: f32) -> Option<f32>
+    {
+        let (mut enter, mut exit) = (0f32, max_distance);
+
+        for axis in 0..2
+        {
+            let (origin, direction) = (ray.origin[axis], ray.direction[axis]);
+            let (min, max) = (self.min[axis], self.max[axis]);
+
+            if direction == 0.
+            {
+                if origin < min || origin > max
+                {
+                    return None
+                }
+
+                continue
+            }
+
+            let (a, b) = ((min - origin) / direction, (max - origin) / direction);
+
+            enter = enter.max(a.min(b));
+            exit = exit.min(a.max(b));
+
+            if enter > exit
+            {
+                return None
+            }
+        }
+
+        Some(enter)
+    }
+
+    /// the corners, counterclockwise from the bottom left
+    pub fn corners(&self) -> [Vec2; 4]
+    {
+        [self.min, vec2(self.max.x, self.min.y), self.max, vec2(self.min.x, self.max.y)]
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Circle
+{
+    pub center: Vec2,
+    pub radius: f32
+}
+
+impl Circle
+{
+    pub fn new(center: Vec2, radius: f32) -> Self
+    {
+        Self { center, radius: radius.max(0.) }
+    }
+}
+
+/// a segment with a radius, like a pill
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Capsule
+{
+    pub a: Vec2,
+    pub b: Vec2,
+    pub radius: f32
+}
+
+impl Capsule
+{
+    pub fn new(a: Vec2, b: Vec2, radius: f32) -> Self
+    {
+        Self { a, b, radius: radius.max(0.) }
+    }
+
+    /// a vertical capsule, `height` includes the rounded ends
+    pub fn vertical(center: Vec2, height: f32, radius: f32) -> Self
+    {
+        let half = (height * 0.5 - radius).max(0.);
+        Self::new(center - Vec2::Y * half, center + Vec2::Y * half, radius)
+    }
+}
+
+/// a rotated box
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Obb
+{
+    pub center: Vec2,
+    pub half_extents: Vec2,
+    /// counterclockwise, in radians
+    pub rotation: f32
+}
+
+impl Obb
+{
+    pub fn new(center: Vec2, half_extents: Vec2, rotation: f32) -> Self
+    {
+        Self { center, half_extents: half_extents.abs(), rotation }
+    }
+
+    /// the corners, counterclockwise
+    pub fn corners(&self) -> [Vec2; 4]
+    {
+        let rotation = Vec2::from_angle(self.rotation);
+        let Vec2 { x, y } = self.half_extents;
+
+        [vec2(-x, -y), vec2(x, -y), vec2(x, y), vec2(-x, y)].map(|corner| self.center + rotation.rotate(corner))
+    }
+}
+
+/// a convex polygon, its points are stored counterclockwise
+#[derive(Debug, Clone, PartialEq)]
+pub struct ConvexPolygon
+{
+    points: Vec<Vec2>
+}
+
+impl ConvexPolygon
+{
+    /// the convex hull of the points, [None] if they're fewer than three or all on the same line
+    pub fn new(points: impl IntoIterator<Item = Vec2>) -> Option<Self>
+    {
+        let mut points = points.into_iter().collect::<Vec<_>>();
+        points.sort_by(|a, b| a.x.total_cmp(&b.x).then(a.y.total_cmp(&b.y)));
+        points.dedup();
+
+        if points.len() < 3
+        {
+            return None
+        }
+
+        // andrew's monotone chain, the lower hull and then the upper one
+        let mut hull: Vec<Vec2> = Vec::with_capacity(points.len() + 1);
+
+        for pass in 0..2
+        {
+            let start = hull.len();
+
+            let ordered: Box<dyn Iterator<Item = &Vec2>> = match pass
+            {
+                0 => Box::new(points.iter()),
+                _ => Box::new(points.iter().rev())
+            };
+
+            for point in ordered
+            {
+                while hull.len() >= start + 2
+                    && (hull[hull.len() - 1] - hull[hull.len() - 2]).perp_dot(*point - hull[hull.len() - 2]) <= f32::EPSILON
+                {
+                    hull.pop();
+                }
+
+                hull.push(*point)
+            }
+
+            // the last point is the first of the other half
+            hull.pop();
+        }
+
+        match hull.len() >= 3
+        {
+            true => Some(Self { points: hull }),
+            false => None
+        }
+    }
+
+    /// a regular polygon with `sides` sides, [None] if they're fewer than three
+    pub fn regular(center: Vec2, radius: f32, sides: usize) -> Option<Self>
+    {
+        Self::new((0..sides).map(|side|
+        {
+            let angle = std::f32::consts::TAU * side as f32 / sides as f32;
+            center + Vec2::from_angle(angle) * radius
+        }))
+    }
+
+    pub fn points(&self) -> &[Vec2]
+    {
+        &self.points
+    }
+}
+
+/// any of the collision shapes, the queries are implemented on this
+#[derive(Debug, Clone, PartialEq)]
+pub enum Shape
+{
+    Aabb(Aabb),
+    Circle(Circle),
+    Capsule(Capsule),
+    Obb(Obb),
+    Polygon(ConvexPolygon)
+}
+
+impl From<Aabb> for Shape
+{
+    fn from(aabb: Aabb) -> Self { Self::Aabb(aabb) }
+}
+
+impl From<Circle> for Shape
+{
+    fn from(circle: Circle) -> Self { Self::Circle(circle) }
+}
+
+impl From<Capsule> for Shape
+{
+    fn from(capsule: Capsule) -> Self { Self::Capsule(capsule) }
+}
+
+impl From<Obb> for Shape
+{
+    fn from(obb: Obb) -> Self { Self::Obb(obb) }
+}
+
+impl From<ConvexPolygon> for Shape
+{
+    fn from(polygon: ConvexPolygon) -> Self { Self::Polygon(polygon) }
+}
+
+impl Shape
+{
+    /// the shape as a convex core and a radius
+    pub(crate) fn hull(&self) -> Hull<'_>
+    {
+        match self
+        {
+            Shape::Aabb(aabb) => Hull::inline(aabb.corners(), 4, 0.),
+            Shape::Circle(circle) => Hull::inline([circle.center; 4], 1, circle.radius),
+            Shape::Capsule(capsule) => Hull::inline([capsule.a, capsule.b, Vec2::ZERO, Vec2::ZERO], 2, capsule.radius),
+            Shape::Obb(obb) => Hull::inline(obb.corners(), 4, 0.),
+            Shape::Polygon(polygon) => Hull::slice(&polygon.points, 0.)
+        }
+    }
+
+    /// the smallest axis aligned box that contains the shape
+    pub fn aabb(&self) -> Aabb
+    {
+        let hull = self.hull();
+
+        let aabb = Aabb::from_points(hull.points().iter().copied()).expect("a hull always has a point");
+        aabb.expand(hull.radius)
+    }
+
+    /// the center of the shape's area
+    pub fn center(&self) -> Vec2
+    {
+        match self
+        {
+            Shape::Aabb(aabb) => aabb.center(),
+            Shape::Circle(circle) => circle.center,
+            Shape::Capsule(capsule) => (capsule.a + capsule.b) * 0.5,
+            Shape::Obb(obb) => obb.center,
+            Shape::Polygon(polygon) =>
+            {
+                let points = &polygon.points;
+                let origin = points[0];
+
+                let (area, centroid) = (1..points.len() - 1).fold((0., Vec2::ZERO), |(area, centroid), i|
+                {
+                    let (b, c) = (points[i] - origin, points[i + 1] - origin);
+                    let triangle = b.perp_dot(c) * 0.5;
+
+                    (area + triangle, centroid + (b + c) / 3. * triangle)
+                });
+
+                match area > f32::EPSILON
+                {
+                    true => origin + centroid / area,
+                    false => origin
+                }
+            }
+        }
+    }
+
+    pub fn contains_point(&self, point: Vec2) -> bool
+    {
+        let hull = self.hull();
+        hull.point_distance(point) <= hull.radius
+    }
+
+    /// the shape moved, rotated and scaled by the transform, the radius of
+    /// the circles and capsules is scaled by the largest component of the scale.
+    ///
+    /// a rotated [Aabb] becomes an [Obb]
+    pub fn transformed(&self, transform: &Transform2D) -> Self
+    {
+        let radius_scale = transform.scale.abs().max_element();
+
+        match self
+        {
+            Shape::Aabb(aabb) if transform.rotation == 0. => Shape::Aabb(Aabb::new
+            (
+                transform.transform_point(aabb.min), transform.transform_point(aabb.max)
+            )),
+            Shape::Aabb(aabb) => Shape::Obb(Obb::new
+            (
+                transform.transform_point(aabb.center()),
+                aabb.half_extents() * transform.scale.abs(),
+                transform.rotation
+            )),
+            Shape::Circle(circle) => Shape::Circle(Circle::new
+            (
+                transform.transform_point(circle.center), circle.radius * radius_scale
+            )),
+            Shape::Capsule(capsule) => Shape::Capsule(Capsule::new
+            (
+                transform.transform_point(capsule.a),
+                transform.transform_point(capsule.b),
+                capsule.radius * radius_scale
+            )),
+            Shape::Obb(obb) => Shape::Obb(Obb::new
+            (
+                transform.transform_point(obb.center),
+                obb.half_extents * transform.scale.abs(),
+                obb.rotation + transform.rotation
+            )),
+            Shape::Polygon(polygon) => Shape::Polygon(ConvexPolygon
+            {
+                // a mirrored polygon would be clockwise
+                points: match transform.scale.x * transform.scale.y < 0.
+                {
+                    true => polygon.points.iter().rev().map(|point| transform.transform_point(*point)).collect(),
+                    false => polygon.points.iter().map(|point| transform.transform_point(*point)).collect()
+                }
+            })
+        }
+    }
+
+    /// the shape moved by `offset`
+    pub fn translated(&self, offset: Vec2) -> Self
+    {
+        self.transformed(&Transform2D::from_translation(offset))
+    }
+}
@@ -0,0 +1,528 @@
+use std::str::FromStr;
+
+use glam::Vec4;
+
+/// a color in the srgb space, the one of color pickers, hex codes and image files,
+/// with a straight alpha.
+///
+/// the gpu blends in linear space, use [Color::to_linear] to hand it a color
+///
+/// # example
+///
+/// ```
+/// use baguette_math::Color;
+///
+/// let sky = Color::hex("#87ceeb").unwrap();
+/// let sunset = Color::hsv(20., 0.8, 1.);
+///
+/// let background = sky.lerp(sunset, 0.3);
+/// assert!(background.r > sky.r && background.r < sunset.r);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[derive(serde::Serialize, serde::Deserialize)]
+pub struct Color
+{
+    pub r: f32,
+    pub g: f32,
+    pub b: f32,
+    pub a: f32
+}
+
+impl Default for Color
+{
+    fn default() -> Self
+    {
+        Self::WHITE
+    }
+}
+
+/// a hex code that couldn't be parsed
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ColorError(pub String);
+
+impl std::fmt::Display for ColorError
+{
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result
+    {
+        write!(f, "{:?} isn't a color, expected #rgb, #rgba, #rrggbb or #rrggbbaa", self.0)
+    }
+}
+
+impl std::error::Error for ColorError {}
+
+impl Color
+{
+    pub const WHITE: Self = Self::rgb(1., 1., 1.);
+    pub const BLACK: Self = Self::rgb(0., 0., 0.);
+    pub const TRANSPARENT: Self = Self::rgba(0., 0., 0., 0.);
+    pub const GRAY: Self = Self::rgb(0.5, 0.5, 0.5);
+    pub const RED: Self = Self::rgb(1., 0., 0.);
+    pub const GREEN: Self = Self::rgb(0., 1., 0.);
+    pub const BLUE: Self = Self::rgb(0., 0., 1.);
+    pub const YELLOW: Self = Self::rgb(1., 1., 0.);
+    pub const CYAN: Self = Self::rgb(0., 1., 1.);
+    pub const MAGENTA: Self = Self::rgb(1., 0., 1.);
+
+    /// an opaque color, the components go from `0.` to `1.`
+    pub const fn rgb(r: f32, g: f32, b: f32) -> Self
+    {
+        Self { r, g, b, a: 1. }
+    }
+
+    pub const fn rgba(r: f32, g: f32, b: f32, a: f32) -> Self
+    {
+        Self { r, g, b, a }
+    }
+
+    /// an opaque color, the components go from `0` to `255`
+    pub fn rgb8(r: u8, g: u8, b: u8) -> Self
+    {
+        Self::rgba8(r, g, b, 255)
+    }
+
+    pub fn rgba8(r: u8, g: u8, b: u8, a: u8) -> Self
+    {
+        Self::rgba(r as f32 / 255., g as f32 / 255., b as f32 / 255., a as f32 / 255.)
+    }
+
+    /// a color from the linear components the gpu works with
+    pub fn from_linear(r: f32, g: f32, b: f32, a: f32) -> Self
+    {
+        Self::rgba(linear_to_srgb(r), linear_to_srgb(g), linear_to_srgb(b), a)
+    }
+
+    /// an opaque color from its hue in degrees, its saturation and its value from `0.` to `1.`
+    pub fn hsv(hue: f32, saturation: f32, value: f32) -> Self
+    {
+        let chroma = value * saturation;
+        Self::from_hue(hue, chroma, value - chroma)
+    }
+
+    /// an opaque color from its hue in degrees, its saturation and its lightness from `0.` to `1.`
+    pub fn hsl(hue: f32, saturation: f32, lightness: f32) -> Self
+    {
+        let chroma = (1. - (2. * lightness - 1.).abs()) * saturation;
+        Self::from_hue(hue, chroma, lightness - chroma / 2.)
+    }
+
+    fn from_hue(hue: f32, chroma: f32, min: f32) -> Self
+    {
+        let sector = hue.rem_euclid(360.) / 60.;
+        let x = chroma * (1. - (sector % 2. - 1.).abs());
+
+        let (r, g, b) = match sector as u32
+        {
+            0 => (chroma, x, 0.),
+            1 => (x, chroma, 0.),
+            2 => (0., chroma, x),
+            3 => (0., x, chroma),
+            4 => (x, 0., chroma),
+            _ => (chroma, 0., x)
+        };
+
+        Self::rgb(r + min, g + min, b + min)
+    }
+
+    /// parses `#rgb`, `#rgba`, `#rrggbb` or `#rrggbbaa`, the `#` is optional
+    pub fn hex(hex: &str) -> Result<Self, ColorError>
+    {
+        let error = || ColorError(hex.to_string());
+
+        let digits = hex.strip_prefix('#').unwrap_or(hex);
+
+        // `from_str_radix` would also take a `+`
+        if !digits.bytes().all(|digit| digit.is_ascii_hexdigit())
+        {
+            return Err(error())
+        }
+
+        let channel = |i: usize, width: usize|
+        {
+            let value = u8::from_str_radix(&digits[i * width..(i + 1) * width], 16).map_err(|_| error())?;
+
+            // `f` is short for `ff`
+            Ok(match width
+            {
+                1 => value * 17,
+                _ => value
+            })
+        };
+
+        let (count, width) = match digits.len()
+        {
+            3 => (3, 1),
+            4 => (4, 1),
+            6 => (3, 2),
+            8 => (4, 2),
+            _ => return Err(error())
+        };
+
+        let alpha = match count
+        {
+            4 => channel(3, width)?,
+            _ => 255
+        };
+
+        Ok(Self::rgba8(channel(0, width)?, channel(1, width)?, channel(2, width)?, alpha))
+    }
+
+    /// the hex code of the color, `#rrggbb` if it's opaque and `#rrggbbaa` if it isn't
+    pub fn to_hex(&self) -> String
+    {
+        let [r, g, b, a] = self.to_rgba8();
+
+        match a
+        {
+            255 => format!("#{r:02x}{g:02x}{b:02x}"),
+            _ => format!("#{r:02x}{g:02x}{b:02x}{a:02x}")
+        }
+    }
+
+    pub fn to_rgba8(&self) -> [u8; 4]
+    {
+        let to_u8 = |value: f32| (value.clamp(0., 1.) * 255.).round() as u8;
+
+        [to_u8(self.r), to_u8(self.g), to_u8(self.b), to_u8(self.a)]
+    }
+
+    /// the linear components, what shaders and clear colors expect when drawing on an srgb surface
+    pub fn to_linear(&self) -> [f32; 4]
+    {
+        [srgb_to_linear(self.r), srgb_to_linear(self.g), srgb_to_linear(self.b), self.a]
+    }
+
+    /// the hue in degrees, the saturation and the value
+    pub fn to_hsv(&self) -> (f32, f32, f32)
+    {
+        let (hue, max, min) = self.hue();
+
+        let saturation = match max > 0.
+        {
+            true => (max - min) / max,
+            false => 0.
+        };
+
+        (hue, saturation, max)
+    }
+
+    /// the hue in degrees, the saturation and the lightness
+    pub fn to_hsl(&self) -> (f32, f32, f32)
+    {
+        let (hue, max, min) = self.hue();
+        let lightness = (max + min) / 2.;
+
+        let saturation = match lightness > 0. && lightness < 1.
+        {
+            true => (max - min) / (1. - (2. * lightness - 1.).abs()),
+            false => 0.
+        };
+
+        (hue, saturation, lightness)
+    }
+
+    /// the hue in degrees and the largest and smallest components
+    fn hue(&self) -> (f32, f32, f32)
+    {
+        let max = self.r.max(self.g).max(self.b);
+        let min = self.r.min(self.g).min(self.b);
+        let chroma = max - min;
+
+        let sector = if chroma == 0.
+        {
+            0.
+        }
+        else if max == self.r
+        {
+            ((self.g - self.b) / chroma).rem_euclid(6.)
+        }
+        else if max == self.g
+        {
+            (self.b - self.r) / chroma + 2.
+        }
+        else
+        {
+            (self.r - self.g) / chroma + 4.
+        };
+
+        (sector * 60., max, min)
+    }
+
+    pub fn with_alpha(mut self, alpha: f32) -> Self
+    {
+        self.a = alpha;
+        self
+    }
+
+    /// the color between `self` and `to`, blended in linear space
+    /// so that it doesn't get darker halfway
+    pub fn lerp(self, to: Self, t: f32) -> Self
+    {
+        let [r, g, b, a] = Vec4::from(self.to_linear()).lerp(Vec4::from(to.to_linear()), t).to_array();
+        Self::from_linear(r, g, b, a)
+    }
+
+    /// the color between `self` and `to`, blended on the srgb components like most image editors do
+    pub fn lerp_srgb(self, to: Self, t: f32) -> Self
+    {
+        Vec4::from(self).lerp(Vec4::from(to), t).into()
+    }
+}
+
+impl FromStr for Color
+{
+    type Err = ColorError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err>
+    {
+        Self::hex(s)
+    }
+}
+
+impl From<[f32; 4]> for Color
+{
+    fn from([r, g, b, a]: [f32; 4]) -> Self
+    {
+        Self::rgba(r, g, b, a)
+    }
+}
+
+impl From<[f32; 3]> for Color
+{
+    fn from([r, g, b]: [f32; 3]) -> Self
+    {
+        Self::rgb(r, g, b)
+    }
+}
+
+impl From<Color> for [f32; 4]
+{
+    fn from(color: Color) -> Self
+    {
+        [color.r, color.g, color.b, color.a]
+    }
+}
+
+impl From<Vec4> for Color
+{
+    fn from(vec: Vec4) -> Self
+    {
+        vec.to_array().into()
+    }
+}
+
+impl From<Color> for Vec4
+{
+    fn from(color: Color) -> Self
+    {
+        Vec4::new(color.r, color.g, color.b, color.a)
+    }
+}
+
+/// colors placed along a line, sampled anywhere between them
+///
+/// # example
+///
+/// ```
+/// use baguette_math::{Color, Gradient};
+///
+/// let fire = Gradient::new(Color::YELLOW).add(0.5, Color::RED).add(1., Color::BLACK.with_alpha(0.));
+///
+/// let (age, lifetime) = (2., 2.);
+/// assert_eq!(fire.sample(age / lifetime), Color::BLACK.with_alpha(0.));
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+#[derive(serde::Serialize, serde::Deserialize)]
+pub struct Gradient
+{
+    /// sorted by position
+    stops: Vec<(f32, Color)>
+}
+
+impl Gradient
+{
+    /// a gradient with a single color at `0.`
+    pub fn new(color: Color) -> Self
+    {
+        Self { stops: vec![(0., color)] }
+    }
+
+    /// adds a color at this position, after the ones at the same position
+    pub fn add(mut self, position: f32, color: Color) -> Self
+    {
+        let index = self.stops.partition_point(|(stop, _)| *stop <= position);
+        self.stops.insert(index, (position, color));
+        self
+    }
+
+    pub fn stops(&self) -> &[(f32, Color)]
+    {
+        &self.stops
+    }
+
+    /// the color at this position, the first and last colors carry on past the ends
+    pub fn sample(&self, position: f32) -> Color
+    {
+        let after = self.stops.partition_point(|(stop, _)| *stop <= position);
+
+        match (after.checked_sub(1).map(|before| self.stops[before]), self.stops.get(after).copied())
+        {
+            (Some((from, start)), Some((to, end))) => start.lerp(end, (position - from) / (to - from)),
+            (Some((_, color)), None) | (None, Some((_, color))) => color,
+            (None, None) => Color::default()
+        }
+    }
+}
+
+fn srgb_to_linear(value: f32) -> f32
+{
+    match value <= 0.04045
+    {
+        true => value / 12.92,
+        false => ((value + 0.055) / 1.055).powf(2.4)
+    }
+}
+
+fn linear_to_srgb(value: f32) -> f32
+{
+    match value <= 0.003_130_8
+    {
+        true => value * 12.92,
+        false => 1.055 * value.powf(1. / 2.4) - 0.055
+    }
+}
+
+#[cfg(test)]
+mod tests
+{
+    use super::*;
+
+    fn assert_close(a: Color, b: Color)
+    {
+        assert!(Vec4::from(a).abs_diff_eq(Vec4::from(b), 1e-5), "{a:?} != {b:?}")
+    }
+
+    #[test]
+    fn parses_every_hex_length()
+    {
+        assert_eq!(Color::hex("#ff8000"), Ok(Color::rgb8(255, 128, 0)));
+        assert_eq!(Color::hex("ff8000"), Ok(Color::rgb8(255, 128, 0)));
+        assert_eq!(Color::hex("#f80"), Ok(Color::rgb8(255, 136, 0)));
+        assert_eq!(Color::hex("#f808"), Ok(Color::rgba8(255, 136, 0, 136)));
+        assert_eq!(Color::hex("#FF800080"), Ok(Color::rgba8(255, 128, 0, 128)));
+        assert_eq!("#000".parse(), Ok(Color::BLACK));
+    }
+
+    #[test]
+    fn rejects_what_isnt_a_hex_code()
+    {
+        for hex in ["", "#", "#12", "#12345", "#1234567", "#ggg", "#+f00ff0", "#-10", "#ffé", "##fff", " #fff"]
+        {
+            assert_eq!(Color::hex(hex), Err(ColorError(hex.to_string())), "{hex:?}");
+        }
+
+        assert!(ColorError("nope".into()).to_string().contains("\"nope\""));
+    }
+
+    #[test]
+    fn hex_codes_round_trip()
+    {
+        for hex in ["#87ceeb", "#00000000", "#ff800080", "#123456"]
+        {
+            assert_eq!(Color::hex(hex).unwrap().to_hex(), hex);
+        }
+
+        assert_eq!(Color::rgba(2., -1., 0.5, 1.).to_hex(), "#ff0080");
+    }
+
+    #[test]
+    fn linear_round_trips()
+    {
+        for i in 0..=255
+        {
+            let color = Color::rgba8(i, 255 - i, i / 2, 100);
+            let [r, g, b, a] = color.to_linear();
+
+            assert_close(Color::from_linear(r, g, b, a), color);
+            assert_eq!(Color::from_linear(r, g, b, a).to_rgba8(), color.to_rgba8());
+        }
+
+        // the ends and the alpha don't move, the middle gets darker
+        assert_eq!(Color::WHITE.to_linear(), [1., 1., 1., 1.]);
+        assert_eq!(Color::BLACK.to_linear(), [0., 0., 0., 1.]);
+        assert!((Color::GRAY.to_linear()[0] - 0.214).abs() < 1e-3);
+        assert_eq!(Color::GRAY.with_alpha(0.5).to_linear()[3], 0.5);
+    }
+
+    #[test]
+    fn hsv_and_hsl_give_the_primaries()
+    {
+        assert_close(Color::hsv(0., 1., 1.), Color::RED);
+        assert_close(Color::hsv(120., 1., 1.), Color::GREEN);
+        assert_close(Color::hsv(240., 1., 1.), Color::BLUE);
+        assert_close(Color::hsv(-300., 1., 1.), Color::YELLOW);
+        assert_close(Color::hsv(540., 1., 1.), Color::CYAN);
+        assert_close(Color::hsv(77., 0., 0.5), Color::GRAY);
+
+        assert_close(Color::hsl(300., 1., 0.5), Color::MAGENTA);
+        assert_close(Color::hsl(0., 1., 1.), Color::WHITE);
+        assert_close(Color::hsl(200., 0.7, 0.), Color::BLACK);
+    }
+
+    #[test]
+    fn hsv_and_hsl_round_trip()
+    {
+        for hue in (0..360).step_by(15)
+        {
+            let hue = hue as f32;
+
+            let (h, s, v) = Color::hsv(hue, 0.6, 0.8).to_hsv();
+            assert!((h - hue).abs() < 1e-3 && (s - 0.6).abs() < 1e-5 && (v - 0.8).abs() < 1e-5, "{hue} -> {h} {s} {v}");
+
+            let (h, s, l) = Color::hsl(hue, 0.6, 0.3).to_hsl();
+            assert!((h - hue).abs() < 1e-3 && (s - 0.6).abs() < 1e-5 && (l - 0.3).abs() < 1e-5, "{hue} -> {h} {s} {l}");
+        }
+
+        // grays have no hue nor saturation
+        assert_eq!(Color::GRAY.to_hsv(), (0., 0., 0.5));
+        assert_eq!(Color::WHITE.to_hsl(), (0., 0., 1.));
+        assert_eq!(Color::BLACK.to_hsv(), (0., 0., 0.));
+    }
+
+    #[test]
+    fn lerp_blends_in_linear_space()
+    {
+        let middle = Color::BLACK.lerp(Color::WHITE, 0.5);
+
+        assert!(middle.r > 0.7);
+        assert_eq!(Color::BLACK.lerp_srgb(Color::WHITE, 0.5), Color::GRAY);
+        assert_close(Color::RED.lerp(Color::BLUE, 0.), Color::RED);
+        assert_close(Color::RED.lerp(Color::BLUE, 1.), Color::BLUE);
+    }
+
+    #[test]
+    fn gradients_blend_between_their_stops()
+    {
+        let gradient = Gradient::new(Color::BLACK).add(1., Color::WHITE).add(0.5, Color::RED);
+
+        assert_eq!(gradient.stops().iter().map(|(position, _)| *position).collect::<Vec<_>>(), [0., 0.5, 1.]);
+
+        assert_close(gradient.sample(0.), Color::BLACK);
+        assert_close(gradient.sample(0.5), Color::RED);
+        assert_close(gradient.sample(0.25), Color::BLACK.lerp(Color::RED, 0.5));
+        assert_close(gradient.sample(0.75), Color::RED.lerp(Color::WHITE, 0.5));
+
+        // the ends carry on
+        assert_eq!(gradient.sample(-3.), Color::BLACK);
+        assert_eq!(gradient.sample(7.), Color::WHITE);
+    }
+
+    #[test]
+    fn gradients_can_jump_at_a_position()
+    {
+        let gradient = Gradient::new(Color::RED).add(0.5, Color::RED).add(0.5, Color::BLUE).add(1., Color::BLUE);
+
+        assert_close(gradient.sample(0.49), Color::RED);
+        assert_close(gradient.sample(0.5), Color::BLUE);
+        assert_eq!(Gradient::new(Color::GREEN).sample(0.3), Color::GREEN);
+    }
+}
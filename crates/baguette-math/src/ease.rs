@@ -0,0 +1,189 @@
+use std::f32::consts::{PI, TAU};
+
+/// the curves a value can follow while it's animated, they take and return a progress
+/// from `0.` to `1.`, some of them go past it to overshoot.
+///
+/// the `In` curves start slowly, the `Out` ones end slowly and the `InOut` ones do both,
+/// see <https://easings.net> for how they look
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+#[derive(serde::Serialize, serde::Deserialize)]
+pub enum Ease
+{
+    #[default]
+    Linear,
+    QuadIn,
+    QuadOut,
+    QuadInOut,
+    CubicIn,
+    CubicOut,
+    CubicInOut,
+    QuartIn,
+    QuartOut,
+    QuartInOut,
+    QuintIn,
+    QuintOut,
+    QuintInOut,
+    SineIn,
+    SineOut,
+    SineInOut,
+    ExpoIn,
+    ExpoOut,
+    ExpoInOut,
+    CircIn,
+    CircOut,
+    CircInOut,
+    /// goes a bit backwards before moving forward
+    BackIn,
+    /// overshoots the end and comes back
+    BackOut,
+    BackInOut,
+    /// wobbles like a spring
+    ElasticIn,
+    ElasticOut,
+    ElasticInOut,
+    BounceIn,
+    /// bounces on the end like a falling ball
+    BounceOut,
+    BounceInOut,
+    /// jumps to the end at the last moment
+    Step
+}
+
+impl Ease
+{
+    /// how much [Ease::BackIn] and the others go past the ends
+    const BACK: f32 = 1.70158;
+
+    /// eases the progress `t`, which is clamped between `0.` and `1.`
+    pub fn apply(self, t: f32) -> f32
+    {
+        let t = t.clamp(0., 1.);
+
+        match self
+        {
+            Ease::Linear => t,
+
+            Ease::QuadIn => t * t,
+            Ease::QuadOut => out(t, |t| t * t),
+            Ease::QuadInOut => in_out(t, |t| t * t),
+
+            Ease::CubicIn => t.powi(3),
+            Ease::CubicOut => out(t, |t| t.powi(3)),
+            Ease::CubicInOut => in_out(t, |t| t.powi(3)),
+
+            Ease::QuartIn => t.powi(4),
+            Ease::QuartOut => out(t, |t| t.powi(4)),
+            Ease::QuartInOut => in_out(t, |t| t.powi(4)),
+
+            Ease::QuintIn => t.powi(5),
+            Ease::QuintOut => out(t, |t| t.powi(5)),
+            Ease::QuintInOut => in_out(t, |t| t.powi(5)),
+
+            Ease::SineIn => sine_in(t),
+            Ease::SineOut => out(t, sine_in),
+            Ease::SineInOut => in_out(t, sine_in),
+
+            Ease::ExpoIn => expo_in(t),
+            Ease::ExpoOut => out(t, expo_in),
+            Ease::ExpoInOut => in_out(t, expo_in),
+
+            Ease::CircIn => circ_in(t),
+            Ease::CircOut => out(t, circ_in),
+            Ease::CircInOut => in_out(t, circ_in),
+
+            Ease::BackIn => back_in(t),
+            Ease::BackOut => out(t, back_in),
+            Ease::BackInOut => in_out(t, back_in),
+
+            Ease::ElasticIn => elastic_in(t),
+            Ease::ElasticOut => out(t, elastic_in),
+            Ease::ElasticInOut => in_out(t, elastic_in),
+
+            Ease::BounceIn => 1. - bounce_out(1. - t),
+            Ease::BounceOut => bounce_out(t),
+            Ease::BounceInOut => in_out(t, |t| 1. - bounce_out(1. - t)),
+
+            Ease::Step => match t >= 1.
+            {
+                true => 1.,
+                false => 0.
+            }
+        }
+    }
+}
+
+/// the mirror of an `In` curve
+fn out(t: f32, ease_in: impl Fn(f32) -> f32) -> f32
+{
+    1. - ease_in(1. - t)
+}
+
+/// an `In` curve for the first half and its mirror for the second
+fn in_out(t: f32, ease_in: impl Fn(f32) -> f32) -> f32
+{
+    match t < 0.5
+    {
+        true => ease_in(t * 2.) * 0.5,
+        false => 1. - ease_in((1. - t) * 2.) * 0.5
+    }
+}
+
+fn sine_in(t: f32) -> f32
+{
+    1. - (t * PI * 0.5).cos()
+}
+
+fn expo_in(t: f32) -> f32
+{
+    match t <= 0.
+    {
+        true => 0.,
+        false => 2f32.powf(10. * t - 10.)
+    }
+}
+
+fn circ_in(t: f32) -> f32
+{
+    1. - (1. - t * t).max(0.).sqrt()
+}
+
+fn back_in(t: f32) -> f32
+{
+    (Ease::BACK + 1.) * t.powi(3) - Ease::BACK * t * t
+}
+
+fn elastic_in(t: f32) -> f32
+{
+    match t
+    {
+        0. => 0.,
+        1. => 1.,
+        t => -(2f32.powf(10. * t - 10.)) * ((t * 10. - 10.75) * TAU / 3.).sin()
+    }
+}
+
+fn bounce_out(t: f32) -> f32
+{
+    const N: f32 = 7.5625;
+    const D: f32 = 2.75;
+
+    if t < 1. / D
+    {
+        N * t * t
+    }
+    else if t < 2. / D
+    {
+        let t = t - 1.5 / D;
+        N * t * t + 0.75
+    }
+    else if t < 2.5 / D
+    {
+        let t = t - 2.25 / D;
+        N * t * t + 0.9375
+    }
+    else
+    {
+        let t = t - 2.625 / D;
+        N * t * t + 0.984375
+    }
+}
@@ -0,0 +1,437 @@
+use crate::Transform2D;
+
+/// identifies a node of a [Hierarchy], it's no longer valid once the node is removed
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct NodeId
+{
+    index: u32,
+    generation: u32
+}
+
+/// errors returned when changing the parent of a node
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HierarchyError
+{
+    /// the node was removed or belongs to another hierarchy
+    InvalidNode(NodeId),
+    /// the node would become a descendant of itself
+    Cycle
+}
+
+impl std::fmt::Display for HierarchyError
+{
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result
+    {
+        match self
+        {
+            HierarchyError::InvalidNode(node) => write!(f, "the node {} doesn't exist", node.index),
+            HierarchyError::Cycle => write!(f, "a node can't be the parent of one of its ancestors"),
+        }
+    }
+}
+
+impl std::error::Error for HierarchyError {}
+
+struct Node
+{
+    local: Transform2D,
+    world: Transform2D,
+    parent: Option<NodeId>,
+    children: Vec<NodeId>
+}
+
+struct Slot
+{
+    generation: u32,
+    node: Option<Node>
+}
+
+/// transforms attached to each other, the world transform of a child
+/// is its local transform relative to the world transform of its parent.
+///
+/// the world transforms are computed by [Hierarchy::update]
+///
+/// # example
+///
+/// ```
+/// use baguette_math::{vec2, Hierarchy, Transform2D};
+///
+/// let mut hierarchy = Hierarchy::new();
+///
+/// let player = hierarchy.insert(Transform2D::from_translation(vec2(2., 0.)));
+/// let hat = hierarchy.insert_child(player, Transform2D::from_translation(vec2(0., 0.5))).unwrap();
+///
+/// hierarchy.local_mut(player).unwrap().translation.x += 1.;
+/// hierarchy.update();
+///
+/// assert_eq!(hierarchy.world(hat).unwrap().translation, vec2(3., 0.5));
+/// ```
+#[derive(Default)]
+pub struct Hierarchy
+{
+    slots: Vec<Slot>,
+    free: Vec<u32>,
+    roots: Vec<NodeId>,
+    len: usize
+}
+
+impl Hierarchy
+{
+    pub fn new() -> Self
+    {
+        Self::default()
+    }
+
+    /// adds a node without a parent
+    pub fn insert(&mut self, local: Transform2D) -> NodeId
+    {
+        let node = Node { local, world: local, parent: None, children: vec![] };
+
+        let id = match self.free.pop()
+        {
+            Some(index) =>
+            {
+                let slot = &mut self.slots[index as usize];
+                slot.node = Some(node);
+
+                NodeId { index, generation: slot.generation }
+            }
+            None =>
+            {
+                self.slots.push(Slot { generation: 0, node: Some(node) });
+
+                NodeId { index: self.slots.len() as u32 - 1, generation: 0 }
+            }
+        };
+
+        self.roots.push(id);
+        self.len += 1;
+
+        id
+    }
+
+    /// adds a node attached to `parent`
+    pub fn insert_child(&mut self, parent: NodeId, local: Transform2D) -> Result<NodeId, HierarchyError>
+    {
+        let world = self.world(parent).ok_or(HierarchyError::InvalidNode(parent))?;
+
+        let child = self.insert(local);
+        self.set_parent(child, Some(parent))?;
+
+        // the child is placed right away, without waiting for the next update
+        self.node_mut(child).expect("the child was just inserted").world = world * local;
+
+        Ok(child)
+    }
+
+    /// removes the node and all of its descendants
+    pub fn remove(&mut self, id: NodeId)
+    {
+        let Some(node) = self.node(id)
+        else
+        {
+            return
+        };
+
+        match node.parent
+        {
+            Some(parent) => self.detach(parent, id),
+            None => self.roots.retain(|root| *root != id)
+        }
+
+        let mut stack = vec![id];
+
+        while let Some(id) = stack.pop()
+        {
+            let slot = &mut self.slots[id.index as usize];
+
+            if let Some(node) = slot.node.take()
+            {
+                stack.extend(node.children);
+
+                slot.generation = slot.generation.wrapping_add(1);
+                self.free.push(id.index);
+                self.len -= 1
+            }
+        }
+    }
+
+    /// attaches the node to a new parent, or makes it a root with [None].
+    /// its local transform is kept, so it moves to stay relative to the new parent
+    pub fn set_parent(&mut self, id: NodeId, parent: Option<NodeId>) -> Result<(), HierarchyError>
+    {
+        let old = self.node(id).ok_or(HierarchyError::InvalidNode(id))?.parent;
+
+        if let Some(parent) = parent
+        {
+            if !self.contains(parent)
+            {
+                return Err(HierarchyError::InvalidNode(parent))
+            }
+
+            if self.ancestors(parent).any(|ancestor| ancestor == id) || parent == id
+            {
+                return Err(HierarchyError::Cycle)
+            }
+        }
+
+        match old
+        {
+            Some(old) => self.detach(old, id),
+            None => self.roots.retain(|root| *root != id)
+        }
+
+        match parent
+        {
+            Some(parent) => self.node_mut(parent).expect("the parent was checked").children.push(id),
+            None => self.roots.push(id)
+        }
+
+        self.node_mut(id).expect("the node was checked").parent = parent;
+
+        Ok(())
+    }
+
+    fn detach(&mut self, parent: NodeId, child: NodeId)
+    {
+        if let Some(parent) = self.node_mut(parent)
+        {
+            parent.children.retain(|id| *id != child)
+        }
+    }
+
+    fn node(&self, id: NodeId) -> Option<&Node>
+    {
+        self.slots
+            .get(id.index as usize)
+            .filter(|slot| slot.generation == id.generation)
+            .and_then(|slot| slot.node.as_ref())
+    }
+
+    fn node_mut(&mut self, id: NodeId) -> Option<&mut Node>
+    {
+        self.slots
+            .get_mut(id.index as usize)
+            .filter(|slot| slot.generation == id.generation)
+            .and_then(|slot| slot.node.as_mut())
+    }
+
+    /// returns false if the node was removed
+    pub fn contains(&self, id: NodeId) -> bool
+    {
+        self.node(id).is_some()
+    }
+
+    pub fn len(&self) -> usize
+    {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool
+    {
+        self.len == 0
+    }
+
+    /// the transform relative to the parent
+    pub fn local(&self, id: NodeId) -> Option<Transform2D>
+    {
+        self.node(id).map(|node| node.local)
+    }
+
+    /// the transform relative to the parent,
+    /// the world transforms change on the next [Hierarchy::update]
+    pub fn local_mut(&mut self, id: NodeId) -> Option<&mut Transform2D>
+    {
+        self.node_mut(id).map(|node| &mut node.local)
+    }
+
+    pub fn set_local(&mut self, id: NodeId, local: Transform2D)
+    {
+        if let Some(node) = self.node_mut(id)
+        {
+            node.local = local
+        }
+    }
+
+    /// the transform in the world, as of the last [Hierarchy::update]
+    pub fn world(&self, id: NodeId) -> Option<Transform2D>
+    {
+        self.node(id).map(|node| node.world)
+    }
+
+    pub fn parent(&self, id: NodeId) -> Option<NodeId>
+    {
+        self.node(id).and_then(|node| node.parent)
+    }
+
+    pub fn children(&self, id: NodeId) -> &[NodeId]
+    {
+        self.node(id).map_or(&[], |node| &node.children)
+    }
+
+    /// the parent of the node, then its parent and so on
+    pub fn ancestors(&self, id: NodeId) -> impl Iterator<Item = NodeId> + '_
+    {
+        std::iter::successors(self.parent(id), |id| self.parent(*id))
+    }
+
+    /// the nodes without a parent
+    pub fn roots(&self) -> &[NodeId]
+    {
+        &self.roots
+    }
+
+    /// computes the world transforms of every node from its parent
+    pub fn update(&mut self)
+    {
+        let mut stack = self.roots
+            .iter()
+            .map(|root| (*root, Transform2D::IDENTITY))
+            .collect::<Vec<_>>();
+
+        while let Some((id, parent)) = stack.pop()
+        {
+            let Some(node) = self.node_mut(id)
+            else
+            {
+                continue
+            };
+
+            node.world = parent * node.local;
+
+            let world = node.world;
+            stack.extend(node.children.iter().map(|child| (*child, world)))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests
+{
+    use glam::{vec2, Vec2};
+
+    use super::*;
+
+    fn at(x: f32, y: f32) -> Transform2D
+    {
+        Transform2D::from_translation(vec2(x, y))
+    }
+
+    fn world_translation(hierarchy: &Hierarchy, id: NodeId) -> Vec2
+    {
+        hierarchy.world(id).unwrap().translation
+    }
+
+    #[test]
+    fn children_follow_their_parent()
+    {
+        let mut hierarchy = Hierarchy::new();
+
+        let body = hierarchy.insert(at(1., 0.));
+        let arm = hierarchy.insert_child(body, at(0., 1.)).unwrap();
+        let hand = hierarchy.insert_child(arm, at(0., 1.)).unwrap();
+
+        // placed right away
+        assert_eq!(world_translation(&hierarchy, hand), vec2(1., 2.));
+
+        hierarchy.set_local(body, at(5., 0.));
+        assert_eq!(world_translation(&hierarchy, hand), vec2(1., 2.));
+
+        hierarchy.update();
+        assert_eq!(world_translation(&hierarchy, hand), vec2(5., 2.));
+
+        assert_eq!(hierarchy.ancestors(hand).collect::<Vec<_>>(), [arm, body]);
+        assert_eq!(hierarchy.roots(), [body]);
+    }
+
+    #[test]
+    fn reparenting_keeps_the_local_transform()
+    {
+        let mut hierarchy = Hierarchy::new();
+
+        let left = hierarchy.insert(at(-10., 0.));
+        let right = hierarchy.insert(at(10., 0.));
+        let item = hierarchy.insert_child(left, at(0., 1.)).unwrap();
+
+        hierarchy.set_parent(item, Some(right)).unwrap();
+        hierarchy.update();
+
+        assert_eq!(world_translation(&hierarchy, item), vec2(10., 1.));
+        assert_eq!(hierarchy.children(left), []);
+        assert_eq!(hierarchy.children(right), [item]);
+        assert_eq!(hierarchy.parent(item), Some(right));
+
+        hierarchy.set_parent(item, None).unwrap();
+        hierarchy.update();
+
+        assert_eq!(world_translation(&hierarchy, item), vec2(0., 1.));
+        assert_eq!(hierarchy.roots(), [left, right, item]);
+    }
+
+    #[test]
+    fn cycles_are_refused()
+    {
+        let mut hierarchy = Hierarchy::new();
+
+        let root = hierarchy.insert(Transform2D::IDENTITY);
+        let child = hierarchy.insert_child(root, Transform2D::IDENTITY).unwrap();
+        let grandchild = hierarchy.insert_child(child, Transform2D::IDENTITY).unwrap();
+
+        assert_eq!(hierarchy.set_parent(root, Some(grandchild)), Err(HierarchyError::Cycle));
+        assert_eq!(hierarchy.set_parent(child, Some(child)), Err(HierarchyError::Cycle));
+
+        // nothing changed
+        assert_eq!(hierarchy.parent(root), None);
+        assert_eq!(hierarchy.roots(), [root]);
+    }
+
+    #[test]
+    fn removing_a_node_removes_its_descendants()
+    {
+        let mut hierarchy = Hierarchy::new();
+
+        let root = hierarchy.insert(Transform2D::IDENTITY);
+        let child = hierarchy.insert_child(root, Transform2D::IDENTITY).unwrap();
+        let grandchild = hierarchy.insert_child(child, Transform2D::IDENTITY).unwrap();
+        let sibling = hierarchy.insert_child(root, Transform2D::IDENTITY).unwrap();
+
+        hierarchy.remove(child);
+
+        assert_eq!(hierarchy.len(), 2);
+        assert!(!hierarchy.contains(child));
+        assert!(!hierarchy.contains(grandchild));
+        assert_eq!(hierarchy.children(root), [sibling]);
+
+        hierarchy.remove(root);
+
+        assert!(hierarchy.is_empty());
+        assert_eq!(hierarchy.roots(), []);
+    }
+
+    #[test]
+    fn stale_ids_are_rejected_after_their_slot_is_reused()
+    {
+        let mut hierarchy = Hierarchy::new();
+
+        let old = hierarchy.insert(at(1., 1.));
+        hierarchy.remove(old);
+
+        let new = hierarchy.insert(at(2., 2.));
+        assert_ne!(old, new);
+
+        assert!(!hierarchy.contains(old));
+        assert_eq!(hierarchy.world(old), None);
+        assert_eq!(hierarchy.local_mut(old), None);
+        assert_eq!(hierarchy.children(old), []);
+        assert_eq!(hierarchy.insert_child(old, Transform2D::IDENTITY), Err(HierarchyError::InvalidNode(old)));
+        assert_eq!(hierarchy.set_parent(new, Some(old)), Err(HierarchyError::InvalidNode(old)));
+        assert_eq!(hierarchy.set_parent(old, None), Err(HierarchyError::InvalidNode(old)));
+
+        // removing it again doesn't touch the new node
+        hierarchy.remove(old);
+        hierarchy.set_local(old, at(9., 9.));
+
+        assert_eq!(hierarchy.len(), 1);
+        assert_eq!(hierarchy.local(new), Some(at(2., 2.)));
+    }
+}
@@ -4,4 +4,34 @@
 pub use glam as math;
 pub use fastrand as rand;
 
-pub use glam::*;
\ No newline at end of file
+pub use glam::*;
+
+pub mod transform;
+pub use transform::{Transform2D, Transformable};
+
+pub mod hierarchy;
+pub use hierarchy::{Hierarchy, HierarchyError, NodeId};
+
+pub mod collision;
+pub use collision::{Aabb, Capsule, Circle, Contact, ContactPoint, ConvexPolygon, Obb, Ray, RayHit, Shape, ShapeHit};
+
+pub mod spatial;
+pub use spatial::{AabbTree, ProxyId, SpatialHash};
+
+pub mod ease;
+pub use ease::Ease;
+
+pub mod tween;
+pub use tween::{Animation, Animator, Repeat, Sequence, Tween, Tweenable};
+
+pub mod random;
+pub use random::Random;
+
+pub mod noise;
+pub use noise::{Fbm, Noise, NoiseKind};
+
+pub mod color;
+pub use color::{Color, ColorError, Gradient};
+
+pub mod pathfinding;
+pub use pathfinding::{Diagonals, FlowField, NavGrid};
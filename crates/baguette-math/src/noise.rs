@@ -0,0 +1,250 @@
+use glam::{vec2, Vec2};
+
+use crate::Random;
+
+/// a number between `0.` and `1.` for a point, with the same formula as `shaders/noise.wgsl`.
+///
+/// the gpu computes `sin` with its own precision, so the values are close to the shader's
+/// but not always equal, don't rely on them matching exactly
+pub fn shader_hash(co: Vec2, seed: f32) -> f32
+{
+    // the shader's `43758.5453` once rounded to an f32, like the shader compiler does
+    let x = (co.dot(vec2(12.9898 + seed, 78.233))).sin() * 43_758.547;
+
+    // the wgsl fract, rust's one keeps the sign of negative numbers
+    x - x.floor()
+}
+
+/// which noise [Noise::sample] and [Noise::fbm] use
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+#[derive(serde::Serialize, serde::Deserialize)]
+pub enum NoiseKind
+{
+    /// random values blended between the points of a grid, blocky
+    Value,
+    /// random slopes blended between the points of a grid, smooth
+    Perlin,
+    /// like perlin but on a grid of triangles, fewer artifacts along the axes
+    #[default]
+    Simplex
+}
+
+/// the settings of fractal brownian motion, layers of noise at growing
+/// frequencies and shrinking amplitudes, for terrain, clouds and such
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[derive(serde::Serialize, serde::Deserialize)]
+pub struct Fbm
+{
+    /// how many layers are added
+    pub octaves: u32,
+    /// the frequency of the first layer
+    pub frequency: f32,
+    /// how much the frequency is multiplied by at each layer
+    pub lacunarity: f32,
+    /// how much the amplitude is multiplied by at each layer
+    pub gain: f32
+}
+
+impl Default for Fbm
+{
+    fn default() -> Self
+    {
+        Self { octaves: 5, frequency: 1., lacunarity: 2., gain: 0.5 }
+    }
+}
+
+/// seeded coherent noise, the same seed always gives the same values.
+/// every noise returns values roughly between `-1.` and `1.`
+///
+/// # example
+///
+/// ```
+/// use baguette_math::{vec2, Fbm, Noise, NoiseKind};
+///
+/// let noise = Noise::new(42);
+///
+/// let tile = vec2(12., 7.);
+/// let height = noise.fbm(NoiseKind::Simplex, tile * 0.05, &Fbm::default());
+///
+/// // the same seed gives the same world
+/// assert_eq!(height, Noise::new(42).fbm(NoiseKind::Simplex, tile * 0.05, &Fbm::default()));
+/// ```
+#[derive(Debug, Clone)]
+pub struct Noise
+{
+    seed: u32,
+    /// a shuffle of `0..256` repeated twice, so that it can be indexed without wrapping
+    permutation: [u8; 512]
+}
+
+impl Noise
+{
+    pub fn new(seed: u32) -> Self
+    {
+        let mut table: [u8; 256] = std::array::from_fn(|i| i as u8);
+        Random::new(seed as u64).shuffle(&mut table);
+
+        Self { seed, permutation: std::array::from_fn(|i| table[i & 255]) }
+    }
+
+    pub fn seed(&self) -> u32
+    {
+        self.seed
+    }
+
+    pub fn sample(&self, kind: NoiseKind, point: Vec2) -> f32
+    {
+        match kind
+        {
+            NoiseKind::Value => self.value(point),
+            NoiseKind::Perlin => self.perlin(point),
+            NoiseKind::Simplex => self.simplex(point)
+        }
+    }
+
+    /// value noise, the corners of the grid use [shader_hash] with the seed as the shader's `seed`
+    pub fn value(&self, point: Vec2) -> f32
+    {
+        let cell = point.floor();
+        let local = point - cell;
+
+        let corner = |offset: Vec2| shader_hash(cell + offset, self.seed as f32) * 2. - 1.;
+
+        let u = fade(local.x);
+        let v = fade(local.y);
+
+        lerp
+        (
+            lerp(corner(Vec2::ZERO), corner(Vec2::X), u),
+            lerp(corner(Vec2::Y), corner(Vec2::ONE), u),
+            v
+        )
+    }
+
+    /// improved perlin noise
+    pub fn perlin(&self, point: Vec2) -> f32
+    {
+        let cell = point.floor();
+        let local = point - cell;
+
+        let x = cell.x as i32 as usize & 255;
+        let y = cell.y as i32 as usize & 255;
+
+        let corner = |dx: usize, dy: usize|
+        {
+            let hash = self.hash(x + dx, y + dy);
+            gradient(hash, local - vec2(dx as f32, dy as f32))
+        };
+
+        let u = fade(local.x);
+        let v = fade(local.y);
+
+        let value = lerp
+        (
+            lerp(corner(0, 0), corner(1, 0), u),
+            lerp(corner(0, 1), corner(1, 1), u),
+            v
+        );
+
+        value.clamp(-1., 1.)
+    }
+
+    /// 2d simplex noise
+    pub fn simplex(&self, point: Vec2) -> f32
+    {
+        // skews the plane so that the triangles become squares and back
+        const F2: f32 = 0.366_025_42;
+        const G2: f32 = 0.211_324_87;
+
+        let skewed = (point + (point.x + point.y) * F2).floor();
+        let first = point - (skewed - (skewed.x + skewed.y) * G2);
+
+        // which triangle of the square the point is in
+        let step = match first.x > first.y
+        {
+            true => (1, 0),
+            false => (0, 1)
+        };
+
+        let second = first - vec2(step.0 as f32, step.1 as f32) + G2;
+        let third = first - 1. + 2. * G2;
+
+        let x = skewed.x as i32 as usize & 255;
+        let y = skewed.y as i32 as usize & 255;
+
+        let corner = |offset: Vec2, dx: usize, dy: usize|
+        {
+            let falloff = 0.5 - offset.length_squared();
+
+            match falloff > 0.
+            {
+                true => falloff.powi(4) * gradient(self.hash(x + dx, y + dy), offset),
+                false => 0.
+            }
+        };
+
+        let value = corner(first, 0, 0) + corner(second, step.0, step.1) + corner(third, 1, 1);
+
+        (value * 70.).clamp(-1., 1.)
+    }
+
+    /// fractal brownian motion of the noise, normalized to stay roughly between `-1.` and `1.`
+    pub fn fbm(&self, kind: NoiseKind, point: Vec2, fbm: &Fbm) -> f32
+    {
+        let mut frequency = fbm.frequency;
+        let mut amplitude = 1.;
+
+        let mut sum = 0.;
+        let mut total = 0.;
+
+        for octave in 0..fbm.octaves
+        {
+            // moves every layer a bit so that they don't all line up on the origin
+            let offset = Vec2::splat(octave as f32 * 17.31);
+
+            sum += self.sample(kind, point * frequency + offset) * amplitude;
+            total += amplitude;
+
+            frequency *= fbm.lacunarity;
+            amplitude *= fbm.gain;
+        }
+
+        match total > 0.
+        {
+            true => sum / total,
+            false => 0.
+        }
+    }
+
+    fn hash(&self, x: usize, y: usize) -> u8
+    {
+        self.permutation[self.permutation[x] as usize + y]
+    }
+}
+
+/// the smootherstep curve, its slope is zero on both ends so the cells blend without creases
+fn fade(t: f32) -> f32
+{
+    t * t * t * (t * (t * 6. - 15.) + 10.)
+}
+
+fn lerp(a: f32, b: f32, t: f32) -> f32
+{
+    a + (b - a) * t
+}
+
+/// the dot product of the offset with one of eight directions picked by the hash
+fn gradient(hash: u8, offset: Vec2) -> f32
+{
+    match hash & 7
+    {
+        0 => offset.x + offset.y,
+        1 => -offset.x + offset.y,
+        2 => offset.x - offset.y,
+        3 => -offset.x - offset.y,
+        4 => offset.x,
+        5 => -offset.x,
+        6 => offset.y,
+        _ => -offset.y
+    }
+}
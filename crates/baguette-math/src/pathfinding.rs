@@ -0,0 +1,95 @@
+//! finds the way through the tiles of a level, with the same positions as the tiles drawn by a tilemap
+//!
+//! - [NavGrid::find_path] gives the path of one agent with A*,
+//!   or with jump point search when all the tiles cost the same
+//! - [FlowField] gives the way to the same target from anywhere, best when a crowd goes to the same place
+//!
+//! # example
+//!
+//! ```
+//! use baguette_math::{vec2, NavGrid, Vec2};
+//!
+//! let ground: Vec<Vec2> = (0..10).flat_map(|x| (0..10).map(move |y| vec2(x as f32, y as f32))).collect();
+//! let walls: Vec<Vec2> = (0..8).map(|y| vec2(5., y as f32)).collect();
+//! let mud = [vec2(2., 5.), vec2(3., 5.)];
+//! let door = vec2(5., 8.);
+//!
+//! let mut grid = NavGrid::from_tiles(ground.iter().copied())
+//!     .with_obstacles(walls.iter().copied());
+//!
+//! for tile in &mud
+//! {
+//!     grid.set_cost(*tile, 3.);
+//! }
+//!
+//! // a door closed
+//! grid.set_blocked(door, true);
+//!
+//! let (enemy, player) = (vec2(1., 1.), vec2(8., 1.));
+//!
+//! // around the wall, through the gap above the door
+//! let path = grid.find_path(enemy, player).expect("there's a way");
+//! let waypoints = grid.smooth_path(&path);
+//!
+//! assert_eq!(waypoints.last(), Some(&player));
+//! ```
+
+use std::cmp::Ordering;
+
+mod grid;
+pub use grid::NavGrid;
+
+mod jps;
+
+mod flow;
+pub use flow::FlowField;
+
+/// when the agents can move diagonally between the tiles
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+#[derive(serde::Serialize, serde::Deserialize)]
+pub enum Diagonals
+{
+    /// only up, down, left and right
+    Never,
+    /// only when both tiles on the sides are free, so that the agents don't cut the corners of the walls
+    #[default]
+    NoCorners,
+    /// even squeezing between two blocked tiles touching by a corner
+    Always
+}
+
+/// a tile waiting to be looked at, the cheapest one comes out of the heap first
+#[derive(Clone, Copy)]
+struct Open
+{
+    /// the cost so far plus the estimate of the rest
+    priority: f32,
+    cost: f32,
+    index: usize
+}
+
+impl PartialEq for Open
+{
+    fn eq(&self, other: &Self) -> bool
+    {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for Open {}
+
+impl PartialOrd for Open
+{
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering>
+    {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Open
+{
+    fn cmp(&self, other: &Self) -> Ordering
+    {
+        other.priority.total_cmp(&self.priority)
+    }
+}
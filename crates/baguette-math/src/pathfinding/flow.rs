@@ -0,0 +1,154 @@
+use std::collections::BinaryHeap;
+
+use glam::{IVec2, Vec2};
+
+use crate::Transform2D;
+use super::{NavGrid, Open};
+
+/// the cost to reach the targets from every tile of a [NavGrid] and the next tile to walk to,
+/// computed once and then read by any number of agents.
+///
+/// it doesn't follow the changes of the grid, build it again when [FlowField::is_outdated]
+///
+/// # example
+///
+/// ```
+/// use baguette_math::{vec2, NavGrid, Vec2};
+///
+/// let mut grid = NavGrid::new(vec2(0., 0.), vec2(9., 9.));
+/// let base = vec2(9., 5.);
+/// let enemies = [vec2(0., 5.), vec2(2., 1.)];
+///
+/// let mut field = grid.flow_field(base);
+///
+/// // a wall built
+/// grid.set_blocked(vec2(5., 5.), true);
+///
+/// // every frame
+/// if field.is_outdated(&grid)
+/// {
+///     field = grid.flow_field(base);
+/// }
+///
+/// let velocities: Vec<Vec2> = enemies.iter().map(|enemy| field.direction(*enemy).unwrap_or_default() * 3.).collect();
+/// assert!(velocities.iter().all(|velocity| velocity.x > 0.));
+/// ```
+pub struct FlowField
+{
+    min: IVec2,
+    width: i32,
+    height: i32,
+
+    costs: Vec<f32>,
+    /// the index of the next tile towards the closest target, [usize::MAX] on the targets
+    /// and the tiles that can't reach one
+    next: Vec<usize>,
+
+    transform: Transform2D,
+    inverse: Transform2D,
+    version: u64
+}
+
+impl FlowField
+{
+    pub(super) fn new(grid: &NavGrid, targets: impl IntoIterator<Item = IVec2>) -> Self
+    {
+        let mut costs = vec![f32::INFINITY; grid.blocked.len()];
+        let mut next = vec![usize::MAX; grid.blocked.len()];
+        let mut open = BinaryHeap::new();
+
+        for index in targets.into_iter().filter(|cell| grid.walkable(*cell)).filter_map(|cell| grid.index(cell))
+        {
+            costs[index] = 0.;
+            open.push(Open { priority: 0., cost: 0., index });
+        }
+
+        // dijkstra from the targets, the moves cost the same both ways
+        while let Some(Open { cost, index, .. }) = open.pop()
+        {
+            if cost > costs[index]
+            {
+                continue
+            }
+
+            for (neighbor, step) in grid.neighbors(grid.cell(index))
+            {
+                let neighbor_index = grid.index(neighbor).expect("the neighbors are in the grid");
+                let neighbor_cost = cost + step;
+
+                if neighbor_cost < costs[neighbor_index]
+                {
+                    costs[neighbor_index] = neighbor_cost;
+                    next[neighbor_index] = index;
+
+                    open.push(Open { priority: neighbor_cost, cost: neighbor_cost, index: neighbor_index })
+                }
+            }
+        }
+
+        Self
+        {
+            min: grid.min,
+            width: grid.width,
+            height: grid.height,
+
+            costs,
+            next,
+
+            transform: grid.transform,
+            inverse: grid.inverse,
+            version: grid.version
+        }
+    }
+
+    /// the cost to reach the closest target from the point of the world,
+    /// [None] if it can't reach any
+    pub fn cost(&self, point: Vec2) -> Option<f32>
+    {
+        self.index_at(point).map(|index| self.costs[index]).filter(|cost| cost.is_finite())
+    }
+
+    /// the center of the next tile to walk to from the point of the world,
+    /// [None] on a target or where no target can be reached
+    pub fn next_tile(&self, point: Vec2) -> Option<Vec2>
+    {
+        let next = self.next[self.index_at(point)?];
+
+        match next
+        {
+            usize::MAX => None,
+            next => Some(self.transform.transform_point(self.cell(next).as_vec2()))
+        }
+    }
+
+    /// the direction from the point of the world to the next tile, normalized.
+    /// zero on a target and [None] where no target can be reached
+    pub fn direction(&self, point: Vec2) -> Option<Vec2>
+    {
+        self.cost(point)?;
+
+        Some(self.next_tile(point).map_or(Vec2::ZERO, |next| (next - point).normalize_or_zero()))
+    }
+
+    /// returns true if the grid changed since the field was built
+    pub fn is_outdated(&self, grid: &NavGrid) -> bool
+    {
+        self.version != grid.version
+    }
+
+    fn index_at(&self, point: Vec2) -> Option<usize>
+    {
+        let offset = super::grid::cell(self.inverse.transform_point(point)) - self.min;
+
+        match offset.x >= 0 && offset.y >= 0 && offset.x < self.width && offset.y < self.height
+        {
+            true => Some((offset.y * self.width + offset.x) as usize),
+            false => None
+        }
+    }
+
+    fn cell(&self, index: usize) -> IVec2
+    {
+        self.min + IVec2::new(index as i32 % self.width, index as i32 / self.width)
+    }
+}
@@ -0,0 +1,715 @@
+use std::collections::BinaryHeap;
+use std::f32::consts::SQRT_2;
+
+use glam::{ivec2, IVec2, Vec2};
+
+use crate::Transform2D;
+use super::{Diagonals, FlowField, Open};
+
+const STRAIGHT: [IVec2; 4] = [IVec2::X, IVec2::Y, IVec2::NEG_X, IVec2::NEG_Y];
+const DIAGONAL: [IVec2; 4] = [IVec2::ONE, ivec2(-1, 1), IVec2::NEG_ONE, ivec2(1, -1)];
+
+/// the tiles agents can walk on and how much walking on them costs.
+///
+/// the tiles are one unit wide and found by their positions inside the tilemap, the `pos` of its `Tile`s,
+/// the transform places them in the world like the one of the tilemap.
+/// the paths go from the center of a tile to the next
+pub struct NavGrid
+{
+    /// the position of the first tile
+    pub(super) min: IVec2,
+    pub(super) width: i32,
+    pub(super) height: i32,
+
+    pub(super) blocked: Vec<bool>,
+    /// the cost of walking one unit on each tile, at least one
+    pub(super) costs: Vec<f32>,
+    /// how many tiles don't cost one, jump point search only works without them
+    weighted: usize,
+
+    pub(super) diagonals: Diagonals,
+    pub(super) transform: Transform2D,
+    pub(super) inverse: Transform2D,
+    /// changes with the tiles, to know when a [FlowField] is outdated
+    pub(super) version: u64
+}
+
+impl NavGrid
+{
+    /// every tile from `min` to `max` is walkable, the corners are positions of tiles
+    pub fn new(min: Vec2, max: Vec2) -> Self
+    {
+        let (min, max) = (cell(min.min(max)), cell(min.max(max)));
+        let size = max - min + 1;
+        let len = (size.x * size.y) as usize;
+
+        Self
+        {
+            min,
+            width: size.x,
+            height: size.y,
+
+            blocked: vec![false; len],
+            costs: vec![1.; len],
+            weighted: 0,
+
+            diagonals: Diagonals::default(),
+            transform: Transform2D::IDENTITY,
+            inverse: Transform2D::IDENTITY,
+            version: 0
+        }
+    }
+
+    /// only the tiles at these positions are walkable, like the floor of a top down level
+    pub fn from_tiles(positions: impl IntoIterator<Item = Vec2>) -> Self
+    {
+        let positions = positions.into_iter().map(cell).collect::<Vec<_>>();
+
+        let (min, max) = positions
+            .iter()
+            .fold((IVec2::MAX, IVec2::MIN), |(min, max), cell| (min.min(*cell), max.max(*cell)));
+
+        if positions.is_empty()
+        {
+            return Self { width: 0, height: 0, blocked: vec![], costs: vec![], ..Self::new(Vec2::ZERO, Vec2::ZERO) }
+        }
+
+        let mut grid = Self::new(min.as_vec2(), max.as_vec2());
+        grid.blocked.fill(true);
+
+        for cell in positions
+        {
+            let index = grid.index(cell).expect("the grid covers the tiles");
+            grid.blocked[index] = false
+        }
+
+        grid
+    }
+
+    /// blocks the tiles at these positions, like the walls of a level
+    pub fn with_obstacles(mut self, positions: impl IntoIterator<Item = Vec2>) -> Self
+    {
+        for position in positions
+        {
+            self.set_blocked(position, true)
+        }
+
+        self
+    }
+
+    /// [Diagonals::NoCorners] by default
+    pub fn with_diagonals(mut self, diagonals: Diagonals) -> Self
+    {
+        self.diagonals = diagonals;
+        self.version += 1;
+        self
+    }
+
+    /// places the tiles in the world, use the transform of the tilemap
+    pub fn with_transform(mut self, transform: Transform2D) -> Self
+    {
+        self.set_transform(transform);
+        self
+    }
+
+    pub fn diagonals(&self) -> Diagonals
+    {
+        self.diagonals
+    }
+
+    pub fn transform(&self) -> Transform2D
+    {
+        self.transform
+    }
+
+    /// moves all the tiles, call it along with `Renderer::set_tilemap_transform`
+    pub fn set_transform(&mut self, transform: Transform2D)
+    {
+        self.transform = transform;
+        self.inverse = transform.inverse();
+        self.version += 1
+    }
+
+    /// blocks or frees the tile at this position inside the tilemap, for doors and moving obstacles.
+    /// nothing happens outside the grid
+    pub fn set_blocked(&mut self, position: Vec2, blocked: bool)
+    {
+        if let Some(index) = self.index(cell(position))
+        {
+            self.blocked[index] = blocked;
+            self.version += 1
+        }
+    }
+
+    /// how much walking on the tile at this position costs compared to a plain one,
+    /// like `3.` for mud, it can't be less than `1.`. nothing happens outside the grid
+    pub fn set_cost(&mut self, position: Vec2, cost: f32)
+    {
+        let Some(index) = self.index(cell(position))
+        else
+        {
+            return
+        };
+
+        let cost = cost.max(1.);
+
+        match (self.costs[index] == 1., cost == 1.)
+        {
+            (true, false) => self.weighted += 1,
+            (false, true) => self.weighted -= 1,
+            _ => ()
+        }
+
+        self.costs[index] = cost;
+        self.version += 1
+    }
+
+    /// the cost of the tile at this position inside the tilemap, [None] if it's blocked or outside the grid
+    pub fn cost(&self, position: Vec2) -> Option<f32>
+    {
+        self.cell_cost(cell(position))
+    }
+
+    pub fn is_walkable(&self, position: Vec2) -> bool
+    {
+        self.walkable(cell(position))
+    }
+
+    /// the position inside the tilemap of the tile covering this point of the world
+    pub fn tile_at(&self, point: Vec2) -> Vec2
+    {
+        self.cell_at(point).as_vec2()
+    }
+
+    /// the center of the tile at this position inside the tilemap, in the world
+    pub fn tile_center(&self, position: Vec2) -> Vec2
+    {
+        self.transform.transform_point(cell(position).as_vec2())
+    }
+
+    /// the centers of the tiles from the one under `from` to the one under `to`, both points in the world.
+    /// [None] if one of them is blocked or there's no way between them
+    pub fn find_path(&self, from: Vec2, to: Vec2) -> Option<Vec<Vec2>>
+    {
+        let (start, goal) = (self.cell_at(from), self.cell_at(to));
+
+        if !self.walkable(start) || !self.walkable(goal)
+        {
+            return None
+        }
+
+        let cells = match self.weighted == 0 && self.diagonals == Diagonals::NoCorners
+        {
+            true => self.jump_point_search(start, goal)?,
+            false => self.astar(start, goal)?
+        };
+
+        Some(cells.into_iter().map(|cell| self.transform.transform_point(cell.as_vec2())).collect())
+    }
+
+    /// the directions towards the tile under `target` from every tile that can reach it
+    pub fn flow_field(&self, target: Vec2) -> FlowField
+    {
+        self.flow_field_to([target])
+    }
+
+    /// the directions towards the closest of the targets from every tile that can reach one,
+    /// like the exits of a building
+    pub fn flow_field_to(&self, targets: impl IntoIterator<Item = Vec2>) -> FlowField
+    {
+        FlowField::new(self, targets.into_iter().map(|target| self.cell_at(target)))
+    }
+
+    /// returns true if the segment between the points of the world only crosses walkable tiles,
+    /// without going through the corners of the blocked ones
+    pub fn has_line_of_sight(&self, from: Vec2, to: Vec2) -> bool
+    {
+        self.line_of_sight(from, to, f32::INFINITY)
+    }
+
+    /// removes the points of the path that can be skipped by going straight,
+    /// without crossing a tile more expensive than those of the part it replaces
+    pub fn smooth_path(&self, path: &[Vec2]) -> Vec<Vec2>
+    {
+        let Some(first) = path.first()
+        else
+        {
+            return vec![]
+        };
+
+        let cost = |point: &Vec2| self.cell_cost(self.cell_at(*point)).unwrap_or(1.);
+
+        let mut smoothed = vec![*first];
+        let mut anchor = 0;
+
+        while anchor + 1 < path.len()
+        {
+            let mut furthest = anchor + 1;
+            let mut limit = cost(&path[anchor]).max(cost(&path[furthest]));
+
+            for next in anchor + 2..path.len()
+            {
+                limit = limit.max(cost(&path[next]));
+
+                match self.line_of_sight(path[anchor], path[next], limit)
+                {
+                    true => furthest = next,
+                    false => break
+                }
+            }
+
+            smoothed.push(path[furthest]);
+            anchor = furthest
+        }
+
+        smoothed
+    }
+
+    pub(super) fn index(&self, cell: IVec2) -> Option<usize>
+    {
+        let offset = cell - self.min;
+
+        match offset.x >= 0 && offset.y >= 0 && offset.x < self.width && offset.y < self.height
+        {
+            true => Some((offset.y * self.width + offset.x) as usize),
+            false => None
+        }
+    }
+
+    pub(super) fn cell(&self, index: usize) -> IVec2
+    {
+        self.min + ivec2(index as i32 % self.width, index as i32 / self.width)
+    }
+
+    pub(super) fn cell_at(&self, point: Vec2) -> IVec2
+    {
+        cell(self.inverse.transform_point(point))
+    }
+
+    pub(super) fn walkable(&self, cell: IVec2) -> bool
+    {
+        self.index(cell).is_some_and(|index| !self.blocked[index])
+    }
+
+    fn cell_cost(&self, cell: IVec2) -> Option<f32>
+    {
+        self.index(cell).filter(|index| !self.blocked[*index]).map(|index| self.costs[index])
+    }
+
+    /// the walkable tiles next to this one, and how much it costs to walk from one to the other.
+    /// the moves are the same both ways
+    pub(super) fn neighbors(&self, cell: IVec2) -> impl Iterator<Item = (IVec2, f32)> + '_
+    {
+        // half of each tile is walked on
+        let step = move |next: IVec2, length: f32| Some((next, (self.cell_cost(cell)? + self.cell_cost(next)?) * 0.5 * length));
+
+        let straight = STRAIGHT.into_iter().filter_map(move |direction| step(cell + direction, 1.));
+
+        let diagonal = DIAGONAL.into_iter().filter_map(move |direction|
+        {
+            let allowed = match self.diagonals
+            {
+                Diagonals::Never => false,
+                Diagonals::NoCorners => self.walkable(cell + ivec2(direction.x, 0)) && self.walkable(cell + ivec2(0, direction.y)),
+                Diagonals::Always => true
+            };
+
+            allowed.then(|| step(cell + direction, SQRT_2)).flatten()
+        });
+
+        straight.chain(diagonal)
+    }
+
+    /// the cheapest possible cost between the tiles, the costs are at least one
+    pub(super) fn heuristic(&self, a: IVec2, b: IVec2) -> f32
+    {
+        let distance = (a - b).abs();
+        let (long, short) = (distance.max_element() as f32, distance.min_element() as f32);
+
+        match self.diagonals
+        {
+            Diagonals::Never => long + short,
+            _ => long - short + short * SQRT_2
+        }
+    }
+
+    fn astar(&self, start: IVec2, goal: IVec2) -> Option<Vec<IVec2>>
+    {
+        let (start, goal) = (self.index(start)?, self.index(goal)?);
+
+        let mut costs = vec![f32::INFINITY; self.blocked.len()];
+        let mut parents = vec![usize::MAX; self.blocked.len()];
+        let mut open = BinaryHeap::new();
+
+        costs[start] = 0.;
+        open.push(Open { priority: 0., cost: 0., index: start });
+
+        while let Some(Open { cost, index, .. }) = open.pop()
+        {
+            if index == goal
+            {
+                return Some(self.trace(&parents, goal))
+            }
+
+            // already reached by a cheaper way
+            if cost > costs[index]
+            {
+                continue
+            }
+
+            let cell = self.cell(index);
+
+            for (next, step) in self.neighbors(cell)
+            {
+                let next_index = self.index(next).expect("the neighbors are in the grid");
+                let next_cost = cost + step;
+
+                if next_cost < costs[next_index]
+                {
+                    costs[next_index] = next_cost;
+                    parents[next_index] = index;
+
+                    open.push(Open
+                    {
+                        priority: next_cost + self.heuristic(next, self.cell(goal)),
+                        cost: next_cost,
+                        index: next_index
+                    })
+                }
+            }
+        }
+
+        None
+    }
+
+    /// the cells from the start to `goal`, following the parents back
+    pub(super) fn trace(&self, parents: &[usize], goal: usize) -> Vec<IVec2>
+    {
+        let mut path = vec![self.cell(goal)];
+        let mut index = goal;
+
+        while parents[index] != usize::MAX
+        {
+            index = parents[index];
+            path.push(self.cell(index))
+        }
+
+        path.reverse();
+        path
+    }
+
+    /// walks the tiles crossed by the segment, they must be walkable and cost at most `limit`
+    fn line_of_sight(&self, from: Vec2, to: Vec2, limit: f32) -> bool
+    {
+        let clear = |cell: IVec2| self.cell_cost(cell).is_some_and(|cost| cost <= limit);
+
+        // the tiles go from `n - 0.5` to `n + 0.5`, moved so that they start on whole numbers
+        let (from, to) = (self.inverse.transform_point(from) + 0.5, self.inverse.transform_point(to) + 0.5);
+
+        let mut current = from.floor().as_ivec2();
+        let end = to.floor().as_ivec2();
+
+        let delta = to - from;
+        let sign = |value: f32| match value
+        {
+            value if value > 0. => 1,
+            value if value < 0. => -1,
+            _ => 0
+        };
+
+        let step = ivec2(sign(delta.x), sign(delta.y));
+
+        // how far along the segment the next tile starts on each axis, and how far each tile is
+        let next_boundary = |position: f32, cell: i32, step: i32, delta: f32| match step
+        {
+            0 => f32::INFINITY,
+            1 => (cell as f32 + 1. - position) / delta,
+            _ => (position - cell as f32) / -delta
+        };
+
+        let mut t_max = Vec2::new(next_boundary(from.x, current.x, step.x, delta.x), next_boundary(from.y, current.y, step.y, delta.y));
+        let t_delta = Vec2::new(1. / delta.x.abs(), 1. / delta.y.abs());
+
+        if !clear(current)
+        {
+            return false
+        }
+
+        let max_steps = (end - current).abs().element_sum() + 1;
+
+        for _ in 0..max_steps
+        {
+            if current == end
+            {
+                return true
+            }
+
+            if (t_max.x - t_max.y).abs() <= 1e-5
+            {
+                // through a corner, both tiles on the sides must be free
+                if !clear(current + ivec2(step.x, 0)) || !clear(current + ivec2(0, step.y))
+                {
+                    return false
+                }
+
+                current += step;
+                t_max += t_delta;
+            }
+            else if t_max.x < t_max.y
+            {
+                current.x += step.x;
+                t_max.x += t_delta.x;
+            }
+            else
+            {
+                current.y += step.y;
+                t_max.y += t_delta.y;
+            }
+
+            if !clear(current)
+            {
+                return false
+            }
+        }
+
+        current == end
+    }
+}
+
+/// the tile at this position inside the tilemap
+pub(super) fn cell(position: Vec2) -> IVec2
+{
+    position.round().as_ivec2()
+}
+
+#[cfg(test)]
+mod tests
+{
+    use glam::vec2;
+
+    use crate::Random;
+    use super::*;
+
+    /// an open square of tiles from `0` to `size - 1`
+    fn open(size: i32) -> NavGrid
+    {
+        NavGrid::new(Vec2::ZERO, Vec2::splat(size as f32 - 1.))
+    }
+
+    /// a square with about a third of the tiles blocked
+    fn maze(seed: u64) -> NavGrid
+    {
+        let mut random = Random::new(seed);
+        let mut grid = open(16);
+
+        for x in 0..16
+        {
+            for y in 0..16
+            {
+                grid.set_blocked(vec2(x as f32, y as f32), random.chance(0.3))
+            }
+        }
+
+        grid
+    }
+
+    /// the cost of walking along the path, each move must be allowed by the grid
+    fn path_cost(grid: &NavGrid, path: &[Vec2]) -> f32
+    {
+        path.windows(2)
+            .map(|pair|
+            {
+                let (from, to) = (grid.cell_at(pair[0]), grid.cell_at(pair[1]));
+
+                grid.neighbors(from)
+                    .find(|(next, _)| *next == to)
+                    .map(|(_, cost)| cost)
+                    .unwrap_or_else(|| panic!("{from} to {to} isn't a move"))
+            })
+            .sum()
+    }
+
+    #[test]
+    fn jump_point_search_costs_the_same_as_astar()
+    {
+        for seed in 0..20
+        {
+            let grid = maze(seed);
+            let mut random = Random::new(seed);
+
+            for _ in 0..20
+            {
+                let start = ivec2(random.i32(0..16), random.i32(0..16));
+                let goal = ivec2(random.i32(0..16), random.i32(0..16));
+
+                if !grid.walkable(start) || !grid.walkable(goal)
+                {
+                    continue
+                }
+
+                let to_points = |cells: Vec<IVec2>| cells.into_iter().map(|cell| cell.as_vec2()).collect::<Vec<_>>();
+
+                let jps = grid.jump_point_search(start, goal).map(to_points);
+                let astar = grid.astar(start, goal).map(to_points);
+
+                match (jps, astar)
+                {
+                    (Some(jps), Some(astar)) =>
+                    {
+                        assert_eq!((jps[0], *jps.last().unwrap()), (start.as_vec2(), goal.as_vec2()));
+
+                        let (jps_cost, astar_cost) = (path_cost(&grid, &jps), path_cost(&grid, &astar));
+                        assert!((jps_cost - astar_cost).abs() < 1e-3, "seed {seed}, {start} to {goal}: {jps_cost} != {astar_cost}");
+                    }
+                    (None, None) => (),
+                    (jps, astar) => panic!("seed {seed}, {start} to {goal}: {jps:?} != {astar:?}")
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn never_moves_diagonally()
+    {
+        let grid = open(5).with_diagonals(Diagonals::Never);
+
+        let path = grid.find_path(vec2(0., 0.), vec2(3., 3.)).unwrap();
+
+        assert_eq!(path.len(), 7);
+        assert_eq!(path_cost(&grid, &path), 6.);
+        assert!(path.windows(2).all(|pair| (pair[1] - pair[0]).abs().element_sum() == 1.));
+    }
+
+    #[test]
+    fn diagonals_squeeze_between_corners_only_when_always()
+    {
+        let corner = |diagonals: Diagonals| open(3)
+            .with_obstacles([vec2(1., 0.), vec2(0., 1.)])
+            .with_diagonals(diagonals)
+            .find_path(vec2(0., 0.), vec2(1., 1.));
+
+        assert_eq!(corner(Diagonals::Always), Some(vec![vec2(0., 0.), vec2(1., 1.)]));
+        assert_eq!(corner(Diagonals::NoCorners), None);
+        assert_eq!(corner(Diagonals::Never), None);
+
+        // the open diagonals are taken by both
+        for diagonals in [Diagonals::Always, Diagonals::NoCorners]
+        {
+            let path = open(5).with_diagonals(diagonals).find_path(vec2(0., 0.), vec2(4., 4.)).unwrap();
+            assert_eq!(path.len(), 5);
+        }
+    }
+
+    #[test]
+    fn routes_around_expensive_tiles()
+    {
+        let with_mud = |cost: f32|
+        {
+            let mut grid = NavGrid::new(vec2(0., 0.), vec2(6., 4.));
+
+            for y in 0..4
+            {
+                grid.set_cost(vec2(3., y as f32), cost)
+            }
+
+            grid
+        };
+
+        let grid = with_mud(10.);
+        let path = grid.find_path(vec2(0., 0.), vec2(6., 0.)).unwrap();
+
+        assert!(path.iter().all(|tile| tile.x != 3. || tile.y == 4.), "{path:?}");
+        assert_eq!(grid.cost(vec2(3., 0.)), Some(10.));
+
+        // going around costs more than a bit of mud
+        let grid = with_mud(1.5);
+        let path = grid.find_path(vec2(0., 0.), vec2(6., 0.)).unwrap();
+
+        // half of the mud tile is walked on from each side
+        assert_eq!(path.len(), 7);
+        assert_eq!(path_cost(&grid, &path), 6. + 0.5);
+    }
+
+    #[test]
+    fn smoothed_paths_dont_cross_blocked_tiles()
+    {
+        for seed in 0..20
+        {
+            let grid = maze(seed);
+            let mut random = Random::new(seed + 100);
+
+            for _ in 0..20
+            {
+                let from = vec2(random.i32(0..16) as f32, random.i32(0..16) as f32);
+                let to = vec2(random.i32(0..16) as f32, random.i32(0..16) as f32);
+
+                let Some(path) = grid.find_path(from, to)
+                else
+                {
+                    continue
+                };
+
+                let smoothed = grid.smooth_path(&path);
+
+                assert_eq!((smoothed.first(), smoothed.last()), (path.first(), path.last()));
+                assert!(smoothed.len() <= path.len());
+
+                for pair in smoothed.windows(2)
+                {
+                    assert!(grid.has_line_of_sight(pair[0], pair[1]));
+
+                    for step in 0..=100
+                    {
+                        let point = pair[0].lerp(pair[1], step as f32 / 100.);
+                        assert!(grid.is_walkable(grid.tile_at(point)), "seed {seed}, {} to {} crosses {point}", pair[0], pair[1]);
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn smoothing_keeps_away_from_expensive_tiles()
+    {
+        let mut grid = NavGrid::new(vec2(0., 0.), vec2(6., 4.));
+        grid.set_cost(vec2(3., 2.), 10.);
+
+        let path = grid.find_path(vec2(0., 2.), vec2(6., 2.)).unwrap();
+        let smoothed = grid.smooth_path(&path);
+
+        assert!(!path.contains(&vec2(3., 2.)));
+        assert!(smoothed.windows(2).all(|pair| grid.line_of_sight(pair[0], pair[1], 1.)), "{smoothed:?}");
+    }
+
+    #[test]
+    fn flow_fields_know_when_the_grid_changed()
+    {
+        let mut grid = open(10);
+        let mut field = grid.flow_field(vec2(9., 5.));
+
+        assert!(!field.is_outdated(&grid));
+        assert_eq!(field.cost(vec2(0., 5.)), Some(9.));
+        assert_eq!(field.next_tile(vec2(0., 5.)), Some(vec2(1., 5.)));
+
+        // outside the grid nothing changes
+        grid.set_blocked(vec2(50., 50.), true);
+        assert!(!field.is_outdated(&grid));
+
+        for y in 1..10
+        {
+            grid.set_blocked(vec2(5., y as f32), true)
+        }
+
+        assert!(field.is_outdated(&grid));
+
+        field = grid.flow_field(vec2(9., 5.));
+
+        assert!(!field.is_outdated(&grid));
+        assert!(field.cost(vec2(0., 5.)).unwrap() > 9.);
+        assert_eq!(field.cost(vec2(5., 5.)), None);
+        assert_eq!(field.direction(vec2(9., 5.)), Some(Vec2::ZERO));
+
+        grid.set_cost(vec2(0., 0.), 2.);
+        assert!(field.is_outdated(&grid));
+    }
+}
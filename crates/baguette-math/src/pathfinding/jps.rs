@@ -0,0 +1,178 @@
+//! jump point search, an A* that skips the tiles in the middle of open areas.
+//! it only gives the cheapest path when all the tiles cost the same,
+//! this is the variant that doesn't cut corners, like [Diagonals::NoCorners](super::Diagonals::NoCorners)
+
+use std::collections::BinaryHeap;
+
+use glam::{ivec2, IVec2};
+
+use super::{NavGrid, Open};
+
+impl NavGrid
+{
+    pub(super) fn jump_point_search(&self, start: IVec2, goal: IVec2) -> Option<Vec<IVec2>>
+    {
+        let (start_index, goal_index) = (self.index(start)?, self.index(goal)?);
+
+        let mut costs = vec![f32::INFINITY; self.blocked.len()];
+        let mut parents = vec![usize::MAX; self.blocked.len()];
+        let mut open = BinaryHeap::new();
+
+        costs[start_index] = 0.;
+        open.push(Open { priority: 0., cost: 0., index: start_index });
+
+        while let Some(Open { cost, index, .. }) = open.pop()
+        {
+            if index == goal_index
+            {
+                return Some(self.fill(self.trace(&parents, goal_index)))
+            }
+
+            if cost > costs[index]
+            {
+                continue
+            }
+
+            let cell = self.cell(index);
+
+            let parent = match parents[index]
+            {
+                usize::MAX => None,
+                parent => Some(self.cell(parent))
+            };
+
+            for direction in self.directions(cell, parent)
+            {
+                let Some(jump_point) = self.jump(cell, direction, goal)
+                else
+                {
+                    continue
+                };
+
+                let next_index = self.index(jump_point).expect("the jump points are in the grid");
+                let next_cost = cost + self.heuristic(cell, jump_point);
+
+                if next_cost < costs[next_index]
+                {
+                    costs[next_index] = next_cost;
+                    parents[next_index] = index;
+
+                    open.push(Open
+                    {
+                        priority: next_cost + self.heuristic(jump_point, goal),
+                        cost: next_cost,
+                        index: next_index
+                    })
+                }
+            }
+        }
+
+        None
+    }
+
+    /// the directions worth exploring from a tile, depending on where it was reached from
+    fn directions(&self, cell: IVec2, parent: Option<IVec2>) -> Vec<IVec2>
+    {
+        let Some(parent) = parent
+        else
+        {
+            return self.neighbors(cell).map(|(next, _)| next - cell).collect()
+        };
+
+        let walkable = |x: i32, y: i32| self.walkable(cell + ivec2(x, y));
+        let IVec2 { x: dx, y: dy } = (cell - parent).signum();
+
+        // the neighbors that can't be reached better without going through this tile
+        let candidates = match (dx, dy)
+        {
+            (_, 0) =>
+            {
+                let (next, up, down) = (walkable(dx, 0), walkable(0, 1), walkable(0, -1));
+
+                vec![(next, ivec2(dx, 0)), (next && up, ivec2(dx, 1)), (next && down, ivec2(dx, -1)), (up, ivec2(0, 1)), (down, ivec2(0, -1))]
+            }
+            (0, _) =>
+            {
+                let (next, right, left) = (walkable(0, dy), walkable(1, 0), walkable(-1, 0));
+
+                vec![(next, ivec2(0, dy)), (next && right, ivec2(1, dy)), (next && left, ivec2(-1, dy)), (right, ivec2(1, 0)), (left, ivec2(-1, 0))]
+            }
+            _ =>
+            {
+                let (vertical, horizontal) = (walkable(0, dy), walkable(dx, 0));
+
+                vec![(vertical, ivec2(0, dy)), (horizontal, ivec2(dx, 0)), (vertical && horizontal, ivec2(dx, dy))]
+            }
+        };
+
+        candidates
+            .into_iter()
+            .filter_map(|(open, direction)| open.then_some(direction))
+            .collect()
+    }
+
+    /// goes in the direction until a tile where the path could turn, [None] if it hits a wall first
+    fn jump(&self, from: IVec2, direction: IVec2, goal: IVec2) -> Option<IVec2>
+    {
+        let walkable = |cell: IVec2, x: i32, y: i32| self.walkable(cell + ivec2(x, y));
+        let IVec2 { x: dx, y: dy } = direction;
+
+        let mut cell = from;
+
+        loop
+        {
+            // the diagonals don't squeeze past corners
+            if dx != 0 && dy != 0 && !(walkable(cell, dx, 0) && walkable(cell, 0, dy))
+            {
+                return None
+            }
+
+            cell += direction;
+
+            if !self.walkable(cell)
+            {
+                return None
+            }
+
+            if cell == goal
+            {
+                return Some(cell)
+            }
+
+            let turns = match (dx, dy)
+            {
+                // a wall ends next to the line, the path can turn around it
+                (_, 0) => walkable(cell, 0, -1) && !walkable(cell, -dx, -1) || walkable(cell, 0, 1) && !walkable(cell, -dx, 1),
+                (0, _) => walkable(cell, -1, 0) && !walkable(cell, -1, -dy) || walkable(cell, 1, 0) && !walkable(cell, 1, -dy),
+                // a straight line from here finds something
+                _ => self.jump(cell, ivec2(dx, 0), goal).is_some() || self.jump(cell, ivec2(0, dy), goal).is_some()
+            };
+
+            if turns
+            {
+                return Some(cell)
+            }
+        }
+    }
+
+    /// the tiles between the jump points, they're always on a straight or diagonal line
+    fn fill(&self, jump_points: Vec<IVec2>) -> Vec<IVec2>
+    {
+        let mut path = Vec::with_capacity(jump_points.len());
+
+        for pair in jump_points.windows(2)
+        {
+            let (mut cell, end) = (pair[0], pair[1]);
+            let step = (end - cell).signum();
+
+            while cell != end
+            {
+                path.push(cell);
+                cell += step
+            }
+        }
+
+        path.extend(jump_points.last());
+        path
+    }
+}
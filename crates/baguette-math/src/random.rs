@@ -0,0 +1,412 @@
+use std::ops::{Bound, RangeBounds};
+
+use glam::{vec2, Vec2};
+
+use crate::collision::Aabb;
+
+/// a deterministic random number generator, the same seed always gives the same numbers.
+///
+/// its state can be saved with [Random::state] or serde and restored later,
+/// for replays, save files and multiplayer lockstep
+///
+/// # example
+///
+/// ```
+/// use baguette_math::{vec2, Aabb, Random};
+///
+/// let spawn_area = Aabb::new(vec2(0., 0.), vec2(10., 10.));
+/// let mut rng = Random::new(42);
+///
+/// let enemy = rng.weighted_choice(&[("slime", 10.), ("bat", 5.), ("dragon", 0.1)]);
+/// let position = spawn_area.center() + rng.in_circle(3.);
+///
+/// assert!(enemy.is_some());
+/// assert!(position.distance(spawn_area.center()) <= 3.);
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(serde::Serialize, serde::Deserialize)]
+pub struct Random
+{
+    state: u64
+}
+
+impl Default for Random
+{
+    /// seeded from the global generator, so it's different every time
+    fn default() -> Self
+    {
+        Self::new(fastrand::u64(..))
+    }
+}
+
+impl Random
+{
+    pub fn new(seed: u64) -> Self
+    {
+        Self { state: seed }
+    }
+
+    /// the current state, the generator continues from here when restored with [Random::set_state]
+    pub fn state(&self) -> u64
+    {
+        self.state
+    }
+
+    pub fn set_state(&mut self, state: u64)
+    {
+        self.state = state
+    }
+
+    /// a new generator seeded from this one, to give a part of the game its own sequence
+    pub fn fork(&mut self) -> Self
+    {
+        Self::new(self.u64())
+    }
+
+    /// a random number, wyrand
+    pub fn u64(&mut self) -> u64
+    {
+        self.state = self.state.wrapping_add(0xa076_1d64_78bd_642f);
+
+        let t = self.state as u128 * (self.state ^ 0xe703_7ed1_a0b4_28db) as u128;
+        (t >> 64) as u64 ^ t as u64
+    }
+
+    pub fn u32(&mut self) -> u32
+    {
+        (self.u64() >> 32) as u32
+    }
+
+    /// a number in `0..bound`, without the bias of a modulo
+    fn below(&mut self, bound: u64) -> u64
+    {
+        if bound == 0
+        {
+            return 0
+        }
+
+        // lemire's method
+        let threshold = bound.wrapping_neg() % bound;
+
+        loop
+        {
+            let t = self.u64() as u128 * bound as u128;
+
+            if t as u64 >= threshold
+            {
+                return (t >> 64) as u64
+            }
+        }
+    }
+
+    /// a number in the range, it panics if the range is empty
+    pub fn i64(&mut self, range: impl RangeBounds<i64>) -> i64
+    {
+        let start = match range.start_bound()
+        {
+            Bound::Included(start) => *start,
+            Bound::Excluded(start) => start.checked_add(1).expect("empty range"),
+            Bound::Unbounded => i64::MIN
+        };
+
+        let end = match range.end_bound()
+        {
+            Bound::Included(end) => *end,
+            Bound::Excluded(end) => end.checked_sub(1).expect("empty range"),
+            Bound::Unbounded => i64::MAX
+        };
+
+        assert!(start <= end, "empty range");
+
+        match (end.wrapping_sub(start) as u64).checked_add(1)
+        {
+            Some(span) => start.wrapping_add(self.below(span) as i64),
+            // the whole range of i64
+            None => self.u64() as i64
+        }
+    }
+
+    /// a number in the range, it panics if the range is empty
+    pub fn i32(&mut self, range: impl RangeBounds<i32>) -> i32
+    {
+        // the unbounded ends stop at the limits of i32, not of i64
+        let limit = |bound, limit: i32| match bound
+        {
+            Bound::Unbounded => Bound::Included(limit as i64),
+            bound => bound
+        };
+
+        let (start, end) = map_bounds(range, |value| *value as i64);
+
+        self.i64((limit(start, i32::MIN), limit(end, i32::MAX))) as i32
+    }
+
+    /// a number in the range, it panics if the range is empty
+    pub fn usize(&mut self, range: impl RangeBounds<usize>) -> usize
+    {
+        let start = match range.start_bound()
+        {
+            Bound::Included(start) => *start,
+            Bound::Excluded(start) => start.checked_add(1).expect("empty range"),
+            Bound::Unbounded => 0
+        };
+
+        let end = match range.end_bound()
+        {
+            Bound::Included(end) => *end,
+            Bound::Excluded(end) => end.checked_sub(1).expect("empty range"),
+            Bound::Unbounded => usize::MAX
+        };
+
+        assert!(start <= end, "empty range");
+
+        match ((end - start) as u64).checked_add(1)
+        {
+            Some(span) => start + self.below(span) as usize,
+            None => self.u64() as usize
+        }
+    }
+
+    /// a number between `0.` included and `1.` excluded
+    pub fn f32(&mut self) -> f32
+    {
+        (self.u32() >> 8) as f32 / (1u32 << 24) as f32
+    }
+
+    /// a number between `0.` included and `1.` excluded
+    pub fn f64(&mut self) -> f64
+    {
+        (self.u64() >> 11) as f64 / (1u64 << 53) as f64
+    }
+
+    /// a number between `min` and `max`
+    pub fn range(&mut self, min: f32, max: f32) -> f32
+    {
+        min + (max - min) * self.f32()
+    }
+
+    pub fn bool(&mut self) -> bool
+    {
+        self.u64() & 1 == 1
+    }
+
+    /// returns true with this probability, between `0.` and `1.`
+    pub fn chance(&mut self, probability: f32) -> bool
+    {
+        self.f32() < probability
+    }
+
+    /// a random item, [None] if there are none
+    pub fn choice<'a, T>(&mut self, items: &'a [T]) -> Option<&'a T>
+    {
+        match items.is_empty()
+        {
+            true => None,
+            false => items.get(self.usize(..items.len()))
+        }
+    }
+
+    /// a random index, picked proportionally to its weight.
+    /// [None] if there are no positive weights
+    pub fn weighted_index(&mut self, weights: impl IntoIterator<Item = f32> + Clone) -> Option<usize>
+    {
+        let total = weights.clone().into_iter().filter(|weight| *weight > 0.).sum::<f32>();
+
+        if total <= 0.
+        {
+            return None
+        }
+
+        let mut target = self.f32() * total;
+        let mut last = None;
+
+        for (index, weight) in weights.into_iter().enumerate().filter(|(_, weight)| *weight > 0.)
+        {
+            if target < weight
+            {
+                return Some(index)
+            }
+
+            target -= weight;
+            last = Some(index)
+        }
+
+        // the rounding errors can leave a bit of weight at the end
+        last
+    }
+
+    /// a random item, picked proportionally to its weight.
+    /// [None] if there are no positive weights
+    pub fn weighted_choice<'a, T>(&mut self, items: &'a [(T, f32)]) -> Option<&'a T>
+    {
+        self.weighted_index(items.iter().map(|(_, weight)| *weight)).map(|index| &items[index].0)
+    }
+
+    /// puts the items in a random order
+    pub fn shuffle<T>(&mut self, items: &mut [T])
+    {
+        // fisher yates
+        for i in (1..items.len()).rev()
+        {
+            items.swap(i, self.usize(..=i))
+        }
+    }
+
+    /// a direction of length one
+    pub fn direction(&mut self) -> Vec2
+    {
+        Vec2::from_angle(self.f32() * std::f32::consts::TAU)
+    }
+
+    /// a point on the edge of a circle centered on zero
+    pub fn on_circle(&mut self, radius: f32) -> Vec2
+    {
+        self.direction() * radius
+    }
+
+    /// a point inside a circle centered on zero, spread evenly
+    pub fn in_circle(&mut self, radius: f32) -> Vec2
+    {
+        self.direction() * radius * self.f32().sqrt()
+    }
+
+    /// a point inside the box
+    pub fn in_aabb(&mut self, aabb: &Aabb) -> Vec2
+    {
+        vec2(self.range(aabb.min.x, aabb.max.x), self.range(aabb.min.y, aabb.max.y))
+    }
+
+    /// points inside the box that are at least `min_distance` apart but still tightly packed,
+    /// looks more natural than random points for placing trees, rocks and such.
+    ///
+    /// bridson's algorithm, `attempts` is how many points are tried around each one, `30` is usual
+    pub fn poisson_disk(&mut self, area: &Aabb, min_distance: f32, attempts: u32) -> Vec<Vec2>
+    {
+        if min_distance <= 0. || area.size().min_element() < 0.
+        {
+            return vec![]
+        }
+
+        // every cell can hold at most one point
+        let cell = min_distance / std::f32::consts::SQRT_2;
+        let columns = (area.size().x / cell).ceil().max(1.) as usize;
+        let rows = (area.size().y / cell).ceil().max(1.) as usize;
+
+        let mut grid: Vec<Option<usize>> = vec![None; columns * rows];
+        let cell_of = |point: Vec2|
+        {
+            let offset = (point - area.min) / cell;
+            ((offset.x as usize).min(columns - 1), (offset.y as usize).min(rows - 1))
+        };
+
+        let mut points = vec![self.in_aabb(area)];
+        let mut active = vec![0];
+
+        let (x, y) = cell_of(points[0]);
+        grid[y * columns + x] = Some(0);
+
+        while !active.is_empty()
+        {
+            let slot = self.usize(..active.len());
+            let center = points[active[slot]];
+
+            let found = (0..attempts).find_map(|_|
+            {
+                // a point between one and two times the distance away
+                let candidate = center + self.direction() * min_distance * (1. + self.f32());
+
+                if !area.contains(candidate)
+                {
+                    return None
+                }
+
+                let (x, y) = cell_of(candidate);
+
+                let near = (y.saturating_sub(2)..(y + 3).min(rows))
+                    .flat_map(|y| (x.saturating_sub(2)..(x + 3).min(columns)).map(move |x| (x, y)))
+                    .filter_map(|(x, y)| grid[y * columns + x])
+                    .any(|other| points[other].distance_squared(candidate) < min_distance * min_distance);
+
+                match near
+                {
+                    true => None,
+                    false => Some((candidate, x, y))
+                }
+            });
+
+            match found
+            {
+                Some((point, x, y)) =>
+                {
+                    grid[y * columns + x] = Some(points.len());
+                    active.push(points.len());
+                    points.push(point)
+                }
+                None =>
+                {
+                    active.swap_remove(slot);
+                }
+            }
+        }
+
+        points
+    }
+}
+
+fn map_bounds<T, U>(range: impl RangeBounds<T>, map: impl Fn(&T) -> U) -> (Bound<U>, Bound<U>)
+{
+    let map_bound = |bound: Bound<&T>| match bound
+    {
+        Bound::Included(value) => Bound::Included(map(value)),
+        Bound::Excluded(value) => Bound::Excluded(map(value)),
+        Bound::Unbounded => Bound::Unbounded
+    };
+
+    (map_bound(range.start_bound()), map_bound(range.end_bound()))
+}
+
+#[cfg(test)]
+mod tests
+{
+    use std::ops::Bound;
+
+    use super::*;
+
+    #[test]
+    fn ranges_at_the_limits()
+    {
+        let mut rng = Random::new(7);
+
+        for _ in 0..100
+        {
+            assert_eq!(rng.i64((Bound::Excluded(i64::MAX - 1), Bound::Unbounded)), i64::MAX);
+            assert_eq!(rng.i64(..i64::MIN + 1), i64::MIN);
+            assert_eq!(rng.usize((Bound::Excluded(usize::MAX - 1), Bound::Unbounded)), usize::MAX);
+
+            assert!(rng.i32(5..) >= 5);
+            assert!(rng.i32(..-5) < -5);
+            assert_eq!(rng.i32(i32::MAX..), i32::MAX);
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "empty range")]
+    fn excluded_max_is_empty()
+    {
+        Random::new(0).i64((Bound::Excluded(i64::MAX), Bound::Unbounded));
+    }
+
+    #[test]
+    #[should_panic(expected = "empty range")]
+    fn excluded_min_is_empty()
+    {
+        Random::new(0).i64(..i64::MIN);
+    }
+
+    #[test]
+    #[should_panic(expected = "empty range")]
+    fn excluded_usize_max_is_empty()
+    {
+        Random::new(0).usize((Bound::Excluded(usize::MAX), Bound::Unbounded));
+    }
+}
@@ -0,0 +1,37 @@
+//! finds what's near something without checking everything,
+//! for the collision broad phase, range checks and culling
+//!
+//! - [SpatialHash] is a uniform grid, best when the objects have similar sizes
+//! - [AabbTree] adapts to any size and distribution, best when the objects are spread out or very different
+//!
+//! # example
+//!
+//! ```
+//! use baguette_math::{vec2, Aabb, AabbTree};
+//!
+//! let camera_view = Aabb::new(vec2(-10., -10.), vec2(10., 10.));
+//! let mut enemies = AabbTree::new();
+//!
+//! let id = enemies.insert(Aabb::from_center(vec2(20., 0.), vec2(0.5, 0.5)), "slime");
+//!
+//! // every frame
+//! enemies.update(id, Aabb::from_center(vec2(8., 0.), vec2(0.5, 0.5)));
+//!
+//! let visible: Vec<_> = enemies.query(&camera_view).map(|(_, enemy)| *enemy).collect();
+//! assert_eq!(visible, ["slime"]);
+//! ```
+
+mod grid;
+pub use grid::SpatialHash;
+
+mod tree;
+pub use tree::AabbTree;
+
+/// identifies something inserted in a [SpatialHash] or an [AabbTree],
+/// it's no longer valid once removed
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct ProxyId
+{
+    index: u32,
+    generation: u32
+}
@@ -0,0 +1,632 @@
+use std::collections::HashMap;
+
+use glam::{IVec2, Vec2};
+
+use crate::collision::{Aabb, Ray};
+use super::ProxyId;
+
+struct Entry<T>
+{
+    aabb: Aabb,
+    /// the first and last cell covered by the box
+    cells: (IVec2, IVec2),
+    data: T
+}
+
+struct Slot<T>
+{
+    generation: u32,
+    entry: Option<Entry<T>>
+}
+
+/// a uniform grid of cells, each one lists what overlaps it.
+///
+/// the cells should be about as big as the objects,
+/// an object much bigger than a cell is listed in a lot of them
+pub struct SpatialHash<T>
+{
+    cell_size: f32,
+    cells: HashMap<IVec2, Vec<u32>>,
+    slots: Vec<Slot<T>>,
+    free: Vec<u32>,
+    /// the cells occupied, limits the searches.
+    ///
+    /// it grows as the objects move and shrinks when they're removed
+    bounds: Option<(IVec2, IVec2)>,
+    len: usize
+}
+
+impl<T> SpatialHash<T>
+{
+    pub fn new(cell_size: f32) -> Self
+    {
+        Self
+        {
+            cell_size: cell_size.max(f32::EPSILON),
+            cells: HashMap::new(),
+            slots: vec![],
+            free: vec![],
+            bounds: None,
+            len: 0
+        }
+    }
+
+    pub fn cell_size(&self) -> f32
+    {
+        self.cell_size
+    }
+
+    pub fn len(&self) -> usize
+    {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool
+    {
+        self.len == 0
+    }
+
+    fn cell(&self, point: Vec2) -> IVec2
+    {
+        (point / self.cell_size).floor().as_ivec2()
+    }
+
+    fn cells_of(&self, aabb: &Aabb) -> (IVec2, IVec2)
+    {
+        (self.cell(aabb.min), self.cell(aabb.max))
+    }
+
+    fn entry(&self, id: ProxyId) -> Option<&Entry<T>>
+    {
+        self.slots
+            .get(id.index as usize)
+            .filter(|slot| slot.generation == id.generation)
+            .and_then(|slot| slot.entry.as_ref())
+    }
+
+    fn entry_mut(&mut self, id: ProxyId) -> Option<&mut Entry<T>>
+    {
+        self.slots
+            .get_mut(id.index as usize)
+            .filter(|slot| slot.generation == id.generation)
+            .and_then(|slot| slot.entry.as_mut())
+    }
+
+    fn id(&self, index: u32) -> ProxyId
+    {
+        ProxyId { index, generation: self.slots[index as usize].generation }
+    }
+
+    fn link(&mut self, index: u32, (min, max): (IVec2, IVec2))
+    {
+        for y in min.y..=max.y
+        {
+            for x in min.x..=max.x
+            {
+                self.cells.entry(IVec2::new(x, y)).or_default().push(index)
+            }
+        }
+
+        self.bounds = Some(match self.bounds
+        {
+            Some((low, high)) => (low.min(min), high.max(max)),
+            None => (min, max)
+        })
+    }
+
+    fn unlink(&mut self, index: u32, (min, max): (IVec2, IVec2))
+    {
+        for y in min.y..=max.y
+        {
+            for x in min.x..=max.x
+            {
+                let cell = IVec2::new(x, y);
+
+                let Some(indices) = self.cells.get_mut(&cell)
+                else
+                {
+                    continue
+                };
+
+                if let Some(position) = indices.iter().position(|other| *other == index)
+                {
+                    indices.swap_remove(position);
+                }
+
+                if indices.is_empty()
+                {
+                    self.cells.remove(&cell);
+                }
+            }
+        }
+    }
+
+    pub fn insert(&mut self, aabb: Aabb, data: T) -> ProxyId
+    {
+        let cells = self.cells_of(&aabb);
+        let entry = Entry { aabb, cells, data };
+
+        let index = match self.free.pop()
+        {
+            Some(index) =>
+            {
+                self.slots[index as usize].entry = Some(entry);
+                index
+            }
+            None =>
+            {
+                self.slots.push(Slot { generation: 0, entry: Some(entry) });
+                self.slots.len() as u32 - 1
+            }
+        };
+
+        self.link(index, cells);
+        self.len += 1;
+
+        self.id(index)
+    }
+
+    /// moves the box of an object, returns false if it was removed
+    pub fn update(&mut self, id: ProxyId, aabb: Aabb) -> bool
+    {
+        let cells = self.cells_of(&aabb);
+
+        let Some(entry) = self.entry_mut(id)
+        else
+        {
+            return false
+        };
+
+        let old = std::mem::replace(&mut entry.cells, cells);
+        entry.aabb = aabb;
+
+        if old != cells
+        {
+            self.unlink(id.index, old);
+            self.link(id.index, cells)
+        }
+
+        true
+    }
+
+    pub fn remove(&mut self, id: ProxyId) -> Option<T>
+    {
+        self.entry(id)?;
+
+        let slot = &mut self.slots[id.index as usize];
+        let entry = slot.entry.take().expect("the entry was checked");
+
+        slot.generation = slot.generation.wrapping_add(1);
+
+        self.unlink(id.index, entry.cells);
+        self.free.push(id.index);
+        self.len -= 1;
+
+        // the bounds only change if the object was on their border
+        if self.bounds.is_some_and(|(low, high)| entry.cells.0.cmpeq(low).any() || entry.cells.1.cmpeq(high).any())
+        {
+            self.bounds = self.slots
+                .iter()
+                .filter_map(|slot| slot.entry.as_ref().map(|entry| entry.cells))
+                .reduce(|(low, high), (min, max)| (low.min(min), high.max(max)))
+        }
+
+        Some(entry.data)
+    }
+
+    pub fn clear(&mut self)
+    {
+        for (index, slot) in self.slots.iter_mut().enumerate()
+        {
+            if slot.entry.take().is_some()
+            {
+                slot.generation = slot.generation.wrapping_add(1);
+                self.free.push(index as u32)
+            }
+        }
+
+        self.cells.clear();
+        self.bounds = None;
+        self.len = 0
+    }
+
+    pub fn get(&self, id: ProxyId) -> Option<&T>
+    {
+        self.entry(id).map(|entry| &entry.data)
+    }
+
+    pub fn get_mut(&mut self, id: ProxyId) -> Option<&mut T>
+    {
+        self.entry_mut(id).map(|entry| &mut entry.data)
+    }
+
+    pub fn aabb(&self, id: ProxyId) -> Option<Aabb>
+    {
+        self.entry(id).map(|entry| entry.aabb)
+    }
+
+    /// everything inserted, in no particular order
+    pub fn iter(&self) -> impl Iterator<Item = (ProxyId, &T)>
+    {
+        self.slots
+            .iter()
+            .enumerate()
+            .filter_map(|(index, slot)| slot.entry.as_ref().map(|entry| (self.id(index as u32), &entry.data)))
+    }
+
+    /// the ids listed in the cells covered by the region,
+    /// an object is only listed in the first of its cells that is also covered by the region
+    fn candidates(&self, (min, max): (IVec2, IVec2)) -> impl Iterator<Item = (ProxyId, &Entry<T>)>
+    {
+        (min.y..=max.y)
+            .flat_map(move |y| (min.x..=max.x).map(move |x| IVec2::new(x, y)))
+            .filter_map(|cell| self.cells.get(&cell).map(|indices| (cell, indices)))
+            .flat_map(move |(cell, indices)| indices.iter().filter_map(move |index|
+            {
+                let entry = self.slots[*index as usize].entry.as_ref()?;
+
+                match entry.cells.0.max(min) == cell
+                {
+                    true => Some((self.id(*index), entry)),
+                    false => None
+                }
+            }))
+    }
+
+    /// the objects whose box overlaps the region
+    pub fn query(&self, region: &Aabb) -> impl Iterator<Item = (ProxyId, &T)> + '_
+    {
+        let region = *region;
+
+        self.candidates(self.cells_of(&region))
+            .filter(move |(_, entry)| entry.aabb.intersects(&region))
+            .map(|(id, entry)| (id, &entry.data))
+    }
+
+    /// the objects whose box contains the point
+    pub fn query_point(&self, point: Vec2) -> impl Iterator<Item = (ProxyId, &T)> + '_
+    {
+        self.query(&Aabb::new(point, point))
+    }
+
+    /// the objects whose box is within `radius` of the center
+    pub fn query_radius(&self, center: Vec2, radius: f32) -> impl Iterator<Item = (ProxyId, &T)> + '_
+    {
+        self.candidates(self.cells_of(&Aabb::from_center(center, Vec2::splat(radius))))
+            .filter(move |(_, entry)| entry.aabb.distance_to_point(center) <= radius)
+            .map(|(id, entry)| (id, &entry.data))
+    }
+
+    /// the object whose box is the closest to the point within `max_distance`, and its distance
+    pub fn nearest(&self, point: Vec2, max_distance: f32) -> Option<(ProxyId, &T, f32)>
+    {
+        let (low, high) = self.bounds?;
+        let center = self.cell(point);
+
+        // past this ring every cell is outside of the occupied ones
+        let last_ring = (low - center).abs().max((high - center).abs()).max_element();
+        let last_ring = match max_distance.is_finite()
+        {
+            true => last_ring.min((max_distance / self.cell_size).ceil() as i32 + 1),
+            false => last_ring
+        };
+
+        let mut best: Option<(ProxyId, f32)> = None;
+
+        for ring in 0..=last_ring
+        {
+            // the cells of this ring are at least this far from the point
+            let reach = (ring - 1).max(0) as f32 * self.cell_size;
+
+            if reach > best.map_or(max_distance, |(_, distance)| distance)
+            {
+                break
+            }
+
+            // once the rings cover more cells than there are objects, checking every object is faster
+            if (2 * ring as u64 + 1).pow(2) > self.len as u64
+            {
+                best = self.slots
+                    .iter()
+                    .enumerate()
+                    .filter_map(|(index, slot)| slot.entry.as_ref().map(|entry| (index as u32, entry.aabb.distance_to_point(point))))
+                    .filter(|(_, distance)| *distance <= max_distance)
+                    .min_by(|(_, a), (_, b)| a.total_cmp(b))
+                    .map(|(index, distance)| (self.id(index), distance));
+
+                break
+            }
+
+            for cell in ring_cells(ring).map(|offset| center + offset)
+            {
+                for index in self.cells.get(&cell).into_iter().flatten()
+                {
+                    let entry = self.slots[*index as usize].entry.as_ref().expect("the cells only list entries");
+                    let distance = entry.aabb.distance_to_point(point);
+
+                    if distance <= max_distance && best.is_none_or(|(_, best)| distance < best)
+                    {
+                        best = Some((self.id(*index), distance))
+                    }
+                }
+            }
+        }
+
+        best.map(|(id, distance)| (id, &self.entry(id).expect("the id was just found").data, distance))
+    }
+
+    /// the first object whose box is hit by the ray, and the distance of the hit
+    pub fn raycast(&self, ray: Ray, max_distance: f32) -> Option<(ProxyId, &T, f32)>
+    {
+        let (id, distance) = self.raycast_with(ray, max_distance, |id, _|
+        {
+            self.aabb(id).and_then(|aabb| aabb.raycast(ray, max_distance))
+        })?;
+
+        Some((id, self.get(id)?, distance))
+    }
+
+    /// walks the cells along the ray and calls `hit` on the objects in them,
+    /// which returns the distance of the hit, for example from [crate::Shape::raycast].
+    ///
+    /// returns the closest hit
+    pub fn raycast_with(&self, ray: Ray, max_distance: f32, mut hit: impl FnMut(ProxyId, &T) -> Option<f32>) -> Option<(ProxyId, f32)>
+    {
+        let (low, high) = self.bounds?;
+
+        if ray.direction == Vec2::ZERO
+        {
+            return None
+        }
+
+        // nothing can be hit past the occupied cells
+        let occupied = Aabb::new(low.as_vec2() * self.cell_size, (high + 1).as_vec2() * self.cell_size);
+        let max_distance = max_distance.min
+        (
+            occupied.corners().iter().map(|corner| corner.distance(ray.origin)).fold(0., f32::max)
+        );
+
+        let mut cell = self.cell(ray.origin);
+        let step = IVec2::new(ray.direction.x.signum() as i32, ray.direction.y.signum() as i32);
+
+        // the distance along the ray to the next cell border on each axis, and between two borders
+        let next_border = (cell + step.max(IVec2::ZERO)).as_vec2() * self.cell_size;
+        let mut border = Vec2::select(ray.direction.cmpeq(Vec2::ZERO), Vec2::INFINITY, (next_border - ray.origin) / ray.direction);
+        let delta = Vec2::select(ray.direction.cmpeq(Vec2::ZERO), Vec2::INFINITY, Vec2::splat(self.cell_size) / ray.direction.abs());
+
+        let mut best: Option<(ProxyId, f32)> = None;
+        let mut enter = 0f32;
+
+        while enter <= best.map_or(max_distance, |(_, distance)| distance)
+        {
+            for index in self.cells.get(&cell).into_iter().flatten()
+            {
+                let id = self.id(*index);
+                let entry = self.slots[*index as usize].entry.as_ref().expect("the cells only list entries");
+
+                if let Some(distance) = hit(id, &entry.data)
+                {
+                    if distance <= max_distance && best.is_none_or(|(_, best)| distance < best)
+                    {
+                        best = Some((id, distance))
+                    }
+                }
+            }
+
+            // the hits found so far are closer than any cell left
+            let exit = border.min_element();
+
+            if best.is_some_and(|(_, distance)| distance <= exit)
+            {
+                break
+            }
+
+            match border.x < border.y
+            {
+                true =>
+                {
+                    cell.x += step.x;
+                    border.x += delta.x
+                }
+                false =>
+                {
+                    cell.y += step.y;
+                    border.y += delta.y
+                }
+            }
+
+            enter = exit
+        }
+
+        best
+    }
+
+    /// the pairs of objects whose boxes overlap, each pair is listed once
+    pub fn pairs(&self) -> Vec<(ProxyId, ProxyId)>
+    {
+        let mut pairs = vec![];
+
+        for (cell, indices) in &self.cells
+        {
+            for (i, a) in indices.iter().enumerate()
+            {
+                for b in &indices[i + 1..]
+                {
+                    let (Some(first), Some(second)) = (&self.slots[*a as usize].entry, &self.slots[*b as usize].entry)
+                    else
+                    {
+                        continue
+                    };
+
+                    // the pair is listed only in the first cell both boxes cover
+                    if first.cells.0.max(second.cells.0) == *cell && first.aabb.intersects(&second.aabb)
+                    {
+                        pairs.push((self.id(*a), self.id(*b)))
+                    }
+                }
+            }
+        }
+
+        pairs
+    }
+}
+
+/// the offsets of the cells at this distance from the center, the border of a square
+fn ring_cells(ring: i32) -> impl Iterator<Item = IVec2>
+{
+    let rows = (-ring..=ring).flat_map(move |x| [IVec2::new(x, -ring), IVec2::new(x, ring)]);
+    let columns = (1 - ring..ring).flat_map(move |y| [IVec2::new(-ring, y), IVec2::new(ring, y)]);
+
+    // the first ring is a single cell, both its top and bottom row
+    rows.chain(columns).take(match ring
+    {
+        0 => 1,
+        _ => 8 * ring as usize
+    })
+}
+
+#[cfg(test)]
+mod tests
+{
+    use glam::vec2;
+
+    use super::*;
+
+    fn square(x: f32, y: f32) -> Aabb
+    {
+        Aabb::from_center(vec2(x, y), Vec2::splat(0.5))
+    }
+
+    fn sorted<'a>(found: impl Iterator<Item = (ProxyId, &'a &'static str)>) -> Vec<&'static str>
+    {
+        let mut found: Vec<_> = found.map(|(_, name)| *name).collect();
+        found.sort();
+        found
+    }
+
+    #[test]
+    fn inserts_updates_and_removes()
+    {
+        let mut hash = SpatialHash::new(1.);
+
+        let a = hash.insert(square(0., 0.), "a");
+        let b = hash.insert(square(10., 0.), "b");
+
+        assert_eq!(hash.len(), 2);
+        assert_eq!(sorted(hash.query_point(vec2(10., 0.))), ["b"]);
+
+        assert!(hash.update(b, square(0., 0.5)));
+        assert_eq!(sorted(hash.query_point(vec2(10., 0.))), [] as [&str; 0]);
+        assert_eq!(sorted(hash.query_point(vec2(0., 0.2))), ["a", "b"]);
+        assert_eq!(hash.pairs().len(), 1);
+
+        assert_eq!(hash.remove(a), Some("a"));
+        assert_eq!(hash.remove(a), None);
+        assert!(!hash.update(a, square(0., 0.)));
+
+        // the freed slot is reused with a new id
+        let c = hash.insert(square(5., 5.), "c");
+        assert_ne!(a, c);
+        assert_eq!(hash.get(a), None);
+        assert_eq!(hash.get(c), Some(&"c"));
+        assert_eq!(hash.len(), 2);
+    }
+
+    #[test]
+    fn queries_regions_and_radii()
+    {
+        let mut hash = SpatialHash::new(2.);
+
+        hash.insert(square(0., 0.), "a");
+        hash.insert(square(3., 0.), "b");
+        hash.insert(Aabb::new(vec2(-10., -1.), vec2(10., 1.)), "wide");
+
+        assert_eq!(sorted(hash.query(&Aabb::new(vec2(2.6, -0.1), vec2(4., 0.1)))), ["b", "wide"]);
+        assert_eq!(sorted(hash.query_radius(vec2(0., 3.), 2.6)), ["a", "wide"]);
+        assert_eq!(sorted(hash.query_radius(vec2(-9., 20.), 1.)), [] as [&str; 0]);
+    }
+
+    #[test]
+    fn nearest_matches_checking_everything()
+    {
+        let mut hash = SpatialHash::new(1.);
+
+        for i in 0..50
+        {
+            let x = (i * 37 % 23) as f32 * 1.7 - 20.;
+            let y = (i * 11 % 17) as f32 * 2.3 - 15.;
+            hash.insert(square(x, y), i);
+        }
+
+        for point in [vec2(0., 0.), vec2(-19., 13.), vec2(3.3, -7.1), vec2(200., -300.)]
+        {
+            let expected = hash
+                .iter()
+                .map(|(id, _)| hash.aabb(id).unwrap().distance_to_point(point))
+                .fold(f32::INFINITY, f32::min);
+
+            let (id, _, distance) = hash.nearest(point, f32::INFINITY).unwrap();
+
+            assert_eq!(distance, expected);
+            assert_eq!(hash.aabb(id).unwrap().distance_to_point(point), expected);
+        }
+
+        assert!(hash.nearest(vec2(200., -300.), 10.).is_none());
+    }
+
+    #[test]
+    fn nearest_only_looks_at_the_border_of_each_ring()
+    {
+        assert_eq!(ring_cells(0).collect::<Vec<_>>(), [IVec2::ZERO]);
+
+        for ring in 1..5
+        {
+            let mut cells: Vec<_> = ring_cells(ring).map(|cell| (cell.x, cell.y)).collect();
+
+            assert!(cells.iter().all(|&(x, y)| x.abs().max(y.abs()) == ring));
+
+            cells.sort();
+            cells.dedup();
+            assert_eq!(cells.len(), 8 * ring as usize);
+        }
+    }
+
+    #[test]
+    fn bounds_shrink_when_the_far_objects_are_removed()
+    {
+        let mut hash = SpatialHash::new(1.);
+
+        let near = hash.insert(square(0.5, 0.5), "near");
+        let far = hash.insert(square(1000.5, 0.5), "far");
+
+        assert_eq!(hash.bounds, Some((IVec2::new(0, 0), IVec2::new(1001, 1))));
+
+        hash.remove(far);
+        assert_eq!(hash.bounds, Some((IVec2::new(0, 0), IVec2::new(1, 1))));
+        assert_eq!(hash.nearest(vec2(-5000., 0.), f32::INFINITY).map(|(id, ..)| id), Some(near));
+
+        hash.remove(near);
+        assert_eq!(hash.bounds, None);
+        assert!(hash.nearest(Vec2::ZERO, f32::INFINITY).is_none());
+    }
+
+    #[test]
+    fn raycasts_hit_the_closest_object()
+    {
+        let mut hash = SpatialHash::new(1.);
+
+        hash.insert(square(5., 0.), "near");
+        hash.insert(square(9., 0.), "far");
+        hash.insert(square(5., 3.), "above");
+
+        let (_, name, distance) = hash.raycast(Ray::new(Vec2::ZERO, Vec2::X), 100.).unwrap();
+        assert_eq!((*name, distance), ("near", 4.5));
+
+        let (_, name, _) = hash.raycast(Ray::new(vec2(20., 0.), -Vec2::X), 100.).unwrap();
+        assert_eq!(*name, "far");
+
+        assert!(hash.raycast(Ray::new(Vec2::ZERO, Vec2::X), 4.).is_none());
+        assert!(hash.raycast(Ray::new(Vec2::ZERO, -Vec2::Y), 100.).is_none());
+        assert!(hash.raycast(Ray::new(Vec2::ZERO, Vec2::ZERO), 100.).is_none());
+    }
+}
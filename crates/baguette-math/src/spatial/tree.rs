@@ -0,0 +1,744 @@
+use glam::Vec2;
+
+use crate::collision::{Aabb, Ray};
+use super::ProxyId;
+
+struct Node<T>
+{
+    /// the box containing the children, for a leaf it's the box of the object grown by the margin
+    aabb: Aabb,
+    parent: Option<u32>,
+    children: [u32; 2],
+    /// zero for the leaves, -1 for the free nodes
+    height: i32,
+    generation: u32,
+    /// the box of the object and the object, only set on the leaves
+    leaf: Option<(Aabb, T)>
+}
+
+impl<T> Node<T>
+{
+    fn is_leaf(&self) -> bool
+    {
+        self.height == 0
+    }
+}
+
+/// a bounding volume hierarchy that stays balanced as the objects move.
+///
+/// every object is stored with a box a bit bigger than its own,
+/// so it can move a little without changing the tree
+pub struct AabbTree<T>
+{
+    nodes: Vec<Node<T>>,
+    root: Option<u32>,
+    free: Vec<u32>,
+    margin: f32,
+    len: usize
+}
+
+impl<T> Default for AabbTree<T>
+{
+    fn default() -> Self
+    {
+        Self::new()
+    }
+}
+
+impl<T> AabbTree<T>
+{
+    /// how much the boxes are grown by default
+    pub const DEFAULT_MARGIN: f32 = 0.1;
+
+    pub fn new() -> Self
+    {
+        Self::with_margin(Self::DEFAULT_MARGIN)
+    }
+
+    /// a bigger margin makes the updates of moving objects cheaper, but the queries less precise
+    pub fn with_margin(margin: f32) -> Self
+    {
+        Self { nodes: vec![], root: None, free: vec![], margin: margin.max(0.), len: 0 }
+    }
+
+    pub fn len(&self) -> usize
+    {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool
+    {
+        self.len == 0
+    }
+
+    /// the height of the tree, the queries visit about this many levels
+    pub fn height(&self) -> i32
+    {
+        self.root.map_or(0, |root| self.nodes[root as usize].height)
+    }
+
+    fn node(&self, index: u32) -> &Node<T>
+    {
+        &self.nodes[index as usize]
+    }
+
+    fn node_mut(&mut self, index: u32) -> &mut Node<T>
+    {
+        &mut self.nodes[index as usize]
+    }
+
+    fn leaf(&self, id: ProxyId) -> Option<&(Aabb, T)>
+    {
+        self.nodes
+            .get(id.index as usize)
+            .filter(|node| node.generation == id.generation)
+            .and_then(|node| node.leaf.as_ref())
+    }
+
+    fn leaf_mut(&mut self, id: ProxyId) -> Option<&mut (Aabb, T)>
+    {
+        self.nodes
+            .get_mut(id.index as usize)
+            .filter(|node| node.generation == id.generation)
+            .and_then(|node| node.leaf.as_mut())
+    }
+
+    fn id(&self, index: u32) -> ProxyId
+    {
+        ProxyId { index, generation: self.node(index).generation }
+    }
+
+    fn allocate(&mut self, aabb: Aabb, height: i32, leaf: Option<(Aabb, T)>) -> u32
+    {
+        match self.free.pop()
+        {
+            Some(index) =>
+            {
+                let node = self.node_mut(index);
+
+                node.aabb = aabb;
+                node.parent = None;
+                node.height = height;
+                node.leaf = leaf;
+
+                index
+            }
+            None =>
+            {
+                self.nodes.push(Node { aabb, parent: None, children: [0; 2], height, generation: 0, leaf });
+                self.nodes.len() as u32 - 1
+            }
+        }
+    }
+
+    fn release(&mut self, index: u32) -> Option<(Aabb, T)>
+    {
+        let node = self.node_mut(index);
+
+        node.height = -1;
+        node.generation = node.generation.wrapping_add(1);
+
+        let leaf = node.leaf.take();
+        self.free.push(index);
+
+        leaf
+    }
+
+    pub fn insert(&mut self, aabb: Aabb, data: T) -> ProxyId
+    {
+        let index = self.allocate(aabb.expand(self.margin), 0, Some((aabb, data)));
+
+        self.insert_leaf(index);
+        self.len += 1;
+
+        self.id(index)
+    }
+
+    /// moves the box of an object, returns true if the tree changed because
+    /// it moved out of its grown box, false if it didn't or it was removed
+    pub fn update(&mut self, id: ProxyId, aabb: Aabb) -> bool
+    {
+        let margin = self.margin;
+
+        let Some(leaf) = self.leaf_mut(id)
+        else
+        {
+            return false
+        };
+
+        leaf.0 = aabb;
+
+        if self.node(id.index).aabb.contains_aabb(&aabb)
+        {
+            return false
+        }
+
+        self.remove_leaf(id.index);
+        self.node_mut(id.index).aabb = aabb.expand(margin);
+        self.insert_leaf(id.index);
+
+        true
+    }
+
+    pub fn remove(&mut self, id: ProxyId) -> Option<T>
+    {
+        self.leaf(id)?;
+
+        self.remove_leaf(id.index);
+        self.len -= 1;
+
+        self.release(id.index).map(|(_, data)| data)
+    }
+
+    pub fn clear(&mut self)
+    {
+        for index in 0..self.nodes.len() as u32
+        {
+            if self.node(index).height >= 0
+            {
+                self.release(index);
+            }
+        }
+
+        self.root = None;
+        self.len = 0
+    }
+
+    pub fn get(&self, id: ProxyId) -> Option<&T>
+    {
+        self.leaf(id).map(|(_, data)| data)
+    }
+
+    pub fn get_mut(&mut self, id: ProxyId) -> Option<&mut T>
+    {
+        self.leaf_mut(id).map(|(_, data)| data)
+    }
+
+    /// the box the object was inserted or updated with
+    pub fn aabb(&self, id: ProxyId) -> Option<Aabb>
+    {
+        self.leaf(id).map(|(aabb, _)| *aabb)
+    }
+
+    /// everything inserted, in no particular order
+    pub fn iter(&self) -> impl Iterator<Item = (ProxyId, &T)>
+    {
+        self.nodes
+            .iter()
+            .enumerate()
+            .filter_map(|(index, node)| node.leaf.as_ref().map(|(_, data)| (self.id(index as u32), data)))
+    }
+
+    fn insert_leaf(&mut self, leaf: u32)
+    {
+        let Some(root) = self.root
+        else
+        {
+            self.root = Some(leaf);
+            self.node_mut(leaf).parent = None;
+            return
+        };
+
+        let leaf_aabb = self.node(leaf).aabb;
+
+        // finds the sibling that grows the perimeter of the tree the least
+        let mut index = root;
+
+        while !self.node(index).is_leaf()
+        {
+            let node = self.node(index);
+            let [first, second] = node.children;
+
+            let perimeter = node.aabb.perimeter();
+            let combined = node.aabb.merge(&leaf_aabb).perimeter();
+
+            // the cost of a new parent for this node and the leaf
+            let cost = 2. * combined;
+            // the cost of pushing the leaf further down
+            let inheritance = 2. * (combined - perimeter);
+
+            let child_cost = |child: u32|
+            {
+                let child = self.node(child);
+                let merged = child.aabb.merge(&leaf_aabb).perimeter();
+
+                match child.is_leaf()
+                {
+                    true => merged + inheritance,
+                    false => merged - child.aabb.perimeter() + inheritance
+                }
+            };
+
+            let (first_cost, second_cost) = (child_cost(first), child_cost(second));
+
+            if cost < first_cost && cost < second_cost
+            {
+                break
+            }
+
+            index = match first_cost < second_cost
+            {
+                true => first,
+                false => second
+            }
+        }
+
+        let sibling = index;
+        let old_parent = self.node(sibling).parent;
+
+        let aabb = self.node(sibling).aabb.merge(&leaf_aabb);
+        let height = self.node(sibling).height + 1;
+
+        let parent = self.allocate(aabb, height, None);
+
+        self.node_mut(parent).parent = old_parent;
+        self.node_mut(parent).children = [sibling, leaf];
+        self.node_mut(sibling).parent = Some(parent);
+        self.node_mut(leaf).parent = Some(parent);
+
+        match old_parent
+        {
+            Some(old_parent) => self.replace_child(old_parent, sibling, parent),
+            None => self.root = Some(parent)
+        }
+
+        self.refit(Some(parent))
+    }
+
+    fn remove_leaf(&mut self, leaf: u32)
+    {
+        if self.root == Some(leaf)
+        {
+            self.root = None;
+            return
+        }
+
+        let parent = self.node(leaf).parent.expect("a leaf that isn't the root has a parent");
+        let grandparent = self.node(parent).parent;
+
+        let sibling = match self.node(parent).children
+        {
+            [first, second] if first == leaf => second,
+            [first, _] => first
+        };
+
+        self.node_mut(sibling).parent = grandparent;
+
+        match grandparent
+        {
+            Some(grandparent) => self.replace_child(grandparent, parent, sibling),
+            None => self.root = Some(sibling)
+        }
+
+        self.release(parent);
+        self.refit(grandparent)
+    }
+
+    fn replace_child(&mut self, parent: u32, old: u32, new: u32)
+    {
+        let children = &mut self.node_mut(parent).children;
+
+        match children[0] == old
+        {
+            true => children[0] = new,
+            false => children[1] = new
+        }
+    }
+
+    /// rebalances and recomputes the boxes and heights from the node to the root
+    fn refit(&mut self, mut index: Option<u32>)
+    {
+        while let Some(current) = index
+        {
+            let current = self.balance(current);
+            let [first, second] = self.node(current).children;
+
+            let aabb = self.node(first).aabb.merge(&self.node(second).aabb);
+            let height = 1 + self.node(first).height.max(self.node(second).height);
+
+            let node = self.node_mut(current);
+            node.aabb = aabb;
+            node.height = height;
+
+            index = node.parent
+        }
+    }
+
+    /// rotates the node if one of its children is more than one level taller than the other,
+    /// returns the node that took its place
+    fn balance(&mut self, a: u32) -> u32
+    {
+        if self.node(a).is_leaf() || self.node(a).height < 2
+        {
+            return a
+        }
+
+        let [b, c] = self.node(a).children;
+        let balance = self.node(c).height - self.node(b).height;
+
+        match balance
+        {
+            2.. => self.rotate(a, c, 1),
+            ..=-2 => self.rotate(a, b, 0),
+            _ => a
+        }
+    }
+
+    /// moves `up`, the child of `a` at `side`, in the place of `a`
+    fn rotate(&mut self, a: u32, up: u32, side: usize) -> u32
+    {
+        let other = self.node(a).children[1 - side];
+        let [f, g] = self.node(up).children;
+
+        // `up` takes the place of `a`
+        let parent = self.node(a).parent;
+
+        self.node_mut(up).children[0] = a;
+        self.node_mut(up).parent = parent;
+        self.node_mut(a).parent = Some(up);
+
+        match parent
+        {
+            Some(parent) => self.replace_child(parent, a, up),
+            None => self.root = Some(up)
+        }
+
+        // the taller grandchild stays with `up`, the other goes to `a`
+        let (kept, given) = match self.node(f).height > self.node(g).height
+        {
+            true => (f, g),
+            false => (g, f)
+        };
+
+        self.node_mut(up).children[1] = kept;
+        self.node_mut(a).children[side] = given;
+        self.node_mut(given).parent = Some(a);
+
+        let a_aabb = self.node(other).aabb.merge(&self.node(given).aabb);
+        let a_height = 1 + self.node(other).height.max(self.node(given).height);
+
+        self.node_mut(a).aabb = a_aabb;
+        self.node_mut(a).height = a_height;
+
+        let up_aabb = a_aabb.merge(&self.node(kept).aabb);
+        let up_height = 1 + a_height.max(self.node(kept).height);
+
+        self.node_mut(up).aabb = up_aabb;
+        self.node_mut(up).height = up_height;
+
+        up
+    }
+
+    /// visits the leaves whose grown box passes `test`, which also prunes the branches
+    fn visit(&self, mut test: impl FnMut(&Aabb) -> bool, mut leaf: impl FnMut(u32))
+    {
+        let mut stack = self.root.into_iter().collect::<Vec<_>>();
+
+        while let Some(index) = stack.pop()
+        {
+            let node = self.node(index);
+
+            if !test(&node.aabb)
+            {
+                continue
+            }
+
+            match node.is_leaf()
+            {
+                true => leaf(index),
+                false => stack.extend(node.children)
+            }
+        }
+    }
+
+    /// the objects whose box overlaps the region
+    pub fn query(&self, region: &Aabb) -> impl Iterator<Item = (ProxyId, &T)> + '_
+    {
+        let region = *region;
+        let mut leaves = vec![];
+
+        self.visit(|aabb| aabb.intersects(&region), |index| leaves.push(index));
+
+        leaves.into_iter().filter_map(move |index|
+        {
+            let (aabb, data) = self.node(index).leaf.as_ref()?;
+
+            match aabb.intersects(&region)
+            {
+                true => Some((self.id(index), data)),
+                false => None
+            }
+        })
+    }
+
+    /// the objects whose box contains the point
+    pub fn query_point(&self, point: Vec2) -> impl Iterator<Item = (ProxyId, &T)> + '_
+    {
+        self.query(&Aabb::new(point, point))
+    }
+
+    /// the objects whose box is within `radius` of the center
+    pub fn query_radius(&self, center: Vec2, radius: f32) -> impl Iterator<Item = (ProxyId, &T)> + '_
+    {
+        let mut leaves = vec![];
+
+        self.visit(|aabb| aabb.distance_to_point(center) <= radius, |index| leaves.push(index));
+
+        leaves.into_iter().filter_map(move |index|
+        {
+            let (aabb, data) = self.node(index).leaf.as_ref()?;
+
+            match aabb.distance_to_point(center) <= radius
+            {
+                true => Some((self.id(index), data)),
+                false => None
+            }
+        })
+    }
+
+    /// the object whose box is the closest to the point within `max_distance`, and its distance
+    pub fn nearest(&self, point: Vec2, max_distance: f32) -> Option<(ProxyId, &T, f32)>
+    {
+        let mut best: Option<(u32, f32)> = None;
+        let mut stack = self.root.into_iter().collect::<Vec<_>>();
+
+        while let Some(index) = stack.pop()
+        {
+            let node = self.node(index);
+            let limit = best.map_or(max_distance, |(_, distance)| distance);
+
+            if node.aabb.distance_to_point(point) > limit
+            {
+                continue
+            }
+
+            match &node.leaf
+            {
+                Some((aabb, _)) =>
+                {
+                    let distance = aabb.distance_to_point(point);
+
+                    if distance <= limit && best.is_none_or(|(_, best)| distance < best)
+                    {
+                        best = Some((index, distance))
+                    }
+                }
+                None =>
+                {
+                    // the closest child is visited first, so the other one is more likely to be pruned
+                    let [first, second] = node.children;
+
+                    match self.node(first).aabb.distance_to_point(point) < self.node(second).aabb.distance_to_point(point)
+                    {
+                        true => stack.extend([second, first]),
+                        false => stack.extend([first, second])
+                    }
+                }
+            }
+        }
+
+        let (index, distance) = best?;
+        let (_, data) = self.node(index).leaf.as_ref()?;
+
+        Some((self.id(index), data, distance))
+    }
+
+    /// the first object whose box is hit by the ray, and the distance of the hit
+    pub fn raycast(&self, ray: Ray, max_distance: f32) -> Option<(ProxyId, &T, f32)>
+    {
+        let (id, distance) = self.raycast_with(ray, max_distance, |id, _|
+        {
+            self.aabb(id).and_then(|aabb| aabb.raycast(ray, max_distance))
+        })?;
+
+        Some((id, self.get(id)?, distance))
+    }
+
+    /// calls `hit` on the objects whose grown box is hit by the ray,
+    /// which returns the distance of the hit, for example from [crate::Shape::raycast].
+    ///
+    /// returns the closest hit
+    pub fn raycast_with(&self, ray: Ray, max_distance: f32, mut hit: impl FnMut(ProxyId, &T) -> Option<f32>) -> Option<(ProxyId, f32)>
+    {
+        let mut best: Option<(ProxyId, f32)> = None;
+        let mut stack = self.root.into_iter().collect::<Vec<_>>();
+
+        while let Some(index) = stack.pop()
+        {
+            let node = self.node(index);
+            let limit = best.map_or(max_distance, |(_, distance)| distance);
+
+            if node.aabb.raycast(ray, limit).is_none()
+            {
+                continue
+            }
+
+            match &node.leaf
+            {
+                Some((_, data)) =>
+                {
+                    let id = self.id(index);
+
+                    if let Some(distance) = hit(id, data).filter(|distance| *distance <= limit)
+                    {
+                        if best.is_none_or(|(_, best)| distance < best)
+                        {
+                            best = Some((id, distance))
+                        }
+                    }
+                }
+                None => stack.extend(node.children)
+            }
+        }
+
+        best
+    }
+
+    /// the pairs of objects whose boxes overlap, each pair is listed once
+    pub fn pairs(&self) -> Vec<(ProxyId, ProxyId)>
+    {
+        let mut pairs = vec![];
+
+        for (index, node) in self.nodes.iter().enumerate()
+        {
+            let Some((aabb, _)) = &node.leaf
+            else
+            {
+                continue
+            };
+
+            self.visit(|other| other.intersects(aabb), |other|
+            {
+                let other_aabb = self.node(other).leaf.as_ref().map(|(aabb, _)| aabb);
+
+                if other > index as u32 && other_aabb.is_some_and(|other| other.intersects(aabb))
+                {
+                    pairs.push((self.id(index as u32), self.id(other)))
+                }
+            });
+        }
+
+        pairs
+    }
+}
+
+#[cfg(test)]
+mod tests
+{
+    use glam::vec2;
+
+    use super::*;
+
+    fn square(x: f32, y: f32) -> Aabb
+    {
+        Aabb::from_center(vec2(x, y), Vec2::splat(0.5))
+    }
+
+    fn sorted<'a>(found: impl Iterator<Item = (ProxyId, &'a &'static str)>) -> Vec<&'static str>
+    {
+        let mut found: Vec<_> = found.map(|(_, name)| *name).collect();
+        found.sort();
+        found
+    }
+
+    #[test]
+    fn inserts_updates_and_removes()
+    {
+        let mut tree = AabbTree::new();
+
+        let a = tree.insert(square(0., 0.), "a");
+        let b = tree.insert(square(10., 0.), "b");
+
+        assert_eq!(tree.len(), 2);
+        assert_eq!(sorted(tree.query_point(vec2(10., 0.))), ["b"]);
+
+        // a move within the margin keeps the tree as it is
+        assert!(!tree.update(b, square(10.05, 0.)));
+        assert_eq!(tree.aabb(b), Some(square(10.05, 0.)));
+
+        assert!(tree.update(b, square(0., 0.5)));
+        assert_eq!(sorted(tree.query_point(vec2(10., 0.))), [] as [&str; 0]);
+        assert_eq!(sorted(tree.query_point(vec2(0., 0.2))), ["a", "b"]);
+        assert_eq!(tree.pairs().len(), 1);
+
+        assert_eq!(tree.remove(a), Some("a"));
+        assert_eq!(tree.remove(a), None);
+        assert!(!tree.update(a, square(0., 0.)));
+        assert_eq!(tree.get(a), None);
+        assert_eq!(tree.len(), 1);
+    }
+
+    #[test]
+    fn queries_use_the_real_boxes()
+    {
+        let mut tree = AabbTree::with_margin(1.);
+
+        tree.insert(square(0., 0.), "a");
+        tree.insert(square(3., 0.), "b");
+
+        // inside the grown box of "a", but not its own
+        assert_eq!(sorted(tree.query_point(vec2(1., 0.))), [] as [&str; 0]);
+        assert_eq!(sorted(tree.query(&Aabb::new(vec2(0.4, -0.1), vec2(2.6, 0.1)))), ["a", "b"]);
+        assert_eq!(sorted(tree.query_radius(vec2(0., 2.), 1.6)), ["a"]);
+    }
+
+    #[test]
+    fn stays_balanced()
+    {
+        let mut tree = AabbTree::new();
+
+        for i in 0..256
+        {
+            tree.insert(square(i as f32 * 2., 0.), i);
+        }
+
+        assert!(tree.height() <= 16, "height {}", tree.height());
+    }
+
+    #[test]
+    fn nearest_matches_checking_everything()
+    {
+        let mut tree = AabbTree::new();
+
+        for i in 0..50
+        {
+            let x = (i * 37 % 23) as f32 * 1.7 - 20.;
+            let y = (i * 11 % 17) as f32 * 2.3 - 15.;
+            tree.insert(square(x, y), i);
+        }
+
+        for point in [vec2(0., 0.), vec2(-19., 13.), vec2(3.3, -7.1), vec2(200., -300.)]
+        {
+            let expected = tree
+                .iter()
+                .map(|(id, _)| tree.aabb(id).unwrap().distance_to_point(point))
+                .fold(f32::INFINITY, f32::min);
+
+            let (_, _, distance) = tree.nearest(point, f32::INFINITY).unwrap();
+
+            assert_eq!(distance, expected);
+        }
+
+        assert!(tree.nearest(vec2(200., -300.), 10.).is_none());
+        assert!(AabbTree::<()>::new().nearest(Vec2::ZERO, f32::INFINITY).is_none());
+    }
+
+    #[test]
+    fn raycasts_hit_the_closest_object()
+    {
+        let mut tree = AabbTree::new();
+
+        tree.insert(square(5., 0.), "near");
+        tree.insert(square(9., 0.), "far");
+        tree.insert(square(5., 3.), "above");
+
+        let (_, name, distance) = tree.raycast(Ray::new(Vec2::ZERO, Vec2::X), 100.).unwrap();
+        assert_eq!((*name, distance), ("near", 4.5));
+
+        let (_, name, _) = tree.raycast(Ray::new(vec2(20., 0.), -Vec2::X), 100.).unwrap();
+        assert_eq!(*name, "far");
+
+        assert!(tree.raycast(Ray::new(Vec2::ZERO, Vec2::X), 4.).is_none());
+        assert!(tree.raycast(Ray::new(Vec2::ZERO, -Vec2::Y), 100.).is_none());
+    }
+}
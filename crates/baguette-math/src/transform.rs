@@ -0,0 +1,316 @@
+use glam::{Affine2, Mat4, Quat, Vec2, Vec3};
+
+/// the position, rotation and scale of something in 2d,
+/// `z` only sorts what's drawn and is added when composing transforms
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[derive(serde::Serialize, serde::Deserialize)]
+pub struct Transform2D
+{
+    pub translation: Vec2,
+    /// counterclockwise, in radians
+    pub rotation: f32,
+    pub scale: Vec2,
+    pub z: f32
+}
+
+impl Default for Transform2D
+{
+    fn default() -> Self
+    {
+        Self::IDENTITY
+    }
+}
+
+impl Transform2D
+{
+    /// doesn't move, rotate or scale anything
+    pub const IDENTITY: Self = Self { translation: Vec2::ZERO, rotation: 0., scale: Vec2::ONE, z: 0. };
+
+    pub fn new(translation: Vec2, rotation: f32, scale: Vec2) -> Self
+    {
+        Self { translation, rotation, scale, z: 0. }
+    }
+
+    pub fn from_translation(translation: Vec2) -> Self
+    {
+        Self { translation, ..Self::IDENTITY }
+    }
+
+    pub fn from_rotation(rotation: f32) -> Self
+    {
+        Self { rotation, ..Self::IDENTITY }
+    }
+
+    pub fn from_scale(scale: Vec2) -> Self
+    {
+        Self { scale, ..Self::IDENTITY }
+    }
+
+    pub fn with_translation(mut self, translation: Vec2) -> Self
+    {
+        self.translation = translation;
+        self
+    }
+
+    pub fn with_rotation(mut self, rotation: f32) -> Self
+    {
+        self.rotation = rotation;
+        self
+    }
+
+    pub fn with_scale(mut self, scale: Vec2) -> Self
+    {
+        self.scale = scale;
+        self
+    }
+
+    pub fn with_z(mut self, z: f32) -> Self
+    {
+        self.z = z;
+        self
+    }
+
+    /// the unit vector the transform is rotated towards
+    pub fn right(&self) -> Vec2
+    {
+        Vec2::from_angle(self.rotation)
+    }
+
+    /// rotates, scales and moves a point
+    pub fn transform_point(&self, point: Vec2) -> Vec2
+    {
+        self.translation + self.transform_vector(point)
+    }
+
+    /// rotates and scales a direction, the translation is ignored
+    pub fn transform_vector(&self, vector: Vec2) -> Vec2
+    {
+        Vec2::from_angle(self.rotation).rotate(vector * self.scale)
+    }
+
+    /// places `child`, relative to this transform, in the space this transform is in.
+    ///
+    /// a rotated parent with a non uniform scale would skew the child,
+    /// which a [Transform2D] can't represent, so the scale is applied to the child without skewing
+    pub fn mul_transform(&self, child: &Self) -> Self
+    {
+        Self
+        {
+            translation: self.transform_point(child.translation),
+            rotation: self.rotation + child.rotation,
+            scale: self.scale * child.scale,
+            z: self.z + child.z
+        }
+    }
+
+    /// the transform that undoes this one, exact when the scale is uniform.
+    /// a zero scale is left at zero
+    pub fn inverse(&self) -> Self
+    {
+        let scale = Vec2::select(self.scale.cmpeq(Vec2::ZERO), Vec2::ZERO, self.scale.recip());
+
+        Self
+        {
+            translation: -(Vec2::from_angle(-self.rotation).rotate(self.translation) * scale),
+            rotation: -self.rotation,
+            scale,
+            z: -self.z
+        }
+    }
+
+    /// interpolates every component, `t` is usually between `0.` and `1.`
+    pub fn lerp(&self, other: &Self, t: f32) -> Self
+    {
+        Self
+        {
+            translation: self.translation.lerp(other.translation, t),
+            rotation: self.rotation + (other.rotation - self.rotation) * t,
+            scale: self.scale.lerp(other.scale, t),
+            z: self.z + (other.z - self.z) * t
+        }
+    }
+
+    pub fn to_affine2(&self) -> Affine2
+    {
+        Affine2::from_scale_angle_translation(self.scale, self.rotation, self.translation)
+    }
+
+    /// the matrix used to render, `z` is the translation on the z axis
+    pub fn to_mat4(&self) -> Mat4
+    {
+        Mat4::from_scale_rotation_translation
+        (
+            self.scale.extend(1.), self.to_quat(), self.translation.extend(self.z)
+        )
+    }
+
+    /// the rotation around the z axis
+    pub fn to_quat(&self) -> Quat
+    {
+        Quat::from_rotation_z(self.rotation)
+    }
+
+    /// the translation with `z` as the third component
+    pub fn translation3(&self) -> Vec3
+    {
+        self.translation.extend(self.z)
+    }
+}
+
+impl std::ops::Mul for Transform2D
+{
+    type Output = Self;
+
+    fn mul(self, child: Self) -> Self
+    {
+        self.mul_transform(&child)
+    }
+}
+
+impl std::ops::Mul<Vec2> for Transform2D
+{
+    type Output = Vec2;
+
+    fn mul(self, point: Vec2) -> Vec2
+    {
+        self.transform_point(point)
+    }
+}
+
+impl From<Transform2D> for Mat4
+{
+    fn from(transform: Transform2D) -> Self
+    {
+        transform.to_mat4()
+    }
+}
+
+impl From<Transform2D> for Affine2
+{
+    fn from(transform: Transform2D) -> Self
+    {
+        transform.to_affine2()
+    }
+}
+
+/// something placed by a [Transform2D], like a sprite instance,
+/// lets other systems move it without knowing what it is
+pub trait Transformable
+{
+    fn transform(&self) -> Transform2D;
+
+    fn set_transform(&mut self, transform: Transform2D);
+}
+
+impl Transformable for Transform2D
+{
+    fn transform(&self) -> Transform2D
+    {
+        *self
+    }
+
+    fn set_transform(&mut self, transform: Transform2D)
+    {
+        *self = transform
+    }
+}
+
+#[cfg(test)]
+mod tests
+{
+    use std::f32::consts::FRAC_PI_2;
+
+    use glam::vec2;
+
+    use super::*;
+
+    fn assert_close(a: Vec2, b: Vec2)
+    {
+        assert!(a.abs_diff_eq(b, 1e-5), "{a} != {b}")
+    }
+
+    #[test]
+    fn points_are_scaled_then_rotated_then_moved()
+    {
+        let transform = Transform2D::new(vec2(10., 0.), FRAC_PI_2, vec2(2., 2.));
+
+        assert_close(transform.transform_point(vec2(1., 0.)), vec2(10., 2.));
+        assert_close(transform.transform_vector(vec2(1., 0.)), vec2(0., 2.));
+        assert_close(transform * Vec2::ZERO, vec2(10., 0.));
+    }
+
+    #[test]
+    fn composing_places_the_child_in_the_parent()
+    {
+        let parent = Transform2D::new(vec2(5., 0.), FRAC_PI_2, Vec2::splat(2.)).with_z(1.);
+        let child = Transform2D::new(vec2(1., 0.), FRAC_PI_2, Vec2::splat(0.5)).with_z(2.);
+
+        let world = parent * child;
+
+        assert_close(world.translation, vec2(5., 2.));
+        assert!((world.rotation - 2. * FRAC_PI_2).abs() < 1e-6);
+        assert_eq!(world.scale, Vec2::ONE);
+        assert_eq!(world.z, 3.);
+
+        // a point of the child lands where the parent would put the child's point
+        let point = vec2(0.3, -0.7);
+        assert_close(world * point, parent * (child * point));
+    }
+
+    #[test]
+    fn composing_with_the_identity_changes_nothing()
+    {
+        let transform = Transform2D::new(vec2(1., 2.), 0.3, vec2(2., 3.));
+
+        assert_eq!(Transform2D::IDENTITY * transform, transform);
+        assert_eq!(transform * Transform2D::IDENTITY, transform);
+    }
+
+    #[test]
+    fn inverse_round_trips_with_a_uniform_scale()
+    {
+        let transform = Transform2D::new(vec2(3., -4.), 0.7, Vec2::splat(2.5)).with_z(4.);
+        let inverse = transform.inverse();
+
+        for point in [Vec2::ZERO, vec2(1., 0.), vec2(-3., 8.)]
+        {
+            assert_close(inverse * (transform * point), point);
+            assert_close(transform * (inverse * point), point);
+        }
+
+        let identity = transform * inverse;
+
+        assert_close(identity.translation, Vec2::ZERO);
+        assert!(identity.rotation.abs() < 1e-6);
+        assert_close(identity.scale, Vec2::ONE);
+        assert_eq!(identity.z, 0.);
+    }
+
+    #[test]
+    fn inverse_round_trips_without_rotation()
+    {
+        // a non uniform scale is only exact while nothing is rotated
+        let transform = Transform2D::new(vec2(3., -4.), 0., vec2(2., 0.5));
+
+        assert_close(transform.inverse() * (transform * vec2(7., 1.)), vec2(7., 1.));
+    }
+
+    #[test]
+    fn inverse_of_a_zero_scale_stays_finite()
+    {
+        let inverse = Transform2D::new(vec2(1., 1.), 0., vec2(0., 2.)).inverse();
+
+        assert_eq!(inverse.scale, vec2(0., 0.5));
+        assert!(inverse.translation.is_finite());
+    }
+
+    #[test]
+    fn matrices_match_the_transform()
+    {
+        let transform = Transform2D::new(vec2(3., -4.), 0.7, vec2(2., 0.5)).with_z(1.);
+        let point = vec2(1.5, -2.);
+
+        assert_close(transform.to_affine2().transform_point2(point), transform * point);
+        assert!(transform.to_mat4().transform_point3(point.extend(0.)).abs_diff_eq((transform * point).extend(1.), 1e-5));
+    }
+}
@@ -0,0 +1,726 @@
+use std::time::Duration;
+
+use glam::{Quat, Vec2, Vec3, Vec4};
+
+use crate::{Color, Ease, Transform2D};
+
+/// a value that can be animated by a [Tween]
+pub trait Tweenable: Copy
+{
+    /// the value between `self` and `to`, `t` can go a bit past `0.` and `1.` with some curves
+    fn interpolate(&self, to: &Self, t: f32) -> Self;
+}
+
+impl Tweenable for f32
+{
+    fn interpolate(&self, to: &Self, t: f32) -> Self
+    {
+        self + (to - self) * t
+    }
+}
+
+impl Tweenable for Vec2
+{
+    fn interpolate(&self, to: &Self, t: f32) -> Self
+    {
+        self.lerp(*to, t)
+    }
+}
+
+impl Tweenable for Vec3
+{
+    fn interpolate(&self, to: &Self, t: f32) -> Self
+    {
+        self.lerp(*to, t)
+    }
+}
+
+impl Tweenable for Vec4
+{
+    fn interpolate(&self, to: &Self, t: f32) -> Self
+    {
+        self.lerp(*to, t)
+    }
+}
+
+/// colors as rgba
+impl Tweenable for [f32; 4]
+{
+    fn interpolate(&self, to: &Self, t: f32) -> Self
+    {
+        Vec4::from(*self).lerp(Vec4::from(*to), t).into()
+    }
+}
+
+/// blends in linear space, see [Color::lerp]
+impl Tweenable for Color
+{
+    fn interpolate(&self, to: &Self, t: f32) -> Self
+    {
+        self.lerp(*to, t)
+    }
+}
+
+impl Tweenable for Quat
+{
+    fn interpolate(&self, to: &Self, t: f32) -> Self
+    {
+        self.slerp(*to, t)
+    }
+}
+
+impl Tweenable for Transform2D
+{
+    fn interpolate(&self, to: &Self, t: f32) -> Self
+    {
+        self.lerp(to, t)
+    }
+}
+
+/// how many times a [Tween] plays after the first time
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum Repeat
+{
+    #[default]
+    Never,
+    Times(u32),
+    Forever
+}
+
+/// something that animates a value over time, like a [Tween] or a [Sequence]
+pub trait Animation<T>
+{
+    /// advances the animation and returns the new value
+    fn update(&mut self, delta: Duration) -> T;
+
+    fn value(&self) -> T;
+
+    fn is_finished(&self) -> bool;
+
+    /// starts the animation over
+    fn reset(&mut self);
+}
+
+/// animates a value from `from` to `to`, following an [Ease] curve
+///
+/// # example
+///
+/// ```
+/// use std::time::Duration;
+/// use baguette_math::{vec3, Animation, Ease, Tween, Vec3};
+///
+/// let mut zoom = Tween::new(Vec3::ZERO, vec3(0., 0., -5.), Duration::from_secs(1))
+///     .ease(Ease::CubicInOut)
+///     .yoyo()
+///     .repeat(1);
+///
+/// // every frame
+/// let position = zoom.update(Duration::from_millis(500));
+/// assert_eq!(position, vec3(0., 0., -2.5));
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct Tween<T: Tweenable>
+{
+    from: T,
+    to: T,
+    duration: Duration,
+    ease: Ease,
+    delay: Duration,
+    repeat: Repeat,
+    /// plays backwards every other time
+    yoyo: bool,
+    elapsed: Duration
+}
+
+impl<T: Tweenable> Tween<T>
+{
+    pub fn new(from: T, to: T, duration: Duration) -> Self
+    {
+        Self
+        {
+            from,
+            to,
+            duration,
+            ease: Ease::Linear,
+            delay: Duration::ZERO,
+            repeat: Repeat::Never,
+            yoyo: false,
+            elapsed: Duration::ZERO
+        }
+    }
+
+    pub fn ease(mut self, ease: Ease) -> Self
+    {
+        self.ease = ease;
+        self
+    }
+
+    /// waits before starting, only the first time
+    pub fn delay(mut self, delay: Duration) -> Self
+    {
+        self.delay = delay;
+        self
+    }
+
+    /// plays again `times` more times
+    pub fn repeat(mut self, times: u32) -> Self
+    {
+        self.repeat = Repeat::Times(times);
+        self
+    }
+
+    pub fn repeat_forever(mut self) -> Self
+    {
+        self.repeat = Repeat::Forever;
+        self
+    }
+
+    /// plays backwards every other time, it needs a repeat to come back
+    pub fn yoyo(mut self) -> Self
+    {
+        self.yoyo = true;
+        self
+    }
+
+    /// plays `next` once this tween is over
+    pub fn then(self, next: Tween<T>) -> Sequence<T>
+    {
+        Sequence::new(self).then(next)
+    }
+
+    pub fn from(&self) -> T
+    {
+        self.from
+    }
+
+    pub fn to(&self) -> T
+    {
+        self.to
+    }
+
+    pub fn duration(&self) -> Duration
+    {
+        self.duration
+    }
+
+    /// how long the tween plays for including the delay and the repeats,
+    /// [None] if it repeats forever. it saturates at [Duration::MAX]
+    pub fn total_duration(&self) -> Option<Duration>
+    {
+        let repeats = match self.repeat
+        {
+            Repeat::Never => Duration::ZERO,
+            Repeat::Times(times) => self.duration.checked_mul(times).unwrap_or(Duration::MAX),
+            Repeat::Forever => return None
+        };
+
+        Some(self.delay.saturating_add(self.duration).saturating_add(repeats))
+    }
+
+    pub fn elapsed(&self) -> Duration
+    {
+        self.elapsed
+    }
+
+    /// jumps to this time from the start, including the delay
+    pub fn seek(&mut self, elapsed: Duration)
+    {
+        self.elapsed = match self.total_duration()
+        {
+            Some(total) => elapsed.min(total),
+            None => elapsed
+        }
+    }
+
+    /// the index of the current play and the progress in it, before easing
+    fn progress(&self) -> (u64, f32)
+    {
+        let elapsed = self.elapsed.saturating_sub(self.delay);
+
+        if self.total_duration().is_some_and(|total| self.elapsed >= total)
+        {
+            let last = match self.repeat
+            {
+                Repeat::Times(times) => times as u64,
+                _ => 0
+            };
+
+            return (last, 1.)
+        }
+
+        if self.duration.is_zero()
+        {
+            return (0, 1.)
+        }
+
+        let cycle = self.duration.as_nanos();
+        let elapsed = elapsed.as_nanos();
+
+        ((elapsed / cycle) as u64, (elapsed % cycle) as f32 / cycle as f32)
+    }
+}
+
+impl<T: Tweenable> Animation<T> for Tween<T>
+{
+    fn update(&mut self, delta: Duration) -> T
+    {
+        self.seek(self.elapsed.saturating_add(delta));
+        self.value()
+    }
+
+    fn value(&self) -> T
+    {
+        let (play, progress) = self.progress();
+
+        let progress = match self.yoyo && play % 2 == 1
+        {
+            true => 1. - progress,
+            false => progress
+        };
+
+        self.from.interpolate(&self.to, self.ease.apply(progress))
+    }
+
+    fn is_finished(&self) -> bool
+    {
+        self.total_duration().is_some_and(|total| self.elapsed >= total)
+    }
+
+    fn reset(&mut self)
+    {
+        self.elapsed = Duration::ZERO
+    }
+}
+
+enum Step<T: Tweenable>
+{
+    Tween(Tween<T>),
+    /// holds the last value
+    Wait(Duration, Duration)
+}
+
+/// tweens played one after the other
+pub struct Sequence<T: Tweenable>
+{
+    steps: Vec<Step<T>>,
+    current: usize,
+    repeat: Repeat,
+    /// how many times the sequence restarted
+    plays: u32,
+    value: T
+}
+
+impl<T: Tweenable> Sequence<T>
+{
+    pub fn new(first: Tween<T>) -> Self
+    {
+        Self { value: first.value(), steps: vec![Step::Tween(first)], current: 0, repeat: Repeat::Never, plays: 0 }
+    }
+
+    pub fn then(mut self, tween: Tween<T>) -> Self
+    {
+        self.steps.push(Step::Tween(tween));
+        self
+    }
+
+    /// holds the value for a while before the next tween
+    pub fn then_wait(mut self, duration: Duration) -> Self
+    {
+        self.steps.push(Step::Wait(duration, Duration::ZERO));
+        self
+    }
+
+    /// plays the whole sequence again `times` more times
+    pub fn repeat(mut self, times: u32) -> Self
+    {
+        self.repeat = Repeat::Times(times);
+        self
+    }
+
+    pub fn repeat_forever(mut self) -> Self
+    {
+        self.repeat = Repeat::Forever;
+        self
+    }
+
+    /// restarts every step, without counting the plays
+    fn restart(&mut self)
+    {
+        for step in &mut self.steps
+        {
+            match step
+            {
+                Step::Tween(tween) => tween.reset(),
+                Step::Wait(_, elapsed) => *elapsed = Duration::ZERO
+            }
+        }
+
+        self.current = 0
+    }
+}
+
+impl<T: Tweenable> Animation<T> for Sequence<T>
+{
+    fn update(&mut self, mut delta: Duration) -> T
+    {
+        let mut play_start = delta;
+
+        while !self.is_finished()
+        {
+            let left = match &mut self.steps[self.current]
+            {
+                Step::Tween(tween) =>
+                {
+                    let before = tween.elapsed();
+                    self.value = tween.update(delta);
+
+                    match tween.total_duration()
+                    {
+                        Some(total) => delta.saturating_sub(total - before),
+                        // a tween repeating forever never lets the sequence go on
+                        None => Duration::ZERO
+                    }
+                }
+                Step::Wait(duration, elapsed) =>
+                {
+                    let left = delta.saturating_sub(*duration - *elapsed);
+                    *elapsed = elapsed.saturating_add(delta).min(*duration);
+                    left
+                }
+            };
+
+            let step_over = match &self.steps[self.current]
+            {
+                Step::Tween(tween) => tween.is_finished(),
+                Step::Wait(duration, elapsed) => elapsed >= duration
+            };
+
+            if !step_over
+            {
+                break
+            }
+
+            self.current += 1;
+
+            if self.current == self.steps.len()
+            {
+                let again = match self.repeat
+                {
+                    Repeat::Never => false,
+                    Repeat::Times(times) => self.plays < times,
+                    Repeat::Forever => true
+                };
+
+                if !again
+                {
+                    break
+                }
+
+                self.plays += 1;
+                self.restart();
+
+                // a sequence that takes no time would loop forever
+                if left == play_start
+                {
+                    break
+                }
+
+                play_start = left
+            }
+
+            delta = left
+        }
+
+        self.value
+    }
+
+    fn value(&self) -> T
+    {
+        self.value
+    }
+
+    fn is_finished(&self) -> bool
+    {
+        self.current >= self.steps.len()
+    }
+
+    fn reset(&mut self)
+    {
+        self.restart();
+        self.plays = 0;
+
+        if let Some(Step::Tween(tween)) = self.steps.first()
+        {
+            self.value = tween.value()
+        }
+    }
+}
+
+/// advances an animation and applies its value to the target, returns true once it's finished
+type Track<Target> = Box<dyn FnMut(&mut Target, Duration) -> bool>;
+
+/// drives fields of a target with animations, the animations are
+/// dropped once they're finished.
+///
+/// # example
+///
+/// ```
+/// use std::time::Duration;
+/// use baguette_math::{Animator, Ease, Tween, Vec3};
+///
+/// struct Sprite { scale: Vec3 }
+///
+/// struct Camera { position: Vec3 }
+///
+/// impl Camera
+/// {
+///     fn set_position(&mut self, position: Vec3) { self.position = position }
+/// }
+///
+/// let mut sprite = Sprite { scale: Vec3::ONE };
+/// let mut animator = Animator::new();
+///
+/// animator.add
+/// (
+///     Tween::new(Vec3::ONE, Vec3::splat(1.5), Duration::from_millis(150)).ease(Ease::BackOut).yoyo().repeat(1),
+///     |sprite: &mut Sprite, scale| sprite.scale = scale
+/// );
+///
+/// // every frame
+/// animator.update(Duration::from_millis(16), &mut sprite);
+///
+/// // methods taking the value work too
+/// let mut camera = Camera { position: Vec3::ZERO };
+/// let mut camera_animator = Animator::new();
+///
+/// camera_animator.add(Tween::new(camera.position, Vec3::Z, Duration::from_secs(2)), Camera::set_position);
+/// camera_animator.update(Duration::from_secs(1), &mut camera);
+///
+/// assert_eq!(camera.position, Vec3::new(0., 0., 0.5));
+/// ```
+pub struct Animator<Target>
+{
+    tracks: Vec<Track<Target>>
+}
+
+impl<Target> Default for Animator<Target>
+{
+    fn default() -> Self
+    {
+        Self { tracks: vec![] }
+    }
+}
+
+impl<Target> Animator<Target>
+{
+    pub fn new() -> Self
+    {
+        Self::default()
+    }
+
+    /// plays the animation and sets its value with `set` on every update
+    pub fn add<T, A>(&mut self, mut animation: A, mut set: impl FnMut(&mut Target, T) + 'static)
+    where
+        A: Animation<T> + 'static
+    {
+        self.tracks.push(Box::new(move |target, delta|
+        {
+            set(target, animation.update(delta));
+            animation.is_finished()
+        }))
+    }
+
+    /// advances the animations and applies their values to the target
+    pub fn update(&mut self, delta: Duration, target: &mut Target)
+    {
+        self.tracks.retain_mut(|track| !track(target, delta))
+    }
+
+    /// returns true if there's nothing left to play
+    pub fn is_finished(&self) -> bool
+    {
+        self.tracks.is_empty()
+    }
+
+    /// stops every animation where it is
+    pub fn clear(&mut self)
+    {
+        self.tracks.clear()
+    }
+}
+
+#[cfg(test)]
+mod tests
+{
+    use super::*;
+
+    fn ms(millis: u64) -> Duration
+    {
+        Duration::from_millis(millis)
+    }
+
+    fn tween() -> Tween<f32>
+    {
+        Tween::new(0., 10., ms(1000))
+    }
+
+    #[test]
+    fn plays_once_by_default()
+    {
+        let mut tween = tween();
+
+        assert_eq!(tween.update(ms(250)), 2.5);
+        assert!(!tween.is_finished());
+
+        assert_eq!(tween.update(ms(5000)), 10.);
+        assert!(tween.is_finished());
+        assert_eq!(tween.elapsed(), ms(1000));
+
+        tween.reset();
+        assert_eq!(tween.value(), 0.);
+    }
+
+    #[test]
+    fn waits_for_the_delay_only_once()
+    {
+        let mut tween = tween().delay(ms(500)).repeat(1);
+
+        assert_eq!(tween.total_duration(), Some(ms(2500)));
+        assert_eq!(tween.update(ms(250)), 0.);
+        assert_eq!(tween.update(ms(750)), 5.);
+        assert_eq!(tween.update(ms(1000)), 5.);
+    }
+
+    #[test]
+    fn repeats()
+    {
+        let mut tween = tween().repeat(2);
+
+        assert_eq!(tween.total_duration(), Some(ms(3000)));
+
+        tween.seek(ms(1500));
+        assert_eq!(tween.value(), 5.);
+
+        tween.seek(ms(2999));
+        assert!(!tween.is_finished());
+
+        tween.seek(ms(3000));
+        assert!(tween.is_finished());
+        assert_eq!(tween.value(), 10.);
+    }
+
+    #[test]
+    fn yoyo_plays_back_every_other_time()
+    {
+        let mut tween = tween().yoyo().repeat(2);
+
+        tween.seek(ms(1250));
+        assert_eq!(tween.value(), 7.5);
+
+        tween.seek(ms(2250));
+        assert_eq!(tween.value(), 2.5);
+
+        // it ends where the last play ends
+        tween.seek(ms(3000));
+        assert_eq!(tween.value(), 10.);
+
+        let mut back = self::tween().yoyo().repeat(1);
+        back.seek(ms(2000));
+        assert_eq!(back.value(), 0.);
+    }
+
+    #[test]
+    fn seeking_stops_at_the_end()
+    {
+        let mut tween = tween().repeat(1);
+
+        tween.seek(ms(10_000));
+        assert_eq!(tween.elapsed(), ms(2000));
+
+        tween.seek(ms(500));
+        assert_eq!(tween.value(), 5.);
+    }
+
+    #[test]
+    fn repeating_forever_never_ends()
+    {
+        let mut tween = tween().repeat_forever();
+
+        assert_eq!(tween.total_duration(), None);
+
+        tween.seek(Duration::from_secs(100) + ms(250));
+        assert_eq!(tween.value(), 2.5);
+        assert!(!tween.is_finished());
+    }
+
+    #[test]
+    fn total_duration_saturates()
+    {
+        assert_eq!(tween().repeat(u32::MAX).total_duration(), Some(Duration::from_secs(1 << 32)));
+        assert_eq!(Tween::new(0., 1., Duration::MAX).repeat(u32::MAX).total_duration(), Some(Duration::MAX));
+        assert_eq!(Tween::new(0., 1., Duration::MAX).delay(ms(1)).total_duration(), Some(Duration::MAX));
+
+        let mut tween = Tween::new(0., 1., Duration::MAX / 2).repeat(3);
+        tween.update(Duration::MAX);
+        tween.update(Duration::MAX);
+
+        assert!(tween.is_finished());
+        assert_eq!(tween.value(), 1.);
+    }
+
+    #[test]
+    fn an_instant_tween_is_finished_right_away()
+    {
+        let mut tween = Tween::new(0., 10., Duration::ZERO);
+
+        assert_eq!(tween.update(Duration::ZERO), 10.);
+        assert!(tween.is_finished());
+    }
+
+    #[test]
+    fn sequences_play_their_steps_in_order()
+    {
+        let mut sequence = Sequence::new(tween()).then_wait(ms(500)).then(Tween::new(10., 0., ms(1000)));
+
+        assert_eq!(sequence.update(ms(500)), 5.);
+        assert_eq!(sequence.update(ms(750)), 10.);
+        assert_eq!(sequence.update(ms(750)), 5.);
+        assert!(!sequence.is_finished());
+
+        assert_eq!(sequence.update(ms(5000)), 0.);
+        assert!(sequence.is_finished());
+
+        sequence.reset();
+        assert_eq!(sequence.value(), 0.);
+        assert!(!sequence.is_finished());
+    }
+
+    #[test]
+    fn sequences_carry_the_time_left_into_the_next_play()
+    {
+        let mut sequence = Sequence::new(tween()).then_wait(ms(500)).then(Tween::new(10., 0., ms(1000))).repeat(1);
+
+        assert_eq!(sequence.update(ms(3000)), 5.);
+        assert!(!sequence.is_finished());
+
+        assert_eq!(sequence.update(ms(10_000)), 0.);
+        assert!(sequence.is_finished());
+    }
+
+    #[test]
+    fn sequences_wait_for_a_tween_that_repeats_forever()
+    {
+        let mut sequence = tween().repeat_forever().then(Tween::new(10., 0., ms(1000)));
+
+        assert_eq!(sequence.update(ms(10_250)), 2.5);
+        assert!(!sequence.is_finished());
+    }
+
+    #[test]
+    fn an_empty_looping_sequence_doesnt_hang()
+    {
+        let mut sequence = Sequence::new(Tween::new(0., 1., Duration::ZERO)).repeat_forever();
+
+        assert_eq!(sequence.update(ms(16)), 1.);
+    }
+}
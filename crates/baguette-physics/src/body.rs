@@ -0,0 +1,468 @@
+use std::f32::consts::PI;
+
+use baguette_math::{Shape, Transform2D, Vec2};
+
+/// identifies a body in a [PhysicsWorld](crate::PhysicsWorld), it's no longer valid once removed
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct BodyHandle
+{
+    pub(crate) index: u32,
+    pub(crate) generation: u32
+}
+
+/// how a body moves
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum BodyKind
+{
+    /// never moves, for the ground and the walls
+    Static,
+    /// moves only by its velocity and pushes the dynamic bodies without being pushed back,
+    /// for moving platforms and doors
+    Kinematic,
+    /// moved by gravity, forces and collisions
+    #[default]
+    Dynamic
+}
+
+/// a rigid body with a single collision shape, built and then added to a [PhysicsWorld](crate::PhysicsWorld)
+///
+/// # example
+///
+/// ```
+/// use baguette_math::{vec2, Aabb, Vec2};
+/// use baguette_physics::{Body, PhysicsWorld};
+///
+/// let mut world = PhysicsWorld::new();
+///
+/// let crate_body = world.add
+/// (
+///     Body::dynamic(Aabb::from_center(Vec2::ZERO, vec2(0.5, 0.5)))
+///         .with_position(vec2(2., 5.))
+///         .with_friction(0.8)
+/// );
+///
+/// assert_eq!(world.get(crate_body).unwrap().position(), vec2(2., 5.));
+/// ```
+#[derive(Debug, Clone)]
+pub struct Body
+{
+    pub(crate) kind: BodyKind,
+    /// relative to the position of the body
+    pub(crate) shape: Shape,
+    /// the shape placed in the world, updated after every step
+    pub(crate) world_shape: Shape,
+
+    pub(crate) position: Vec2,
+    pub(crate) rotation: f32,
+    pub(crate) velocity: Vec2,
+    pub(crate) angular_velocity: f32,
+
+    pub(crate) force: Vec2,
+    pub(crate) torque: f32,
+
+    pub(crate) restitution: f32,
+    pub(crate) friction: f32,
+    pub(crate) density: f32,
+    pub(crate) gravity_scale: f32,
+    pub(crate) linear_damping: f32,
+    pub(crate) angular_damping: f32,
+
+    pub(crate) sensor: bool,
+    pub(crate) fixed_rotation: bool,
+    /// the layers the body is on
+    pub(crate) layers: u32,
+    /// the layers the body collides with
+    pub(crate) mask: u32,
+
+    /// the center of mass relative to the position
+    pub(crate) local_center: Vec2,
+    pub(crate) inverse_mass: f32,
+    pub(crate) inverse_inertia: f32,
+
+    /// where the body was before the last step, to smooth out the movement between steps
+    pub(crate) previous: (Vec2, f32)
+}
+
+impl Body
+{
+    pub fn new(kind: BodyKind, shape: impl Into<Shape>) -> Self
+    {
+        let shape = shape.into();
+
+        let mut body = Self
+        {
+            kind,
+            world_shape: shape.clone(),
+            shape,
+
+            position: Vec2::ZERO,
+            rotation: 0.,
+            velocity: Vec2::ZERO,
+            angular_velocity: 0.,
+
+            force: Vec2::ZERO,
+            torque: 0.,
+
+            restitution: 0.,
+            friction: 0.5,
+            density: 1.,
+            gravity_scale: 1.,
+            linear_damping: 0.,
+            angular_damping: 0.,
+
+            sensor: false,
+            fixed_rotation: false,
+            layers: 1,
+            mask: u32::MAX,
+
+            local_center: Vec2::ZERO,
+            inverse_mass: 0.,
+            inverse_inertia: 0.,
+
+            previous: (Vec2::ZERO, 0.)
+        };
+
+        body.update_mass();
+        body
+    }
+
+    /// a [BodyKind::Static] body
+    pub fn fixed(shape: impl Into<Shape>) -> Self
+    {
+        Self::new(BodyKind::Static, shape)
+    }
+
+    pub fn kinematic(shape: impl Into<Shape>) -> Self
+    {
+        Self::new(BodyKind::Kinematic, shape)
+    }
+
+    pub fn dynamic(shape: impl Into<Shape>) -> Self
+    {
+        Self::new(BodyKind::Dynamic, shape)
+    }
+
+    pub fn with_position(mut self, position: Vec2) -> Self
+    {
+        self.set_position(position);
+        self
+    }
+
+    /// counterclockwise, in radians
+    pub fn with_rotation(mut self, rotation: f32) -> Self
+    {
+        self.set_rotation(rotation);
+        self
+    }
+
+    pub fn with_velocity(mut self, velocity: Vec2) -> Self
+    {
+        self.velocity = velocity;
+        self
+    }
+
+    pub fn with_angular_velocity(mut self, angular_velocity: f32) -> Self
+    {
+        self.angular_velocity = angular_velocity;
+        self
+    }
+
+    /// how bouncy the body is, `0.` doesn't bounce and `1.` bounces back at the same speed
+    pub fn with_restitution(mut self, restitution: f32) -> Self
+    {
+        self.restitution = restitution.max(0.);
+        self
+    }
+
+    /// how much the body resists sliding, `0.` is ice
+    pub fn with_friction(mut self, friction: f32) -> Self
+    {
+        self.friction = friction.max(0.);
+        self
+    }
+
+    /// the mass for each unit of area, `1.` by default
+    pub fn with_density(mut self, density: f32) -> Self
+    {
+        self.density = density.max(0.);
+        self.update_mass();
+        self
+    }
+
+    /// how much gravity pulls the body, `0.` makes it float
+    pub fn with_gravity_scale(mut self, gravity_scale: f32) -> Self
+    {
+        self.gravity_scale = gravity_scale;
+        self
+    }
+
+    /// slows the body down over time, like air resistance
+    pub fn with_damping(mut self, linear: f32, angular: f32) -> Self
+    {
+        self.linear_damping = linear.max(0.);
+        self.angular_damping = angular.max(0.);
+        self
+    }
+
+    /// keeps the body from rotating, what platformer characters usually want
+    pub fn fixed_rotation(mut self) -> Self
+    {
+        self.fixed_rotation = true;
+        self.update_mass();
+        self
+    }
+
+    /// the body only detects what enters it without colliding,
+    /// for triggers, pickups and damage zones
+    pub fn sensor(mut self) -> Self
+    {
+        self.sensor = true;
+        self
+    }
+
+    /// `layers` are the bits of the layers the body is on and `mask` those it collides with,
+    /// two bodies collide only if each one is on a layer the other collides with
+    pub fn with_layers(mut self, layers: u32, mask: u32) -> Self
+    {
+        self.layers = layers;
+        self.mask = mask;
+        self
+    }
+
+    pub fn kind(&self) -> BodyKind
+    {
+        self.kind
+    }
+
+    pub fn shape(&self) -> &Shape
+    {
+        &self.shape
+    }
+
+    /// the shape where the body is
+    pub fn world_shape(&self) -> &Shape
+    {
+        &self.world_shape
+    }
+
+    pub fn position(&self) -> Vec2
+    {
+        self.position
+    }
+
+    /// teleports the body
+    pub fn set_position(&mut self, position: Vec2)
+    {
+        self.position = position;
+        self.previous.0 = position;
+        self.update_world_shape()
+    }
+
+    pub fn rotation(&self) -> f32
+    {
+        self.rotation
+    }
+
+    pub fn set_rotation(&mut self, rotation: f32)
+    {
+        self.rotation = rotation;
+        self.previous.1 = rotation;
+        self.update_world_shape()
+    }
+
+    /// the position and rotation of the body
+    pub fn transform(&self) -> Transform2D
+    {
+        Transform2D::new(self.position, self.rotation, Vec2::ONE)
+    }
+
+    /// the center of mass in the world
+    pub fn center_of_mass(&self) -> Vec2
+    {
+        self.position + Vec2::from_angle(self.rotation).rotate(self.local_center)
+    }
+
+    /// the velocity of the center of mass
+    pub fn velocity(&self) -> Vec2
+    {
+        self.velocity
+    }
+
+    pub fn set_velocity(&mut self, velocity: Vec2)
+    {
+        if self.kind != BodyKind::Static
+        {
+            self.velocity = velocity
+        }
+    }
+
+    /// counterclockwise, in radians per second
+    pub fn angular_velocity(&self) -> f32
+    {
+        self.angular_velocity
+    }
+
+    pub fn set_angular_velocity(&mut self, angular_velocity: f32)
+    {
+        if self.kind != BodyKind::Static && !self.fixed_rotation
+        {
+            self.angular_velocity = angular_velocity
+        }
+    }
+
+    /// the velocity of a point of the body, in the world
+    pub fn velocity_at(&self, point: Vec2) -> Vec2
+    {
+        self.velocity + (point - self.center_of_mass()).perp() * self.angular_velocity
+    }
+
+    /// zero for static and kinematic bodies
+    pub fn mass(&self) -> f32
+    {
+        match self.inverse_mass > 0.
+        {
+            true => 1. / self.inverse_mass,
+            false => 0.
+        }
+    }
+
+    pub fn is_sensor(&self) -> bool
+    {
+        self.sensor
+    }
+
+    pub fn restitution(&self) -> f32
+    {
+        self.restitution
+    }
+
+    pub fn friction(&self) -> f32
+    {
+        self.friction
+    }
+
+    /// pushes the center of mass during the next step, the forces are cleared after it
+    pub fn apply_force(&mut self, force: Vec2)
+    {
+        self.force += force
+    }
+
+    /// pushes a point of the body during the next step, which also makes it spin
+    pub fn apply_force_at(&mut self, force: Vec2, point: Vec2)
+    {
+        self.force += force;
+        self.torque += (point - self.center_of_mass()).perp_dot(force)
+    }
+
+    pub fn apply_torque(&mut self, torque: f32)
+    {
+        self.torque += torque
+    }
+
+    /// changes the velocity right away, for jumps and explosions
+    pub fn apply_impulse(&mut self, impulse: Vec2)
+    {
+        self.velocity += impulse * self.inverse_mass
+    }
+
+    /// changes the velocity of a point of the body right away
+    pub fn apply_impulse_at(&mut self, impulse: Vec2, point: Vec2)
+    {
+        self.velocity += impulse * self.inverse_mass;
+        self.angular_velocity += (point - self.center_of_mass()).perp_dot(impulse) * self.inverse_inertia
+    }
+
+    /// returns true if the bodies can touch, depending on their kinds and layers
+    pub(crate) fn interacts_with(&self, other: &Body) -> bool
+    {
+        let layers = self.layers & other.mask != 0 && other.layers & self.mask != 0;
+
+        let kinds = match (self.kind, other.kind)
+        {
+            (BodyKind::Static, BodyKind::Static) => false,
+            (BodyKind::Dynamic, _) | (_, BodyKind::Dynamic) => true,
+            // a sensor can still notice a moving platform
+            _ => self.sensor || other.sensor
+        };
+
+        layers && kinds
+    }
+
+    pub(crate) fn update_world_shape(&mut self)
+    {
+        self.world_shape = self.shape.transformed(&self.transform())
+    }
+
+    fn update_mass(&mut self)
+    {
+        let (area, inertia) = mass_properties(&self.shape);
+
+        self.local_center = self.shape.center();
+
+        let mass = area * self.density;
+
+        self.inverse_mass = match self.kind == BodyKind::Dynamic && mass > 0.
+        {
+            true => 1. / mass,
+            false => 0.
+        };
+
+        self.inverse_inertia = match self.kind == BodyKind::Dynamic && !self.fixed_rotation && inertia > 0.
+        {
+            true => 1. / (inertia * self.density),
+            false => 0.
+        };
+
+        if self.inverse_inertia == 0.
+        {
+            self.angular_velocity = 0.
+        }
+    }
+}
+
+/// the area and the moment of inertia around the center of a shape with a density of one
+fn mass_properties(shape: &Shape) -> (f32, f32)
+{
+    let rectangle = |size: Vec2|
+    {
+        let area = size.x * size.y;
+        (area, area * size.length_squared() / 12.)
+    };
+
+    match shape
+    {
+        Shape::Aabb(aabb) => rectangle(aabb.size()),
+        Shape::Obb(obb) => rectangle(obb.half_extents * 2.),
+        Shape::Circle(circle) =>
+        {
+            let area = PI * circle.radius * circle.radius;
+            (area, area * circle.radius * circle.radius * 0.5)
+        }
+        Shape::Capsule(capsule) =>
+        {
+            let length = capsule.a.distance(capsule.b);
+            let radius = capsule.radius;
+
+            let (box_area, box_inertia) = rectangle(baguette_math::vec2(length, radius * 2.));
+
+            // the two half circles, moved to the ends
+            let circle_area = PI * radius * radius;
+            let circle_inertia = circle_area * (radius * radius * 0.5 + length * length * 0.25);
+
+            (box_area + circle_area, box_inertia + circle_inertia)
+        }
+        Shape::Polygon(polygon) =>
+        {
+            let points = polygon.points();
+            let center = shape.center();
+
+            points.iter().zip(points.iter().cycle().skip(1)).fold((0., 0.), |(area, inertia), (a, b)|
+            {
+                let (a, b) = (*a - center, *b - center);
+                let cross = a.perp_dot(b);
+
+                (area + cross * 0.5, inertia + cross * (a.dot(a) + a.dot(b) + b.dot(b)) / 12.)
+            })
+        }
+    }
+}
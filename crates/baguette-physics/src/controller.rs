@@ -0,0 +1,771 @@
+use std::time::Duration;
+
+use baguette_math::{vec2, Aabb, Shape, ShapeHit, Vec2};
+
+use crate::{BodyKind, PhysicsWorld, TileCollision, TileShape};
+
+/// the gap kept between the character and what it stands on, so that it doesn't start
+/// the next move touching it
+const SKIN: f32 = 0.01;
+/// how many times a move can be deflected before stopping
+const MAX_SLIDES: usize = 4;
+/// how long the one-way platforms are ignored after [CharacterController::drop_through]
+const DROP_THROUGH_TIME: Duration = Duration::from_millis(250);
+
+/// something a [CharacterController] collides with
+#[derive(Debug, Clone)]
+pub struct Obstacle
+{
+    /// in the world
+    pub shape: Shape,
+    /// only its top is solid, when coming from above
+    pub one_way: bool
+}
+
+/// what a [CharacterController] moves through
+pub trait Obstacles
+{
+    /// adds the obstacles that can touch the region
+    fn obstacles(&self, region: &Aabb, found: &mut Vec<Obstacle>);
+}
+
+impl Obstacles for TileCollision
+{
+    fn obstacles(&self, region: &Aabb, found: &mut Vec<Obstacle>)
+    {
+        found.extend(self.query_aabb(region).map(|(_, tile, shape)| Obstacle
+        {
+            shape,
+            one_way: tile == TileShape::OneWay
+        }))
+    }
+}
+
+/// the static and kinematic bodies, the sensors and the dynamic bodies are ignored
+impl Obstacles for PhysicsWorld
+{
+    fn obstacles(&self, region: &Aabb, found: &mut Vec<Obstacle>)
+    {
+        let bodies = self
+            .query_aabb(*region)
+            .filter_map(|handle| self.get(handle))
+            .filter(|body| !body.is_sensor() && body.kind() != BodyKind::Dynamic);
+
+        found.extend(bodies.map(|body| Obstacle { shape: body.world_shape().clone(), one_way: false }))
+    }
+}
+
+impl<T: Obstacles + ?Sized> Obstacles for &T
+{
+    fn obstacles(&self, region: &Aabb, found: &mut Vec<Obstacle>)
+    {
+        (**self).obstacles(region, found)
+    }
+}
+
+/// collides with both, like the tiles of a level and its moving platforms
+impl<A: Obstacles, B: Obstacles> Obstacles for (A, B)
+{
+    fn obstacles(&self, region: &Aabb, found: &mut Vec<Obstacle>)
+    {
+        self.0.obstacles(region, found);
+        self.1.obstacles(region, found)
+    }
+}
+
+/// moves a character by its velocity and slides it along what it hits, without being pushed around
+/// like a dynamic body. it walks on slopes, climbs steps, lands on one-way platforms
+/// and knows when it's on the ground, against a wall or under a ceiling.
+///
+/// the up direction is `+y`, apply the gravity to the velocity before every move
+///
+/// # example
+///
+/// ```
+/// use std::time::Duration;
+/// use baguette_math::{vec2, Aabb, Vec2};
+/// use baguette_physics::{CharacterController, TileCollision};
+///
+/// let tiles = TileCollision::from_tiles((-5..=5).map(|x| vec2(x as f32, 0.)));
+///
+/// let mut player = CharacterController::new(Aabb::from_center(Vec2::ZERO, vec2(0.4, 0.9)))
+///     .with_position(vec2(0., 2.))
+///     .with_step_height(0.3);
+///
+/// // read from the input
+/// let (walk, jump_pressed) = (1., false);
+/// let delta = Duration::from_millis(16);
+///
+/// // every frame
+/// for _ in 0..30
+/// {
+///     player.velocity.x = walk * 6.;
+///     player.velocity.y -= 30. * delta.as_secs_f32();
+///
+///     if jump_pressed
+///     {
+///         player.jump(12.);
+///     }
+///
+///     player.move_and_slide(delta, &tiles);
+/// }
+///
+/// assert!(player.is_on_ground());
+/// assert!(player.position().x > 2.);
+/// ```
+#[derive(Debug, Clone)]
+pub struct CharacterController
+{
+    /// relative to the position
+    shape: Shape,
+    position: Vec2,
+    /// in units per second, changed by the collisions
+    pub velocity: Vec2,
+
+    /// the cosine of the steepest slope walked on
+    max_slope: f32,
+    step_height: f32,
+    snap_distance: f32,
+    coyote_time: Duration,
+
+    on_ground: bool,
+    on_wall: bool,
+    on_ceiling: bool,
+    ground_normal: Vec2,
+    wall_normal: Vec2,
+    collisions: Vec<ShapeHit>,
+
+    /// since the character left the ground
+    airborne: Duration,
+    /// the jump allowed by the coyote time was used
+    jumped: bool,
+    /// left before the one-way platforms collide again
+    drop_through: Duration
+}
+
+impl CharacterController
+{
+    pub fn new(shape: impl Into<Shape>) -> Self
+    {
+        Self
+        {
+            shape: shape.into(),
+            position: Vec2::ZERO,
+            velocity: Vec2::ZERO,
+
+            max_slope: 50f32.to_radians().cos(),
+            step_height: 0.,
+            snap_distance: 0.2,
+            coyote_time: Duration::from_millis(100),
+
+            on_ground: false,
+            on_wall: false,
+            on_ceiling: false,
+            ground_normal: Vec2::Y,
+            wall_normal: Vec2::ZERO,
+            collisions: vec![],
+
+            airborne: Duration::MAX,
+            jumped: false,
+            drop_through: Duration::ZERO
+        }
+    }
+
+    pub fn with_position(mut self, position: Vec2) -> Self
+    {
+        self.position = position;
+        self
+    }
+
+    /// the steepest slope the character can stand on, in radians, `50°` by default.
+    /// the steeper ones are walls and it slides down them.
+    ///
+    /// it's kept between `0°` and `89°`, a vertical wall can't be walked on
+    pub fn with_max_slope(mut self, max_slope: f32) -> Self
+    {
+        self.max_slope = max_slope.clamp(0., 89f32.to_radians()).cos();
+        self
+    }
+
+    /// how high a step the character climbs without jumping, none by default
+    pub fn with_step_height(mut self, step_height: f32) -> Self
+    {
+        self.step_height = step_height.max(0.);
+        self
+    }
+
+    /// how far down the character sticks to the ground when it walks off a step or down a slope,
+    /// instead of flying off it, `0.2` by default
+    pub fn with_snap_distance(mut self, snap_distance: f32) -> Self
+    {
+        self.snap_distance = snap_distance.max(0.);
+        self
+    }
+
+    /// how long the character can still jump after walking off a ledge, `100ms` by default
+    pub fn with_coyote_time(mut self, coyote_time: Duration) -> Self
+    {
+        self.coyote_time = coyote_time;
+        self
+    }
+
+    pub fn shape(&self) -> &Shape
+    {
+        &self.shape
+    }
+
+    /// the shape where the character is
+    pub fn world_shape(&self) -> Shape
+    {
+        self.shape.translated(self.position)
+    }
+
+    pub fn position(&self) -> Vec2
+    {
+        self.position
+    }
+
+    /// teleports the character
+    pub fn set_position(&mut self, position: Vec2)
+    {
+        self.position = position
+    }
+
+    pub fn is_on_ground(&self) -> bool
+    {
+        self.on_ground
+    }
+
+    pub fn is_on_wall(&self) -> bool
+    {
+        self.on_wall
+    }
+
+    pub fn is_on_ceiling(&self) -> bool
+    {
+        self.on_ceiling
+    }
+
+    /// the normal of the ground under the character, [None] if it's in the air
+    pub fn ground_normal(&self) -> Option<Vec2>
+    {
+        self.on_ground.then_some(self.ground_normal)
+    }
+
+    /// the normal of the wall the character hit, pointing away from it, for wall jumps
+    pub fn wall_normal(&self) -> Option<Vec2>
+    {
+        self.on_wall.then_some(self.wall_normal)
+    }
+
+    /// where the character hit something during the last move
+    pub fn collisions(&self) -> &[ShapeHit]
+    {
+        &self.collisions
+    }
+
+    /// returns true if the character is on the ground or just left it
+    pub fn can_jump(&self) -> bool
+    {
+        !self.jumped && (self.on_ground || self.airborne <= self.coyote_time)
+    }
+
+    /// sets the vertical speed if the character [can jump](Self::can_jump), returns true if it jumped
+    pub fn jump(&mut self, speed: f32) -> bool
+    {
+        if !self.can_jump()
+        {
+            return false
+        }
+
+        self.velocity.y = speed;
+        self.jumped = true;
+
+        true
+    }
+
+    /// falls through the one-way platforms for a moment
+    pub fn drop_through(&mut self)
+    {
+        self.drop_through = DROP_THROUGH_TIME
+    }
+
+    /// moves the character by its velocity during `delta` and slides it along what it hits,
+    /// the velocity loses what went into the obstacles
+    pub fn move_and_slide(&mut self, delta: Duration, obstacles: &impl Obstacles)
+    {
+        let was_on_ground = self.on_ground;
+
+        self.on_ground = false;
+        self.on_wall = false;
+        self.on_ceiling = false;
+        self.collisions.clear();
+        self.drop_through = self.drop_through.saturating_sub(delta);
+
+        let motion = self.velocity * delta.as_secs_f32();
+
+        let mut found = vec![];
+        let start = self.world_shape().aabb();
+        let region = start.merge(&start.translate(motion)).expand(self.step_height + self.snap_distance + SKIN * 2.);
+
+        obstacles.obstacles(&region, &mut found);
+
+        self.depenetrate(&found);
+
+        let mut remaining = motion;
+
+        for _ in 0..MAX_SLIDES
+        {
+            if remaining.length_squared() <= f32::EPSILON * f32::EPSILON
+            {
+                break
+            }
+
+            let Some(hit) = self.sweep(self.position, remaining, &found)
+            else
+            {
+                self.position += remaining;
+                break
+            };
+
+            // pushing away from a steep slope along its normal would lift the character up it a bit every frame
+            let skin = match self.is_ground(hit.normal) || hit.normal.y <= 0. || remaining.y > 0.
+            {
+                true => hit.normal * SKIN,
+                false => vec2(hit.normal.x.signum() * SKIN, 0.)
+            };
+
+            self.position += remaining * hit.time + skin;
+            self.collisions.push(hit);
+
+            let rest = remaining * (1. - hit.time);
+
+            if self.is_ground(hit.normal)
+            {
+                self.on_ground = true;
+                self.ground_normal = hit.normal;
+
+                // keeps the horizontal speed along the slope and doesn't slide down it
+                remaining = vec2(rest.x, -hit.normal.x * rest.x / hit.normal.y);
+                self.velocity.y = self.velocity.y.max(0.);
+            }
+            else if hit.normal.y <= -self.max_slope
+            {
+                self.on_ceiling = true;
+
+                remaining = slide(rest, hit.normal);
+                self.velocity = slide(self.velocity, hit.normal);
+            }
+            else
+            {
+                let stepped = match was_on_ground || self.on_ground
+                {
+                    true => self.step_up(rest, &found),
+                    false => None
+                };
+
+                if let Some(stepped) = stepped
+                {
+                    self.position = stepped;
+                    self.on_ground = true;
+                    break
+                }
+
+                self.on_wall = true;
+                self.wall_normal = hit.normal;
+
+                let falling = rest.y <= 0.;
+
+                remaining = slide(rest, hit.normal);
+                self.velocity = slide(self.velocity, hit.normal);
+
+                // a steep slope can't be walked up
+                if falling
+                {
+                    remaining.y = remaining.y.min(0.);
+                    self.velocity.y = self.velocity.y.min(0.);
+                }
+            }
+        }
+
+        if !self.on_ground && self.velocity.y <= 0.
+        {
+            self.snap_to_ground(was_on_ground, &found)
+        }
+
+        match self.on_ground
+        {
+            true =>
+            {
+                self.airborne = Duration::ZERO;
+                self.jumped = false;
+            }
+            false => self.airborne = self.airborne.saturating_add(delta)
+        }
+    }
+
+    fn is_ground(&self, normal: Vec2) -> bool
+    {
+        normal.y >= self.max_slope
+    }
+
+    /// the first obstacle hit when moving from `from`, ignoring those the character moves away from
+    fn sweep(&self, from: Vec2, motion: Vec2, found: &[Obstacle]) -> Option<ShapeHit>
+    {
+        self.hits(from, motion, found).min_by(|a, b| a.time.total_cmp(&b.time))
+    }
+
+    /// the first ground hit when moving from `from`, a steep slope touching the side
+    /// of the character doesn't hide the ground under it
+    fn sweep_ground(&self, from: Vec2, motion: Vec2, found: &[Obstacle]) -> Option<ShapeHit>
+    {
+        self.hits(from, motion, found)
+            .filter(|hit| self.is_ground(hit.normal))
+            .min_by(|a, b| a.time.total_cmp(&b.time))
+    }
+
+    /// every obstacle hit when moving from `from`, except those the character moves away from
+    fn hits<'a>(&'a self, from: Vec2, motion: Vec2, found: &'a [Obstacle]) -> impl Iterator<Item = ShapeHit> + 'a
+    {
+        let shape = self.shape.translated(from);
+        let feet = shape.aabb().min.y;
+
+        found
+            .iter()
+            .filter(move |obstacle| !obstacle.one_way || self.drop_through.is_zero() && motion.y < 0. && feet >= obstacle.shape.aabb().max.y - SKIN)
+            .filter_map(move |obstacle| shape.cast(motion, &obstacle.shape).filter(|hit| !obstacle.one_way || self.is_ground(hit.normal)))
+            .filter(move |hit| motion.dot(hit.normal) < 0.)
+    }
+
+    /// pushes the character out of what it overlaps, like a platform that moved into it
+    fn depenetrate(&mut self, found: &[Obstacle])
+    {
+        for obstacle in found.iter().filter(|obstacle| !obstacle.one_way)
+        {
+            if let Some(contact) = self.world_shape().contact(&obstacle.shape)
+            {
+                self.position -= contact.normal * contact.depth().max(0.)
+            }
+        }
+    }
+
+    /// where the character ends up if it climbs over what's in front of it, [None] if it's too high
+    fn step_up(&self, rest: Vec2, found: &[Obstacle]) -> Option<Vec2>
+    {
+        let forward = vec2(rest.x, 0.);
+
+        if self.step_height <= 0. || forward.x.abs() <= SKIN * 0.1
+        {
+            return None
+        }
+
+        let up = Vec2::Y * self.step_height;
+
+        let raised = match self.sweep(self.position, up, found)
+        {
+            Some(hit) => self.position + up * hit.time + hit.normal * SKIN,
+            None => self.position + up
+        };
+
+        let moved = match self.sweep(raised, forward, found)
+        {
+            Some(hit) => raised + forward * hit.time + hit.normal * SKIN,
+            None => raised + forward
+        };
+
+        if (moved.x - raised.x).abs() <= SKIN
+        {
+            return None
+        }
+
+        let down = Vec2::NEG_Y * (raised.y - self.position.y + SKIN * 2.);
+        let hit = self.sweep(moved, down, found).filter(|hit| self.is_ground(hit.normal))?;
+
+        Some(moved + down * hit.time + Vec2::Y * SKIN)
+    }
+
+    /// finds the ground right under the character, and pulls it down to it
+    /// if it was on the ground before this move
+    fn snap_to_ground(&mut self, was_on_ground: bool, found: &[Obstacle])
+    {
+        let distance = match was_on_ground
+        {
+            true => self.snap_distance.max(SKIN * 2.),
+            false => SKIN * 2.
+        };
+
+        let down = Vec2::NEG_Y * distance;
+
+        let Some(hit) = self.sweep_ground(self.position, down, found)
+        else
+        {
+            return
+        };
+
+        self.position += down * hit.time + Vec2::Y * SKIN;
+        self.on_ground = true;
+        self.ground_normal = hit.normal;
+        self.velocity.y = 0.;
+    }
+}
+
+/// removes the part of the vector going into the surface
+fn slide(vector: Vec2, normal: Vec2) -> Vec2
+{
+    let into = vector.dot(normal);
+
+    match into < 0.
+    {
+        true => vector - normal * into,
+        false => vector
+    }
+}
+
+#[cfg(test)]
+mod tests
+{
+    use baguette_math::Transform2D;
+
+    use super::*;
+
+    const STEP: Duration = Duration::from_millis(16);
+    const GRAVITY: f32 = 30.;
+    /// the height of the center of the player standing on the tiles at `y = 0`
+    const STANDING: f32 = 0.5 + 0.9;
+
+    fn player(position: Vec2) -> CharacterController
+    {
+        CharacterController::new(Aabb::from_center(Vec2::ZERO, vec2(0.4, 0.9))).with_position(position)
+    }
+
+    fn floor(tiles: &mut TileCollision, from: i32, to: i32, y: i32, shape: TileShape)
+    {
+        for x in from..=to
+        {
+            tiles.insert(vec2(x as f32, y as f32), shape);
+        }
+    }
+
+    /// walks for a number of frames, returns the ground normals of every frame
+    fn walk(player: &mut CharacterController, tiles: &TileCollision, speed: f32, frames: u32) -> Vec<Option<Vec2>>
+    {
+        (0..frames).map(|_|
+        {
+            player.velocity.x = speed;
+            player.velocity.y -= GRAVITY * STEP.as_secs_f32();
+            player.move_and_slide(STEP, tiles);
+
+            player.ground_normal()
+        })
+        .collect()
+    }
+
+    fn assert_near(value: f32, expected: f32)
+    {
+        assert!((value - expected).abs() < 0.05, "{value} isn't {expected}")
+    }
+
+    #[test]
+    fn lands_on_the_ground()
+    {
+        let tiles = TileCollision::from_tiles((-5..=5).map(|x| vec2(x as f32, 0.)));
+        let mut player = player(vec2(0., 4.));
+
+        walk(&mut player, &tiles, 0., 10);
+        assert!(!player.is_on_ground());
+        assert!(!player.can_jump());
+
+        walk(&mut player, &tiles, 0., 60);
+
+        assert!(player.is_on_ground());
+        assert!(!player.is_on_wall() && !player.is_on_ceiling());
+        assert_eq!(player.ground_normal(), Some(Vec2::Y));
+        assert_near(player.position().y, STANDING);
+        assert_eq!(player.velocity.y, 0.);
+
+        // standing still doesn't sink nor bounce
+        walk(&mut player, &tiles, 0., 60);
+        assert_near(player.position().y, STANDING);
+        assert!(player.is_on_ground());
+    }
+
+    #[test]
+    fn stops_against_walls()
+    {
+        let mut tiles = TileCollision::from_tiles((-5..=5).map(|x| vec2(x as f32, 0.)));
+        floor(&mut tiles, 1, 3, 3, TileShape::Solid);
+        tiles.insert(vec2(3., 1.), TileShape::Solid);
+        tiles.insert(vec2(3., 2.), TileShape::Solid);
+
+        let mut player = player(vec2(0., STANDING));
+
+        walk(&mut player, &tiles, 6., 60);
+
+        assert!(player.is_on_wall());
+        assert!(player.is_on_ground());
+        assert_eq!(player.wall_normal(), Some(Vec2::NEG_X));
+        assert_eq!(player.velocity.x, 0.);
+        assert_near(player.position().x, 2.5 - 0.4);
+        assert!(player.collisions().iter().any(|hit| hit.normal == Vec2::NEG_X));
+    }
+
+    #[test]
+    fn bumps_into_ceilings()
+    {
+        let mut tiles = TileCollision::from_tiles((-5..=5).map(|x| vec2(x as f32, 0.)));
+        floor(&mut tiles, -5, 5, 3, TileShape::Solid);
+
+        let mut player = player(vec2(0., STANDING));
+        walk(&mut player, &tiles, 0., 2);
+
+        assert!(player.jump(12.));
+
+        let hit_ceiling = (0..10).any(|_|
+        {
+            walk(&mut player, &tiles, 0., 1);
+            player.is_on_ceiling()
+        });
+
+        assert!(hit_ceiling);
+        assert!(player.velocity.y <= 0.);
+        assert!(player.position().y + 0.9 <= 2.5);
+
+        walk(&mut player, &tiles, 0., 60);
+        assert!(player.is_on_ground() && !player.is_on_ceiling());
+    }
+
+    #[test]
+    fn walks_up_and_down_slopes()
+    {
+        let mut tiles = TileCollision::from_tiles((-5..=12).map(|x| vec2(x as f32, 0.)));
+        tiles.insert(vec2(3., 1.), TileShape::SlopeRight);
+        floor(&mut tiles, 4, 8, 1, TileShape::Solid);
+        tiles.insert(vec2(9., 1.), TileShape::SlopeLeft);
+
+        let mut player = player(vec2(0., STANDING));
+        walk(&mut player, &tiles, 0., 2);
+
+        let normals = walk(&mut player, &tiles, 4., 60);
+
+        assert!(normals.iter().all(Option::is_some), "left the ground: {normals:?}");
+        assert!(normals.iter().flatten().any(|normal| normal.abs_diff_eq(vec2(-1., 1.).normalize(), 1e-3)));
+        assert_near(player.position().y, STANDING + 1.);
+
+        // and down the other side, without flying off
+        let normals = walk(&mut player, &tiles, 4., 120);
+
+        assert!(normals.iter().all(Option::is_some), "left the ground: {normals:?}");
+        assert!(normals.iter().flatten().any(|normal| normal.abs_diff_eq(vec2(1., 1.).normalize(), 1e-3)));
+        assert_near(player.position().y, STANDING);
+        assert!(player.position().x > 10.);
+    }
+
+    #[test]
+    fn steep_slopes_are_walls()
+    {
+        let mut tiles = TileCollision::from_tiles((-5..=5).map(|x| vec2(x as f32, 0.)));
+        tiles.insert(vec2(3., 1.), TileShape::SlopeRight);
+        floor(&mut tiles, 4, 5, 1, TileShape::Solid);
+
+        let mut player = player(vec2(0., STANDING)).with_max_slope(30f32.to_radians());
+        walk(&mut player, &tiles, 4., 60);
+
+        // it stays at the foot of the slope without creeping up it, and can still jump
+        assert!(player.is_on_wall() && player.is_on_ground());
+        assert!(player.position().x < 2.5);
+        assert_near(player.position().y, STANDING);
+        assert!(player.can_jump());
+    }
+
+    #[test]
+    fn max_slope_stays_below_vertical()
+    {
+        let player = player(Vec2::ZERO).with_max_slope(std::f32::consts::PI);
+
+        assert!(player.max_slope > 0.);
+        assert!(!player.is_ground(Vec2::X));
+        assert!(player.is_ground(vec2(1., 0.1).normalize()));
+
+        assert_eq!(self::player(Vec2::ZERO).with_max_slope(-1.).max_slope, 1.);
+    }
+
+    #[test]
+    fn one_way_platforms_are_jumped_through_and_dropped_through()
+    {
+        let mut tiles = TileCollision::from_tiles((-5..=5).map(|x| vec2(x as f32, 0.)));
+        floor(&mut tiles, -2, 2, 3, TileShape::OneWay);
+
+        let mut player = player(vec2(0., STANDING));
+        walk(&mut player, &tiles, 0., 2);
+
+        assert!(player.jump(15.));
+        assert!(!player.is_on_ceiling());
+
+        walk(&mut player, &tiles, 0., 90);
+
+        assert!(player.is_on_ground());
+        assert_near(player.position().y, STANDING + 3.);
+
+        player.drop_through();
+        walk(&mut player, &tiles, 0., 90);
+
+        assert!(player.is_on_ground());
+        assert_near(player.position().y, STANDING);
+    }
+
+    #[test]
+    fn coyote_time_allows_a_late_jump_once()
+    {
+        let tiles = TileCollision::from_tiles((-5..=0).map(|x| vec2(x as f32, 0.)));
+
+        let mut player = player(vec2(0., STANDING)).with_coyote_time(Duration::from_millis(100));
+        walk(&mut player, &tiles, 0., 2);
+
+        // walks off the ledge
+        while player.is_on_ground()
+        {
+            walk(&mut player, &tiles, 4., 1);
+        }
+
+        assert!(player.can_jump());
+
+        let mut late = player.clone();
+        walk(&mut late, &tiles, 0., 7);
+        assert!(!late.can_jump());
+        assert!(!late.jump(12.));
+
+        assert!(player.jump(12.));
+        assert!(!player.can_jump());
+        assert!(!player.jump(12.));
+        assert_eq!(player.velocity.y, 12.);
+    }
+
+    #[test]
+    fn climbs_steps_lower_than_the_step_height()
+    {
+        // the tiles are a quarter unit high
+        let mut tiles = TileCollision::new().with_transform(Transform2D::from_scale(vec2(1., 0.25)));
+        floor(&mut tiles, -5, 10, 0, TileShape::Solid);
+        floor(&mut tiles, 3, 10, 1, TileShape::Solid);
+
+        let standing = 0.125 + 0.9;
+
+        let mut blocked = player(vec2(0., standing));
+        walk(&mut blocked, &tiles, 4., 60);
+
+        assert!(blocked.is_on_wall());
+        assert!(blocked.position().x < 2.5);
+
+        let mut climbing = player(vec2(0., standing)).with_step_height(0.3);
+        walk(&mut climbing, &tiles, 4., 60);
+
+        assert!(climbing.is_on_ground() && !climbing.is_on_wall());
+        assert!(climbing.position().x > 3.5);
+        assert_near(climbing.position().y, standing + 0.25);
+
+        let mut too_high = player(vec2(0., standing)).with_step_height(0.2);
+        walk(&mut too_high, &tiles, 4., 60);
+
+        assert!(too_high.position().x < 2.5);
+    }
+}
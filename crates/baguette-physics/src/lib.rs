@@ -0,0 +1,18 @@
+//! # baguette-physics
+//! a lightweight 2d physics world built on the collision shapes of baguette-math,
+//! with static, kinematic and dynamic bodies, friction, restitution, sensors and collision events,
+//! and a character controller moving through the tiles of a level
+
+mod body;
+pub use body::{Body, BodyHandle, BodyKind};
+
+mod solver;
+
+pub mod world;
+pub use world::{PhysicsEvent, PhysicsWorld};
+
+pub mod tiles;
+pub use tiles::{TileCollision, TileShape};
+
+pub mod controller;
+pub use controller::{CharacterController, Obstacle, Obstacles};
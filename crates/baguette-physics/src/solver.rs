@@ -0,0 +1,232 @@
+use baguette_math::{Contact, Vec2};
+
+use crate::Body;
+
+/// how much the shapes can overlap before they're pushed apart, keeps resting contacts from jittering
+const SLOP: f32 = 0.005;
+/// how much of the overlap is fixed at every step
+const BAUMGARTE: f32 = 0.2;
+/// the shapes hitting each other slower than this don't bounce, so that resting bodies stay still
+const RESTITUTION_THRESHOLD: f32 = 1.;
+/// a contact point closer than this to one of the last step is considered the same,
+/// and starts from its impulses
+const MATCH_DISTANCE: f32 = 0.05;
+
+/// what the solver needs of a body, the static ones have an inverse mass of zero
+#[derive(Default, Clone, Copy)]
+pub(crate) struct SolverBody
+{
+    pub velocity: Vec2,
+    pub angular_velocity: f32,
+    pub center: Vec2,
+    pub inverse_mass: f32,
+    pub inverse_inertia: f32
+}
+
+impl From<&Body> for SolverBody
+{
+    fn from(body: &Body) -> Self
+    {
+        Self
+        {
+            velocity: body.velocity,
+            angular_velocity: body.angular_velocity,
+            center: body.center_of_mass(),
+            inverse_mass: body.inverse_mass,
+            inverse_inertia: body.inverse_inertia
+        }
+    }
+}
+
+impl SolverBody
+{
+    fn velocity_at(&self, offset: Vec2) -> Vec2
+    {
+        self.velocity + offset.perp() * self.angular_velocity
+    }
+
+    fn apply_impulse(&mut self, impulse: Vec2, offset: Vec2)
+    {
+        self.velocity += impulse * self.inverse_mass;
+        self.angular_velocity += offset.perp_dot(impulse) * self.inverse_inertia
+    }
+}
+
+/// the impulses of a contact point, kept from one step to the next so that
+/// the solver starts close to the answer and the stacks stay still
+#[derive(Debug, Default, Clone, Copy)]
+pub(crate) struct ContactImpulse
+{
+    pub position: Vec2,
+    pub normal: f32,
+    pub tangent: f32
+}
+
+impl ContactImpulse
+{
+    /// the impulses of the closest point of the last step, zero if there's none
+    pub fn matching(position: Vec2, previous: &[ContactImpulse]) -> Self
+    {
+        let closest = previous
+            .iter()
+            .filter(|impulse| impulse.position.distance_squared(position) < MATCH_DISTANCE * MATCH_DISTANCE)
+            .min_by(|a, b| a.position.distance_squared(position).total_cmp(&b.position.distance_squared(position)));
+
+        Self { position, ..closest.copied().unwrap_or_default() }
+    }
+}
+
+#[derive(Default, Clone, Copy)]
+struct ConstraintPoint
+{
+    /// from the centers of mass to the point
+    offset_a: Vec2,
+    offset_b: Vec2,
+    normal_mass: f32,
+    tangent_mass: f32,
+    /// the speed the bodies should separate at, to bounce and to fix the overlap
+    bias: f32,
+    normal_impulse: f32,
+    tangent_impulse: f32,
+    position: Vec2
+}
+
+/// keeps two bodies from going through each other, solved with sequential impulses
+pub(crate) struct ContactConstraint
+{
+    a: usize,
+    b: usize,
+    /// from `a` to `b`
+    normal: Vec2,
+    friction: f32,
+    points: [ConstraintPoint; 2],
+    len: usize
+}
+
+impl ContactConstraint
+{
+    pub fn new
+    (
+        bodies: &[SolverBody],
+        (a, b): (usize, usize),
+        contact: &Contact,
+        impulses: &[ContactImpulse],
+        friction: f32,
+        restitution: f32,
+        dt: f32
+    )
+    -> Self
+    {
+        let (body_a, body_b) = (&bodies[a], &bodies[b]);
+
+        let normal = contact.normal;
+        let tangent = normal.perp();
+
+        let mut points = [ConstraintPoint::default(); 2];
+
+        for ((point, contact_point), impulse) in points.iter_mut().zip(contact.points()).zip(impulses)
+        {
+            let offset_a = contact_point.position - body_a.center;
+            let offset_b = contact_point.position - body_b.center;
+
+            let effective_mass = |axis: Vec2|
+            {
+                let mass = body_a.inverse_mass + body_b.inverse_mass
+                    + body_a.inverse_inertia * offset_a.perp_dot(axis).powi(2)
+                    + body_b.inverse_inertia * offset_b.perp_dot(axis).powi(2);
+
+                match mass > 0.
+                {
+                    true => 1. / mass,
+                    false => 0.
+                }
+            };
+
+            let approach = (body_b.velocity_at(offset_b) - body_a.velocity_at(offset_a)).dot(normal);
+
+            let bounce = match approach < -RESTITUTION_THRESHOLD
+            {
+                true => -restitution * approach,
+                false => 0.
+            };
+
+            let correction = BAUMGARTE / dt * (contact_point.depth - SLOP).max(0.);
+
+            *point = ConstraintPoint
+            {
+                offset_a,
+                offset_b,
+                normal_mass: effective_mass(normal),
+                tangent_mass: effective_mass(tangent),
+                bias: bounce.max(correction),
+                normal_impulse: impulse.normal,
+                tangent_impulse: impulse.tangent,
+                position: contact_point.position
+            }
+        }
+
+        Self { a, b, normal, friction, points, len: contact.points().len() }
+    }
+
+    /// applies the impulses kept from the last step
+    pub fn warm_start(&self, bodies: &mut [SolverBody])
+    {
+        let tangent = self.normal.perp();
+
+        for point in &self.points[..self.len]
+        {
+            let impulse = self.normal * point.normal_impulse + tangent * point.tangent_impulse;
+
+            bodies[self.a].apply_impulse(-impulse, point.offset_a);
+            bodies[self.b].apply_impulse(impulse, point.offset_b);
+        }
+    }
+
+    /// the impulses to start from on the next step
+    pub fn impulses(&self) -> impl Iterator<Item = ContactImpulse> + '_
+    {
+        self.points[..self.len].iter().map(|point| ContactImpulse
+        {
+            position: point.position,
+            normal: point.normal_impulse,
+            tangent: point.tangent_impulse
+        })
+    }
+
+    /// one iteration of the solver
+    pub fn solve(&mut self, bodies: &mut [SolverBody])
+    {
+        let (mut a, mut b) = (bodies[self.a], bodies[self.b]);
+        let tangent = self.normal.perp();
+
+        for point in &mut self.points[..self.len]
+        {
+            // pushes the bodies apart
+            let relative = b.velocity_at(point.offset_b) - a.velocity_at(point.offset_a);
+            let impulse = point.normal_mass * (point.bias - relative.dot(self.normal));
+
+            // the total impulse can only push, never pull
+            let total = (point.normal_impulse + impulse).max(0.);
+            let impulse = total - point.normal_impulse;
+            point.normal_impulse = total;
+
+            a.apply_impulse(-self.normal * impulse, point.offset_a);
+            b.apply_impulse(self.normal * impulse, point.offset_b);
+
+            // slows down the sliding, up to what the pressure allows
+            let relative = b.velocity_at(point.offset_b) - a.velocity_at(point.offset_a);
+            let impulse = -point.tangent_mass * relative.dot(tangent);
+
+            let limit = self.friction * point.normal_impulse;
+            let total = (point.tangent_impulse + impulse).clamp(-limit, limit);
+            let impulse = total - point.tangent_impulse;
+            point.tangent_impulse = total;
+
+            a.apply_impulse(-tangent * impulse, point.offset_a);
+            b.apply_impulse(tangent * impulse, point.offset_b);
+        }
+
+        bodies[self.a] = a;
+        bodies[self.b] = b;
+    }
+}
@@ -0,0 +1,229 @@
+use std::collections::HashMap;
+
+use baguette_math::{vec2, Aabb, ConvexPolygon, IVec2, Ray, RayHit, Shape, Transform2D, Vec2};
+
+/// how a tile collides, the tiles are one unit wide like the ones drawn by a tilemap
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum TileShape
+{
+    /// the whole square
+    #[default]
+    Solid,
+    /// only its top stops what falls on it, it can be jumped through from below
+    /// and dropped through with [CharacterController::drop_through](crate::CharacterController::drop_through)
+    OneWay,
+    /// a ramp going up to the right
+    SlopeRight,
+    /// a ramp going up to the left
+    SlopeLeft
+}
+
+impl TileShape
+{
+    /// the shape of a tile centered at the origin
+    fn local_shape(&self) -> Shape
+    {
+        let (min, max) = (vec2(-0.5, -0.5), vec2(0.5, 0.5));
+
+        let ramp = |top: Vec2| ConvexPolygon::new([min, vec2(max.x, min.y), top])
+            .expect("a ramp is a triangle")
+            .into();
+
+        match self
+        {
+            Self::Solid | Self::OneWay => Aabb::new(min, max).into(),
+            Self::SlopeRight => ramp(max),
+            Self::SlopeLeft => ramp(vec2(min.x, max.y))
+        }
+    }
+}
+
+/// the solid tiles of a level, built from the positions of the `Tile`s of a tilemap
+/// so that the collisions match what's drawn.
+///
+/// the tiles are stored on a grid, in the space of the tilemap,
+/// and the transform places them in the world like the one of the tilemap
+///
+/// # example
+///
+/// ```ignore
+/// let level = TilemapBuilder::from_path("level.map")?.solid_layer(1).with_transform(transform);
+/// app.renderer.add_tilemap(level);
+///
+/// let mut tiles = TileCollision::from_tiles(app.renderer.solid_tiles().iter().map(|tile| tile.pos))
+///     .with_transform(transform);
+///
+/// for ramp in &ramps
+/// {
+///     tiles.insert(ramp.pos, TileShape::SlopeRight);
+/// }
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct TileCollision
+{
+    tiles: HashMap<IVec2, TileShape>,
+    transform: Transform2D,
+    inverse: Transform2D
+}
+
+impl TileCollision
+{
+    pub fn new() -> Self
+    {
+        Self::default()
+    }
+
+    /// solid tiles at these positions
+    pub fn from_tiles(positions: impl IntoIterator<Item = Vec2>) -> Self
+    {
+        let mut collision = Self::new();
+
+        for position in positions
+        {
+            collision.insert(position, TileShape::Solid);
+        }
+
+        collision
+    }
+
+    /// places the tiles in the world, use the transform of the tilemap
+    pub fn with_transform(mut self, transform: Transform2D) -> Self
+    {
+        self.set_transform(transform);
+        self
+    }
+
+    pub fn transform(&self) -> Transform2D
+    {
+        self.transform
+    }
+
+    /// moves all the tiles, call it along with `Renderer::set_tilemap_transform`
+    pub fn set_transform(&mut self, transform: Transform2D)
+    {
+        self.transform = transform;
+        self.inverse = transform.inverse();
+    }
+
+    /// adds or replaces the tile at this position inside the tilemap, returns the one replaced
+    pub fn insert(&mut self, position: Vec2, shape: TileShape) -> Option<TileShape>
+    {
+        self.tiles.insert(Self::cell(position), shape)
+    }
+
+    /// removes the tile at this position inside the tilemap, for doors and breakable blocks
+    pub fn remove(&mut self, position: Vec2) -> Option<TileShape>
+    {
+        self.tiles.remove(&Self::cell(position))
+    }
+
+    /// the tile at this position inside the tilemap
+    pub fn get(&self, position: Vec2) -> Option<TileShape>
+    {
+        self.tiles.get(&Self::cell(position)).copied()
+    }
+
+    pub fn clear(&mut self)
+    {
+        self.tiles.clear()
+    }
+
+    pub fn len(&self) -> usize
+    {
+        self.tiles.len()
+    }
+
+    pub fn is_empty(&self) -> bool
+    {
+        self.tiles.is_empty()
+    }
+
+    /// the positions of the tiles inside the tilemap and their shapes, in no particular order
+    pub fn iter(&self) -> impl Iterator<Item = (Vec2, TileShape)> + '_
+    {
+        self.tiles.iter().map(|(cell, shape)| (cell.as_vec2(), *shape))
+    }
+
+    /// the position inside the tilemap of the tile covering this point of the world,
+    /// whether there's a tile there or not
+    pub fn tile_at(&self, point: Vec2) -> Vec2
+    {
+        Self::cell(self.inverse.transform_point(point)).as_vec2()
+    }
+
+    /// the shape of the tile at this position inside the tilemap, placed in the world
+    pub fn shape(&self, position: Vec2) -> Option<Shape>
+    {
+        let cell = Self::cell(position);
+
+        self.tiles.get(&cell).map(|shape| self.world_shape(cell, *shape))
+    }
+
+    /// returns true if the point of the world is inside a tile
+    pub fn contains_point(&self, point: Vec2) -> bool
+    {
+        let position = self.tile_at(point);
+
+        self.shape(position).is_some_and(|shape| shape.contains_point(point))
+    }
+
+    /// the tiles that can touch the region of the world, with their shapes placed in the world
+    pub fn query_aabb(&self, region: &Aabb) -> impl Iterator<Item = (Vec2, TileShape, Shape)> + '_
+    {
+        let local = Aabb::from_points(region.corners().map(|corner| self.inverse.transform_point(corner)))
+            .expect("a box has corners");
+
+        // the tiles reach half a unit around their positions
+        let min = (local.min - 0.5).ceil().as_ivec2();
+        let max = (local.max + 0.5).floor().as_ivec2();
+
+        let count = (max - min + 1).max(IVec2::ZERO).as_i64vec2();
+
+        // big regions look at the tiles instead of every cell
+        let cells: Box<dyn Iterator<Item = (IVec2, TileShape)>> = match count.x * count.y > self.tiles.len() as i64
+        {
+            true => Box::new(self.tiles
+                .iter()
+                .filter(move |(cell, _)| cell.cmpge(min).all() && cell.cmple(max).all())
+                .map(|(cell, shape)| (*cell, *shape))),
+            false => Box::new((min.y..=max.y)
+                .flat_map(move |y| (min.x..=max.x).map(move |x| IVec2::new(x, y)))
+                .filter_map(|cell| self.tiles.get(&cell).map(|shape| (cell, *shape))))
+        };
+
+        cells.map(|(cell, shape)| (cell.as_vec2(), shape, self.world_shape(cell, shape)))
+    }
+
+    /// the closest tile hit by the ray within `max_distance`, the one-way tiles are hit only from above
+    pub fn raycast(&self, ray: Ray, max_distance: f32) -> Option<(Vec2, RayHit)>
+    {
+        let region = Aabb::new(ray.origin, ray.at(max_distance));
+        let up = self.transform.transform_vector(Vec2::Y).normalize_or_zero();
+
+        self.query_aabb(&region)
+            .filter_map(|(position, tile, shape)|
+            {
+                let hit = shape.raycast(ray, max_distance)?;
+
+                match tile != TileShape::OneWay || hit.normal.dot(up) > 0.5
+                {
+                    true => Some((position, hit)),
+                    false => None
+                }
+            })
+            .min_by(|(_, a), (_, b)| a.distance.total_cmp(&b.distance))
+    }
+
+    fn cell(position: Vec2) -> IVec2
+    {
+        position.round().as_ivec2()
+    }
+
+    fn world_shape(&self, cell: IVec2, shape: TileShape) -> Shape
+    {
+        shape
+            .local_shape()
+            .translated(cell.as_vec2())
+            .transformed(&self.transform)
+    }
+}
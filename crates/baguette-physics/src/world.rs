@@ -0,0 +1,690 @@
+use std::collections::BTreeMap;
+use std::time::Duration;
+
+use baguette_math::{vec2, Aabb, AabbTree, Contact, ProxyId, Ray, RayHit, Transform2D, Transformable, Vec2};
+
+use crate::solver::{ContactConstraint, ContactImpulse, SolverBody};
+use crate::{Body, BodyHandle, BodyKind};
+
+/// what happened between the bodies during the last [PhysicsWorld::update]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PhysicsEvent
+{
+    /// two bodies started touching
+    CollisionStarted(BodyHandle, BodyHandle),
+    /// two bodies stopped touching, or one of them was removed
+    CollisionEnded(BodyHandle, BodyHandle),
+    /// a body entered a sensor
+    SensorEntered { sensor: BodyHandle, body: BodyHandle },
+    /// a body left a sensor, or one of them was removed
+    SensorExited { sensor: BodyHandle, body: BodyHandle }
+}
+
+/// two bodies touching after a step
+#[derive(Clone, Copy)]
+struct Touching
+{
+    /// from the first body to the second
+    contact: Contact,
+    /// the sensor and the other body, if one of them is a sensor
+    sensor: Option<(BodyHandle, BodyHandle)>,
+    /// one for each point of the contact
+    impulses: [ContactImpulse; 2]
+}
+
+struct Slot
+{
+    generation: u32,
+    body: Option<(Body, ProxyId)>
+}
+
+/// the bodies and the simulation moving them, it advances in fixed steps
+/// so that it behaves the same at any frame rate.
+///
+/// # example
+///
+/// ```
+/// use std::time::Duration;
+/// use baguette_math::{vec2, Aabb, Circle, Transform2D, Vec2};
+/// use baguette_physics::{Body, PhysicsEvent, PhysicsWorld};
+///
+/// let mut world = PhysicsWorld::new().with_gravity(vec2(0., -20.));
+///
+/// world.add(Body::fixed(Aabb::new(vec2(-10., -1.), vec2(10., 0.))));
+/// let ball = world.add(Body::dynamic(Circle::new(Vec2::ZERO, 0.5)).with_position(vec2(0., 5.)).with_restitution(0.6));
+///
+/// let mut ball_transform = Transform2D::default();
+/// let mut bounces = 0;
+///
+/// // every frame
+/// for _ in 0..60
+/// {
+///     world.update(Duration::from_millis(16));
+///     world.sync(ball, &mut ball_transform);
+///
+///     for event in world.events()
+///     {
+///         if let PhysicsEvent::CollisionStarted(..) = event
+///         {
+///             bounces += 1
+///         }
+///     }
+/// }
+///
+/// assert_eq!(bounces, 1);
+/// assert!(ball_transform.translation.y > 0.);
+/// ```
+pub struct PhysicsWorld
+{
+    slots: Vec<Slot>,
+    free: Vec<u32>,
+    len: usize,
+    broad_phase: AabbTree<BodyHandle>,
+
+    gravity: Vec2,
+    timestep: Duration,
+    iterations: u32,
+    /// the most steps done in one update, so that a slow frame doesn't make the next one slower
+    max_steps: u32,
+    /// the time not simulated yet
+    accumulator: Duration,
+
+    /// the pairs that were touching after the last step
+    contacts: BTreeMap<(BodyHandle, BodyHandle), Touching>,
+    events: Vec<PhysicsEvent>
+}
+
+impl Default for PhysicsWorld
+{
+    fn default() -> Self
+    {
+        Self
+        {
+            slots: vec![],
+            free: vec![],
+            len: 0,
+            broad_phase: AabbTree::new(),
+
+            gravity: vec2(0., -9.81),
+            timestep: Duration::from_secs(1) / 60,
+            iterations: 8,
+            max_steps: 8,
+            accumulator: Duration::ZERO,
+
+            contacts: BTreeMap::new(),
+            events: vec![]
+        }
+    }
+}
+
+impl PhysicsWorld
+{
+    pub fn new() -> Self
+    {
+        Self::default()
+    }
+
+    /// the acceleration of the dynamic bodies, `(0., -9.81)` by default
+    pub fn with_gravity(mut self, gravity: Vec2) -> Self
+    {
+        self.gravity = gravity;
+        self
+    }
+
+    /// how long a step lasts, a sixtieth of a second by default
+    pub fn with_timestep(mut self, timestep: Duration) -> Self
+    {
+        self.timestep = timestep.max(Duration::from_micros(100));
+        self
+    }
+
+    /// how many times the contacts are solved at each step, more makes stacks steadier
+    pub fn with_iterations(mut self, iterations: u32) -> Self
+    {
+        self.iterations = iterations.max(1);
+        self
+    }
+
+    pub fn gravity(&self) -> Vec2
+    {
+        self.gravity
+    }
+
+    pub fn set_gravity(&mut self, gravity: Vec2)
+    {
+        self.gravity = gravity
+    }
+
+    pub fn timestep(&self) -> Duration
+    {
+        self.timestep
+    }
+
+    pub fn add(&mut self, mut body: Body) -> BodyHandle
+    {
+        body.update_world_shape();
+        body.previous = (body.position, body.rotation);
+
+        let (index, generation) = match self.free.pop()
+        {
+            Some(index) => (index, self.slots[index as usize].generation),
+            None =>
+            {
+                self.slots.push(Slot { generation: 0, body: None });
+                (self.slots.len() as u32 - 1, 0)
+            }
+        };
+
+        let handle = BodyHandle { index, generation };
+        let proxy = self.broad_phase.insert(body.world_shape.aabb(), handle);
+
+        self.slots[index as usize].body = Some((body, proxy));
+        self.len += 1;
+
+        handle
+    }
+
+    /// takes the body out of the world, its collisions end on the next update
+    pub fn remove(&mut self, handle: BodyHandle) -> Option<Body>
+    {
+        let slot = self.slots.get_mut(handle.index as usize).filter(|slot| slot.generation == handle.generation)?;
+        let (body, proxy) = slot.body.take()?;
+
+        slot.generation = slot.generation.wrapping_add(1);
+        self.free.push(handle.index);
+        self.len -= 1;
+
+        self.broad_phase.remove(proxy);
+
+        Some(body)
+    }
+
+    pub fn contains(&self, handle: BodyHandle) -> bool
+    {
+        self.get(handle).is_some()
+    }
+
+    pub fn len(&self) -> usize
+    {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool
+    {
+        self.len == 0
+    }
+
+    pub fn get(&self, handle: BodyHandle) -> Option<&Body>
+    {
+        self.slots
+            .get(handle.index as usize)
+            .filter(|slot| slot.generation == handle.generation)
+            .and_then(|slot| slot.body.as_ref())
+            .map(|(body, _)| body)
+    }
+
+    pub fn get_mut(&mut self, handle: BodyHandle) -> Option<&mut Body>
+    {
+        self.slots
+            .get_mut(handle.index as usize)
+            .filter(|slot| slot.generation == handle.generation)
+            .and_then(|slot| slot.body.as_mut())
+            .map(|(body, _)| body)
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (BodyHandle, &Body)>
+    {
+        self.slots.iter().enumerate().filter_map(|(index, slot)|
+        {
+            let (body, _) = slot.body.as_ref()?;
+            Some((BodyHandle { index: index as u32, generation: slot.generation }, body))
+        })
+    }
+
+    /// advances the simulation by as many steps as fit in the time since the last update,
+    /// returns how many were done
+    pub fn update(&mut self, delta: Duration) -> u32
+    {
+        self.events.clear();
+        self.accumulator += delta;
+
+        let mut steps = 0;
+
+        while self.accumulator >= self.timestep && steps < self.max_steps
+        {
+            self.accumulator -= self.timestep;
+            self.step(self.timestep.as_secs_f32());
+
+            steps += 1
+        }
+
+        // the simulation can't keep up, it's better to slow down than to freeze
+        if steps == self.max_steps
+        {
+            self.accumulator = self.accumulator.min(self.timestep)
+        }
+
+        steps
+    }
+
+    /// what happened during the last update
+    pub fn events(&self) -> &[PhysicsEvent]
+    {
+        &self.events
+    }
+
+    /// the bodies touching this one and how, the normals point away from it
+    pub fn contacts(&self, handle: BodyHandle) -> impl Iterator<Item = (BodyHandle, Contact)> + '_
+    {
+        self.contacts.iter().filter_map(move |((a, b), touching)| match handle
+        {
+            handle if handle == *a => Some((*b, touching.contact)),
+            handle if handle == *b => Some((*a, touching.contact.flipped())),
+            _ => None
+        })
+    }
+
+    /// returns true if the bodies were touching after the last step
+    pub fn is_touching(&self, a: BodyHandle, b: BodyHandle) -> bool
+    {
+        self.contacts.contains_key(&(a.min(b), a.max(b)))
+    }
+
+    /// where the body is drawn, between its last two steps so that it moves smoothly
+    /// even when the frames don't line up with the steps
+    pub fn interpolated_transform(&self, handle: BodyHandle) -> Option<Transform2D>
+    {
+        let body = self.get(handle)?;
+        let t = self.accumulator.as_secs_f32() / self.timestep.as_secs_f32();
+
+        let (position, rotation) = body.previous;
+
+        Some(Transform2D::new
+        (
+            position.lerp(body.position, t),
+            rotation + (body.rotation - rotation) * t,
+            Vec2::ONE
+        ))
+    }
+
+    /// moves the target, usually a sprite instance, where the body is.
+    /// its scale and depth are kept, returns false if the body doesn't exist
+    pub fn sync(&self, handle: BodyHandle, target: &mut impl Transformable) -> bool
+    {
+        let Some(transform) = self.interpolated_transform(handle)
+        else
+        {
+            return false
+        };
+
+        let mut placed = target.transform();
+
+        placed.translation = transform.translation;
+        placed.rotation = transform.rotation;
+
+        target.set_transform(placed);
+
+        true
+    }
+
+    /// the closest body hit by the ray within `max_distance` on one of the `layers`, sensors are ignored
+    pub fn raycast(&self, ray: Ray, max_distance: f32, layers: u32) -> Option<(BodyHandle, RayHit)>
+    {
+        let mut closest = None;
+
+        self.broad_phase.raycast_with(ray, max_distance, |_, handle|
+        {
+            let body = self.get(*handle)?;
+
+            if body.sensor || body.layers & layers == 0
+            {
+                return None
+            }
+
+            let hit = body.world_shape.raycast(ray, max_distance)?;
+            closest = Some((*handle, hit));
+
+            Some(hit.distance)
+        })?;
+
+        closest
+    }
+
+    /// the bodies containing the point
+    pub fn query_point(&self, point: Vec2) -> impl Iterator<Item = BodyHandle> + '_
+    {
+        self.broad_phase
+            .query_point(point)
+            .map(|(_, handle)| *handle)
+            .filter(move |handle| self.get(*handle).is_some_and(|body| body.world_shape.contains_point(point)))
+    }
+
+    /// the bodies whose shapes are in the region
+    pub fn query_aabb(&self, region: Aabb) -> impl Iterator<Item = BodyHandle> + '_
+    {
+        let shape = region.into();
+
+        self.broad_phase
+            .query(&region)
+            .map(|(_, handle)| *handle)
+            .filter(move |handle| self.get(*handle).is_some_and(|body| body.world_shape.intersects(&shape)))
+    }
+
+    fn step(&mut self, dt: f32)
+    {
+        for (body, proxy) in self.slots.iter_mut().filter_map(|slot| slot.body.as_mut())
+        {
+            body.previous = (body.position, body.rotation);
+            self.broad_phase.update(*proxy, body.world_shape.aabb());
+        }
+
+        self.find_contacts();
+
+        // gravity and forces
+        for (body, _) in self.slots.iter_mut().filter_map(|slot| slot.body.as_mut())
+        {
+            if body.kind == BodyKind::Dynamic
+            {
+                body.velocity += (self.gravity * body.gravity_scale + body.force * body.inverse_mass) * dt;
+                body.angular_velocity += body.torque * body.inverse_inertia * dt;
+
+                body.velocity /= 1. + dt * body.linear_damping;
+                body.angular_velocity /= 1. + dt * body.angular_damping;
+            }
+
+            body.force = Vec2::ZERO;
+            body.torque = 0.
+        }
+
+        self.solve(dt);
+
+        for (body, _) in self.slots.iter_mut().filter_map(|slot| slot.body.as_mut())
+        {
+            if body.kind == BodyKind::Static
+            {
+                continue
+            }
+
+            let center = body.center_of_mass() + body.velocity * dt;
+            body.rotation += body.angular_velocity * dt;
+            body.position = center - Vec2::from_angle(body.rotation).rotate(body.local_center);
+
+            body.update_world_shape()
+        }
+    }
+
+    /// finds the touching pairs and sends the events of the ones that changed
+    fn find_contacts(&mut self)
+    {
+        let mut contacts = BTreeMap::new();
+
+        for (a, b) in self.broad_phase.pairs()
+        {
+            let (Some(a), Some(b)) = (self.broad_phase.get(a), self.broad_phase.get(b))
+            else
+            {
+                continue
+            };
+
+            let (a, b) = (*a.min(b), *a.max(b));
+            let (Some(body_a), Some(body_b)) = (self.get(a), self.get(b))
+            else
+            {
+                continue
+            };
+
+            if !body_a.interacts_with(body_b)
+            {
+                continue
+            }
+
+            let sensor = match (body_a.sensor, body_b.sensor)
+            {
+                (true, _) => Some((a, b)),
+                (_, true) => Some((b, a)),
+                _ => None
+            };
+
+            let Some(contact) = body_a.world_shape.contact(&body_b.world_shape)
+            else
+            {
+                continue
+            };
+
+            let previous = self.contacts.get(&(a, b)).map_or(&[][..], |touching| &touching.impulses[..touching.contact.points().len()]);
+
+            let mut impulses = [ContactImpulse::default(); 2];
+
+            for (impulse, point) in impulses.iter_mut().zip(contact.points())
+            {
+                *impulse = ContactImpulse::matching(point.position, previous)
+            }
+
+            contacts.insert((a, b), Touching { contact, sensor, impulses });
+        }
+
+        let previous = std::mem::replace(&mut self.contacts, contacts);
+
+        for (&(a, b), touching) in previous.iter().filter(|(pair, _)| !self.contacts.contains_key(pair))
+        {
+            self.events.push(match touching.sensor
+            {
+                Some((sensor, body)) => PhysicsEvent::SensorExited { sensor, body },
+                None => PhysicsEvent::CollisionEnded(a, b)
+            })
+        }
+
+        for (&(a, b), touching) in self.contacts.iter().filter(|(pair, _)| !previous.contains_key(pair))
+        {
+            self.events.push(match touching.sensor
+            {
+                Some((sensor, body)) => PhysicsEvent::SensorEntered { sensor, body },
+                None => PhysicsEvent::CollisionStarted(a, b)
+            })
+        }
+    }
+
+    fn solve(&mut self, dt: f32)
+    {
+        let mut bodies: Vec<SolverBody> = self.slots
+            .iter()
+            .map(|slot| slot.body.as_ref().map(|(body, _)| body.into()).unwrap_or_default())
+            .collect();
+
+        let mut constraints: Vec<((BodyHandle, BodyHandle), ContactConstraint)> = self.contacts
+            .iter()
+            .filter_map(|(&(a, b), touching)|
+            {
+                let (body_a, body_b) = (self.get(a)?, self.get(b)?);
+
+                let responds = touching.sensor.is_none()
+                    && (body_a.kind == BodyKind::Dynamic || body_b.kind == BodyKind::Dynamic);
+
+                responds.then(|| ((a, b), ContactConstraint::new
+                (
+                    &bodies,
+                    (a.index as usize, b.index as usize),
+                    &touching.contact,
+                    &touching.impulses,
+                    (body_a.friction * body_b.friction).sqrt(),
+                    body_a.restitution.max(body_b.restitution),
+                    dt
+                )))
+            })
+            .collect();
+
+        for (_, constraint) in &constraints
+        {
+            constraint.warm_start(&mut bodies)
+        }
+
+        for _ in 0..self.iterations
+        {
+            for (_, constraint) in &mut constraints
+            {
+                constraint.solve(&mut bodies)
+            }
+        }
+
+        for (pair, constraint) in &constraints
+        {
+            if let Some(touching) = self.contacts.get_mut(pair)
+            {
+                for (kept, impulse) in touching.impulses.iter_mut().zip(constraint.impulses())
+                {
+                    *kept = impulse
+                }
+            }
+        }
+
+        for (slot, solved) in self.slots.iter_mut().zip(bodies)
+        {
+            if let Some((body, _)) = &mut slot.body
+            {
+                if body.kind == BodyKind::Dynamic
+                {
+                    body.velocity = solved.velocity;
+                    body.angular_velocity = solved.angular_velocity
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests
+{
+    use baguette_math::{Capsule, Circle};
+
+    use super::*;
+
+    const STEP: Duration = Duration::from_nanos(16_666_667);
+
+    fn ground(world: &mut PhysicsWorld) -> BodyHandle
+    {
+        world.add(Body::fixed(Aabb::new(vec2(-10., -1.), vec2(10., 0.))))
+    }
+
+    fn unit_box(position: Vec2) -> Body
+    {
+        Body::dynamic(Aabb::from_center(Vec2::ZERO, vec2(0.5, 0.5))).with_position(position)
+    }
+
+    fn run(world: &mut PhysicsWorld, seconds: f32) -> Vec<PhysicsEvent>
+    {
+        let mut events = vec![];
+
+        for _ in 0..(seconds * 60.) as u32
+        {
+            world.update(STEP);
+            events.extend_from_slice(world.events())
+        }
+
+        events
+    }
+
+    fn body(world: &PhysicsWorld, handle: BodyHandle) -> &Body
+    {
+        world.get(handle).expect("the body is in the world")
+    }
+
+    #[test]
+    fn rests_on_the_ground()
+    {
+        let mut world = PhysicsWorld::new();
+        let ground = ground(&mut world);
+
+        let crate_box = world.add(unit_box(vec2(0., 3.)));
+        let ball = world.add(Body::dynamic(Circle::new(Vec2::ZERO, 0.5)).with_position(vec2(3., 2.)));
+
+        let events = run(&mut world, 3.);
+
+        assert!(events.contains(&PhysicsEvent::CollisionStarted(ground, crate_box)));
+        assert!(events.contains(&PhysicsEvent::CollisionStarted(ground, ball)));
+
+        for (handle, x) in [(crate_box, 0.), (ball, 3.)]
+        {
+            let body = body(&world, handle);
+
+            assert!((body.position().y - 0.5).abs() < 0.05, "{:?} at {}", handle, body.position());
+            assert!((body.position().x - x).abs() < 0.05, "{:?} at {}", handle, body.position());
+            assert!(body.velocity().length() < 0.05, "{:?} moving at {}", handle, body.velocity());
+        }
+
+        assert!(world.is_touching(ground, crate_box));
+    }
+
+    #[test]
+    fn boxes_stack()
+    {
+        let mut world = PhysicsWorld::new().with_iterations(10);
+        ground(&mut world);
+
+        let boxes: Vec<_> = (0..4).map(|level| world.add(unit_box(vec2(0., 0.5 + level as f32 * 1.05)))).collect();
+
+        run(&mut world, 5.);
+
+        for (level, handle) in boxes.iter().enumerate()
+        {
+            let body = body(&world, *handle);
+            let height = 0.5 + level as f32;
+
+            assert!((body.position().y - height).abs() < 0.1, "box {level} at {}", body.position());
+            assert!(body.position().x.abs() < 0.05, "box {level} at {}", body.position());
+            assert!(body.rotation().abs() < 0.05, "box {level} rotated by {}", body.rotation());
+            assert!(body.velocity().length() < 0.05, "box {level} moving at {}", body.velocity());
+        }
+    }
+
+    #[test]
+    fn sensors_report_entering_and_leaving()
+    {
+        let mut world = PhysicsWorld::new();
+        ground(&mut world);
+
+        let sensor = world.add(Body::fixed(Aabb::new(vec2(-1., 4.), vec2(1., 5.))).sensor());
+        let ball = world.add(Body::dynamic(Circle::new(Vec2::ZERO, 0.25)).with_position(vec2(0., 7.)));
+
+        let events = run(&mut world, 3.);
+
+        let sensor_events: Vec<_> = events
+            .iter()
+            .filter(|event| matches!(event, PhysicsEvent::SensorEntered { .. } | PhysicsEvent::SensorExited { .. }))
+            .collect();
+
+        assert_eq!(sensor_events, [&PhysicsEvent::SensorEntered { sensor, body: ball }, &PhysicsEvent::SensorExited { sensor, body: ball }]);
+
+        // the sensor doesn't stop the ball, it falls to the ground
+        assert!((body(&world, ball).position().y - 0.25).abs() < 0.05);
+
+        // removing a body inside a sensor ends the overlap
+        world.get_mut(ball).unwrap().set_position(vec2(0., 4.5));
+        run(&mut world, 0.1);
+        world.remove(ball);
+        world.update(STEP);
+
+        assert!(world.events().contains(&PhysicsEvent::SensorExited { sensor, body: ball }));
+    }
+
+    #[test]
+    fn circle_on_top_of_a_capsule()
+    {
+        let mut world = PhysicsWorld::new();
+
+        world.add(Body::fixed(Capsule::new(vec2(0., 0.), vec2(0., 1.), 0.4)));
+        let ball = world.add(Body::dynamic(Circle::new(Vec2::ZERO, 0.5)).with_position(vec2(0., 1.8)));
+
+        world.update(STEP);
+
+        // pushed up out of the capsule, not sideways
+        assert!(body(&world, ball).velocity().x.abs() < 0.01, "launched at {}", body(&world, ball).velocity());
+
+        run(&mut world, 2.);
+
+        let ball = body(&world, ball);
+
+        assert!(ball.position().x.abs() < 0.01, "slid to {}", ball.position());
+        assert!((ball.position().y - 1.9).abs() < 0.05, "rests at {}", ball.position());
+        assert!(ball.velocity().length() < 0.05);
+    }
+}
@@ -166,6 +166,8 @@ pub struct SpriteInstance
     /// `0..row * columns` is the range of allowed values, where rows and columns are the number of 
     /// rows and columns that this texture is sliced by
     pub(crate) uv_idx: u32,
+    /// multiplies the colors of the texture, white leaves it as it is
+    pub color: Color,
 }
 
 impl Default for SpriteInstance
@@ -178,14 +180,39 @@ impl Default for SpriteInstance
             orientation: Quat::default(),
             scale: Vec3::ONE,
             uv_idx: u32::default(),
+            color: Color::WHITE,
         }
     }
 }
 
+impl From<Transform2D> for SpriteInstance
+{
+    fn from(transform: Transform2D) -> Self
+    {
+        let mut instance = Self::default();
+        instance.set_transform(transform);
+        instance
+    }
+}
+
+/// lets a physics body or a tween move the instance
+impl Transformable for SpriteInstance
+{
+    fn transform(&self) -> Transform2D
+    {
+        SpriteInstance::transform(self)
+    }
+
+    fn set_transform(&mut self, transform: Transform2D)
+    {
+        SpriteInstance::set_transform(self, transform)
+    }
+}
+
 impl SpriteInstance
 {
     #[inline]
-    pub(crate) fn as_raw(&self, slice: &SpriteSlice, pivot: Option<Vec2>, bind_idx: u32) -> SpriteInstanceRaw
+    pub(crate) fn as_raw(&self, slice: &SpriteSlice, pivot: Option<Vec2>, bind_idx: u32, format: wgpu::TextureFormat) -> SpriteInstanceRaw
     {
         SpriteInstanceRaw
         {
@@ -210,6 +237,28 @@ impl SpriteInstance
             }.to_cols_array_2d(),
             uv_idx: u32::min(self.uv_idx, slice.rows * slice.columns - 1),
             bind_idx,
+            color: color_components(self.color, format),
+        }
+    }
+
+    /// places the instance with a 2d transform, usually the world transform of a [Hierarchy] node,
+    /// `z` becomes the translation on the z axis
+    pub fn set_transform(&mut self, transform: Transform2D)
+    {
+        self.translation = transform.translation3();
+        self.orientation = transform.to_quat();
+        self.scale = transform.scale.extend(self.scale.z)
+    }
+
+    /// the 2d transform of the instance, the rotation around the x and y axis is lost
+    pub fn transform(&self) -> Transform2D
+    {
+        Transform2D
+        {
+            translation: self.translation.truncate(),
+            rotation: self.orientation.to_euler(EulerRot::XYZ).2,
+            scale: self.scale.truncate(),
+            z: self.translation.z
         }
     }
 
@@ -35,14 +35,17 @@ struct InstanceInput
     @location(4) model_matrix_3: vec4<f32>,
 
     @location(5) uv_idx: u32,
-    @location(6) bind_index: u32
+    @location(6) bind_index: u32,
+
+    @location(7) color: vec4<f32>
 }
 
 struct VertexOutput
 {
     @builtin(position) clip_position: vec4<f32>,
     @location(0) tex_coords: vec2<f32>,
-    @location(1) bind_index: u32
+    @location(1) bind_index: u32,
+    @location(2) color: vec4<f32>
 }
 
 @vertex fn vertex(@location(0) vert_idx: u32, instance: InstanceInput) -> VertexOutput
@@ -79,11 +82,12 @@ struct VertexOutput
     (
         camera.view_proj * model_matrix * vec4<f32>(vertex.x, vertex.y, 0., 1.),
         tex_coords,
-        bind_index
+        bind_index,
+        instance.color
     );
 }
 
 @fragment fn fragment(in: VertexOutput) -> @location(0) vec4<f32>
 {
-    return textureSample(diff_texs[in.bind_index], diff_samplers[in.bind_index], in.tex_coords);
+    return textureSample(diff_texs[in.bind_index], diff_samplers[in.bind_index], in.tex_coords) * in.color;
 }
\ No newline at end of file
@@ -17,13 +17,15 @@ pub(crate) struct SpritePass
     handle: Handle,
     instances: FastIndexMap<u8, Vec<SpriteInstanceRaw>>,
     sort_scratch_buffer: Vec<SpriteInstanceRaw>,
+    /// the id given to the next sprite, counting up so that ids are the same on every run
+    next_id: u16,
 }
 
 impl SpritePass
 {
     pub fn add_sprite(&mut self, ctx: ContextHandle, builder: SpriteBuilder, layers: Layers2D) -> Sprite
     {
-        let id = baguette_math::rand::u16(..);
+        let id = self.next_free_id();
 
         let sprite = Sprite
         {
@@ -41,7 +43,26 @@ impl SpritePass
         sprite
     }
 
-    pub(crate) fn prepare_instances(&mut self)
+    /// the next id no loaded sprite is using, the ids of removed sprites come back once the counter wraps
+    fn next_free_id(&mut self) -> u16
+    {
+        let sprites = &self.handle.read().sprites;
+
+        assert!(sprites.len() <= u16::MAX as usize, "too many sprites are loaded");
+
+        while sprites.contains_key(&self.next_id)
+        {
+            self.next_id = self.next_id.wrapping_add(1)
+        }
+
+        let id = self.next_id;
+        self.next_id = self.next_id.wrapping_add(1);
+
+        id
+    }
+
+    /// `format` is the one of the target the sprites are drawn to
+    pub(crate) fn prepare_instances(&mut self, format: wgpu::TextureFormat)
     {
         self.instances.clear();
 
@@ -55,7 +76,7 @@ impl SpritePass
             {
                 let s = instances.iter().map
                 (
-                    |instance| instance.as_raw(&sprite.slice, sprite.pivot, i as u32)
+                    |instance| instance.as_raw(&sprite.slice, sprite.pivot, i as u32, format)
                 );
 
                 match self.instances.get_mut(layer)
@@ -383,6 +404,8 @@ pub(crate) struct SpriteInstanceRaw
     
     pub uv_idx: u32,
     pub bind_idx: u32,
+    /// the tint, in linear space for the srgb targets
+    pub color: [f32; 4],
 }
 
 /// the uvs of the spritepass are stored inside a storage array
@@ -587,11 +610,11 @@ impl SpriteBinding
                         step_mode: VertexStepMode::Vertex,
                         attributes: &vertex_attr_array![0 => Uint32],
                     },
-                    // for each instance we pass the model matrix and an index
-                    // that we'll use to get the uvs of the vertices from the storage buffer
+                    // for each instance we pass the model matrix, an index
+                    // that we'll use to get the uvs of the vertices from the storage buffer and a tint
                     VertexBufferLayout
                     {
-                        array_stride: std::mem::size_of::<([[f32; 4]; 4], u32, u32)>() as u64,
+                        array_stride: std::mem::size_of::<SpriteInstanceRaw>() as u64,
                         step_mode: VertexStepMode::Instance,
                         attributes: &vertex_attr_array!
                         [
@@ -602,6 +625,8 @@ impl SpriteBinding
                             
                             5 => Uint32, // uv index
                             6 => Uint32, // bind index
+
+                            7 => Float32x4, // color
                         ]
                     }
                 ],
@@ -615,7 +640,7 @@ impl SpriteBinding
                 &[
                     Some(ColorTargetState
                     {
-                        format: ctx.screen.config.format,
+                        format: FRAME_FORMAT,
                         write_mask: ColorWrites::ALL,
                         blend: Some(BlendState::ALPHA_BLENDING)
                     })
@@ -31,6 +31,9 @@ pub struct TilemapBuilder
     scale: Vec3,
 
     layers: indexmap::IndexMap<u8, Vec<Tile>>,
+    /// the layers collided against, not saved with the tilemap
+    #[serde(skip)]
+    solid_layers: Vec<u8>,
 
     filter: FilterMode,
     pxunit: f32
@@ -58,6 +61,7 @@ impl TilemapBuilder
             scale: Vec3::ONE,
 
             layers: Default::default(),
+            solid_layers: vec![],
             filter: FilterMode::Nearest,
             pxunit: 100.,
         }
@@ -70,6 +74,34 @@ impl TilemapBuilder
         self
     }
 
+    /// marks the layer as solid, its tiles can be read back with [crate::Renderer::solid_tiles]
+    /// to build the collisions of the level
+    ///
+    /// # example
+    ///
+    /// ```
+    /// let level = TilemapBuilder::from_path("level.map")?.solid_layer(1);
+    /// ```
+    pub fn solid_layer(mut self, layer: u8) -> Self
+    {
+        if !self.solid_layers.contains(&layer)
+        {
+            self.solid_layers.push(layer)
+        }
+
+        self
+    }
+
+    /// places the whole tilemap, it can be moved later with [crate::Renderer::set_tilemap_transform]
+    pub fn with_transform(mut self, transform: Transform2D) -> Self
+    {
+        self.position = transform.translation3();
+        self.rotation = transform.to_quat();
+        self.scale = transform.scale.extend(1.);
+
+        self
+    }
+
     pub fn add_texture(mut self, path: impl Into<std::path::PathBuf>, rows: u32, columns: u32) -> Self
     {
         self.maps.push(TextureLoadDescriptor
@@ -120,6 +152,7 @@ impl From<&'static str> for TilemapBuilder
 pub(crate) struct TilemapPass
 {
     pub layers: FastIndexMap<u8, Vec<Tile>>,
+    pub solid_layers: Vec<u8>,
     tranform: Mat4,
     binding: Option<TilemapBinding>
 }
@@ -129,7 +162,7 @@ impl TilemapPass
     pub fn add
     (
         &mut self, ctx: &ContextHandleInner,
-        TilemapBuilder { maps, layers, filter, pxunit, position, rotation, scale }:
+        TilemapBuilder { maps, layers, solid_layers, filter, pxunit, position, rotation, scale }:
         TilemapBuilder
     )
     {
@@ -176,6 +209,14 @@ impl TilemapPass
             }
         }
 
+        for layer in solid_layers
+        {
+            if !self.solid_layers.contains(&layer)
+            {
+                self.solid_layers.push(layer)
+            }
+        }
+
         if self.layers.is_empty()
         {
             self.layers.insert(0, vec![Tile::default()]);
@@ -197,6 +238,15 @@ impl TilemapPass
         };
     }
 
+    /// the tiles of the layers marked as solid
+    pub fn solid_tiles(&self) -> impl Iterator<Item = &Tile>
+    {
+        self.solid_layers
+            .iter()
+            .filter_map(|layer| self.layers.get(layer))
+            .flatten()
+    }
+
     pub(crate) fn draw<'a>
     (
         &'a self,
@@ -220,6 +270,17 @@ impl TilemapPass
         pass.draw_indexed(0..SPRITE_INDICES_U16.len() as _, 0, 0..binding.num_instances);
     }
 
+    /// moves the whole tilemap, for example to follow the node of a [Hierarchy] it's attached to
+    pub fn set_transform(&mut self, ctx: &ContextHandleInner, transform: Mat4)
+    {
+        self.tranform = transform;
+
+        if let Some(binding) = &self.binding
+        {
+            ctx.write_entire_buffer(&binding.matrix_buffer, &[transform.to_cols_array_2d()])
+        }
+    }
+
     pub fn resize(&mut self, ctx: &ContextHandleInner)
     {
         if let Some(binding) = &mut self.binding
@@ -454,7 +515,7 @@ impl TilemapBinding
                 &[
                     Some(ColorTargetState
                     {
-                        format: ctx.screen.config.format,
+                        format: FRAME_FORMAT,
                         write_mask: ColorWrites::ALL,
                         blend: Some(BlendState::ALPHA_BLENDING)
                     })
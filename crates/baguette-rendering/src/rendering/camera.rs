@@ -28,6 +28,13 @@ impl Camera
     {
         self.data.borrow_mut().set_position(position)
     }
+
+    /// converts a position in pixels, like the cursor position,
+    /// to the world position it points at on the `z = 0` plane
+    pub fn screen_to_world(&self, position: Vec2, screen_size: (f32, f32)) -> Vec3
+    {
+        self.data.borrow().screen_to_world(position, screen_size)
+    }
 }
 
 /// a scene camera
@@ -177,6 +184,31 @@ impl CameraData
         self.projection.orientation = self.projection.recalculate_orientation()
     }
 
+    pub fn screen_to_world(&self, position: Vec2, (width, height): (f32, f32)) -> Vec3
+    {
+        // pixels grow downward while normalized coordinates grow upward
+        let ndc = vec2
+        (
+            position.x / width * 2. - 1.,
+            1. - position.y / height * 2.
+        );
+
+        let inverse = self.projection.screen_space_matrix().inverse();
+
+        // two points along the ray that passes through the pixel
+        let near = inverse.project_point3(ndc.extend(-1.));
+        let far = inverse.project_point3(ndc.extend(1.));
+
+        let direction = far - near;
+
+        match direction.z.abs() > f32::EPSILON
+        {
+            true => near + direction * (-near.z / direction.z),
+            // the ray is parallel to the plane, there is no intersection
+            false => near
+        }
+    }
+
     /// aligns a camera space vector to global space
     #[inline]
     pub fn to_world_space(&self, pos: math::Vec3) -> math::Vec3
@@ -5,6 +5,9 @@ use parking_lot::{RwLock, RwLockReadGuard};
 use crate::*;
 use input::winit::{event_loop::ActiveEventLoop, window::{Window, WindowAttributes}};
 
+/// the format the passes draw the frame in, it's then copied to the surface whatever its format is
+pub(crate) const FRAME_FORMAT: wgpu::TextureFormat = wgpu::TextureFormat::Bgra8UnormSrgb;
+
 pub struct Renderer<'a>(&'a mut RendererData);
 
 impl<'a> From<&'a mut RendererData> for Renderer<'a> 
@@ -55,6 +58,34 @@ impl Renderer<'_>
         renderpasses.add_tilemap(&ctx, tilemap.into())
     }
 
+    /// moves the tilemaps, nothing happens if there are none
+    ///
+    /// # example
+    ///
+    /// ```
+    /// // keep the level attached to a moving platform
+    /// app.renderer.set_tilemap_transform(hierarchy.world(platform).unwrap());
+    /// ```
+    pub fn set_tilemap_transform(&mut self, transform: Transform2D)
+    {
+        let ctx = self.0.ctx.read();
+
+        if let Some(renderpasses) = &mut self.0.passes
+        {
+            renderpasses.set_tilemap_transform(&ctx, transform.to_mat4())
+        }
+    }
+
+    /// the tiles of the layers marked with [TilemapBuilder::solid_layer], positioned inside the tilemap
+    /// like they were added, empty if there are no tilemaps
+    pub fn solid_tiles(&self) -> Vec<Tile>
+    {
+        self.0.passes
+            .as_ref()
+            .map(RenderPassCommands::solid_tiles)
+            .unwrap_or_default()
+    }
+
     /// returns the screen size in the format you decide,
     /// ex:
     /// ```
@@ -69,9 +100,10 @@ impl Renderer<'_>
         (width.cast(), heigth.cast())
     }
     
-    pub fn set_background_color(&mut self, r: f64, g: f64, b: f64)
+    /// sets the color the screen is cleared with before drawing
+    pub fn set_background_color(&mut self, color: Color)
     {
-        self.0.set_clear_color(r, g, b)
+        self.0.set_clear_color(color)
     }
 }
 
@@ -79,7 +111,7 @@ impl Renderer<'_>
 pub struct RendererData
 {
     ctx: ContextHandle,
-    clear_color: wgpu::Color,
+    clear_color: Color,
 
     /// the window that the renderer draws on
     pub window: Option<Arc<Window>>,
@@ -97,15 +129,9 @@ pub struct RendererData
 // integration specific
 impl RendererData
 {
-    pub fn set_clear_color(&mut self, r: f64, g: f64, b: f64)
+    pub fn set_clear_color(&mut self, color: Color)
     {
-        self.clear_color = wgpu::Color
-        {
-            r,
-            g,
-            b,
-            a: 1.0,
-        }
+        self.clear_color = color
     }
 
     fn camera(&mut self) -> std::cell::RefMut<CameraData>
@@ -180,7 +206,7 @@ impl RendererData
                     resolve_target: None,
                     ops: wgpu::Operations
                     {
-                        load: wgpu::LoadOp::Clear(self.clear_color),
+                        load: wgpu::LoadOp::Clear(wgpu_color(self.clear_color, FRAME_FORMAT)),
                         store: wgpu::StoreOp::Store
                     }
                 })],
@@ -217,7 +243,7 @@ impl RendererData
     /// # Errors
     ///
     /// this function will return an error if the surface is not able to be retrieved.
-    pub fn render_plain_color(&mut self, r:f64,g:f64,b:f64) -> Result<(), wgpu::SurfaceError>
+    pub fn render_plain_color(&mut self, color: Color) -> Result<(), wgpu::SurfaceError>
     {
         let ctx_read = self.ctx.read();
 
@@ -238,7 +264,7 @@ impl RendererData
                 resolve_target: None,
                 ops: wgpu::Operations
                 {
-                    load: wgpu::LoadOp::Clear(wgpu::Color { r, g, b, a: 1. }),
+                    load: wgpu::LoadOp::Clear(wgpu_color(color, ctx_read.screen.config.format)),
                     store: wgpu::StoreOp::Store
                 }
             })],
@@ -301,7 +327,7 @@ impl RendererData
     ///
     /// panics if an appropriate adapter or device is not avaiable.
     #[must_use]
-    pub fn new(w_attributes: WindowAttributes, color: Option<(f64,f64,f64)>) -> Self
+    pub fn new(w_attributes: WindowAttributes, color: Option<Color>) -> Self
     {   
         use wgpu::*;
 
@@ -367,10 +393,10 @@ impl RendererData
             // scalefactor of the screen we are rendering inside of
             let scale = 1.;
 
-        let output = FrameOutput::new(&device,width,height);
-        
         let ctx_data = ContextHandleInner::new(instance, device, queue);
 
+        let output = FrameOutput::new(&ctx_data.device, width, height, ctx_data.screen.config.format);
+
         let ui = ui::UiData::new(&ctx_data, width,height,scale);
 
         let camera = Camera
@@ -383,20 +409,8 @@ impl RendererData
 
         let ctx = ContextHandle(RwLock::new(ctx_data).into());
 
-        let clear_color = color.map(|(r,g,b)| wgpu::Color
-        {
-            r,
-            g,
-            b,
-            a: 1.0
-        }
-        ).unwrap_or(wgpu::Color
-        {
-            r: 0.13,
-            g: 0.31,
-            b: 0.85,
-            a: 1.0
-        });
+        // the blue baguette always had, it used to be given as linear components
+        let clear_color = color.unwrap_or(crate::Color::from_linear(0.13, 0.31, 0.85, 1.));
 
         Self
         {
@@ -456,6 +470,14 @@ impl RendererData
         ////
 
         self.ctx.0.write().screen = Screen::new(surface, config);
+
+        // the frame is copied to the surface in its format
+        let ctx_read = self.ctx.read();
+        let (width, height) = ctx_read.screen.size();
+
+        self.output = FrameOutput::new(&ctx_read.device, width, height, ctx_read.screen.config.format);
+        drop(ctx_read);
+
         self.update_surface()
     }
 
@@ -477,7 +499,8 @@ struct FrameOutput
 }
 impl FrameOutput
 {
-    fn new(device: &wgpu::Device, width: u32, height: u32) -> Self 
+    /// `surface_format` is the format of the surface the frame is copied to
+    fn new(device: &wgpu::Device, width: u32, height: u32, surface_format: wgpu::TextureFormat) -> Self 
     {
         use wgpu::*;
 
@@ -528,7 +551,7 @@ impl FrameOutput
                 mip_level_count: 1,
                 sample_count: 1,
                 dimension: TextureDimension::D2,
-                format: TextureFormat::Bgra8UnormSrgb,
+                format: FRAME_FORMAT,
                 usage: TextureUsages::RENDER_ATTACHMENT | TextureUsages::TEXTURE_BINDING,
                 view_formats: &[],
             }
@@ -608,10 +631,14 @@ impl FrameOutput
                         FragmentState 
                         {
                             module,
-                            entry_point: "fragment",
+                            entry_point: match surface_format.is_srgb()
+                            {
+                                true => "fragment",
+                                false => "fragment_gamma"
+                            },
                             targets: &[Some(ColorTargetState
                             {
-                                format: TextureFormat::Bgra8UnormSrgb,
+                                format: surface_format,
                                 blend: Some(BlendState::REPLACE),
                                 write_mask: ColorWrites::ALL
                             })],
@@ -659,7 +686,7 @@ impl FrameOutput
                 mip_level_count: 1,
                 sample_count: 1,
                 dimension: wgpu::TextureDimension::D2,
-                format: wgpu::TextureFormat::Bgra8UnormSrgb,
+                format: FRAME_FORMAT,
                 usage: wgpu::TextureUsages::RENDER_ATTACHMENT | wgpu::TextureUsages::TEXTURE_BINDING,
                 view_formats: &[],
             }
@@ -841,3 +868,21 @@ pub(crate) fn queue(ctx: &ContextHandleInner) -> &wgpu::Queue
 {
     &ctx.queue
 }
+
+/// the components of the color for a target of this format,
+/// the gpu encodes the linear ones when writing to srgb formats and writes the others as they are
+pub(crate) fn color_components(color: Color, format: wgpu::TextureFormat) -> [f32; 4]
+{
+    match format.is_srgb()
+    {
+        true => color.to_linear(),
+        false => [color.r, color.g, color.b, color.a]
+    }
+}
+
+fn wgpu_color(color: Color, format: wgpu::TextureFormat) -> wgpu::Color
+{
+    let [r, g, b, a] = color_components(color, format);
+
+    wgpu::Color { r: r as f64, g: g as f64, b: b as f64, a: a as f64 }
+}
@@ -93,6 +93,22 @@ impl RenderPassCommands
         tilemap
     }
 
+    pub fn set_tilemap_transform(&mut self, ctx: &ContextHandleInner, transform: Mat4)
+    {
+        if let Some(tilemap_pass) = &mut self.tilemap_pass
+        {
+            tilemap_pass.set_transform(ctx, transform)
+        }
+    }
+
+    pub fn solid_tiles(&self) -> Vec<Tile>
+    {
+        self.tilemap_pass
+            .as_ref()
+            .map(|tilemap_pass| tilemap_pass.solid_tiles().copied().collect())
+            .unwrap_or_default()
+    }
+
     pub fn draw<'a>
     (
         &'a self, ctx: &ContextHandleInner,
@@ -148,7 +164,7 @@ impl RenderPassCommands
     {
         if let Some(sprite_pass) = &mut self.sprite_pass
         {
-            sprite_pass.prepare_instances();
+            sprite_pass.prepare_instances(FRAME_FORMAT);
         }     
     }
 
@@ -27,4 +27,16 @@ struct VertexOutput
 @fragment fn fragment(in: VertexOutput) -> @location(0) vec4<f32>
 {
     return textureSample(tex, sampletex, in.tex_coords,);
+}
+
+// the surface isn't srgb, so the gpu won't encode the colors when writing them
+@fragment fn fragment_gamma(in: VertexOutput) -> @location(0) vec4<f32>
+{
+    let color = textureSample(tex, sampletex, in.tex_coords,);
+
+    let cutoff = color.rgb < vec3<f32>(0.0031308);
+    let lower = color.rgb * 12.92;
+    let higher = 1.055 * pow(color.rgb, vec3<f32>(1.0 / 2.4)) - 0.055;
+
+    return vec4<f32>(select(higher, lower, cutoff), color.a);
 }
\ No newline at end of file
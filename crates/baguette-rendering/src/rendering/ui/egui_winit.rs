@@ -63,6 +63,10 @@ pub struct State {
     any_pointer_button_down: bool,
     current_cursor_icon: Option<egui::CursorIcon>,
 
+    /// The cursor shown when egui asks for [`egui::CursorIcon::Default`].
+    default_cursor: winit::window::Cursor,
+    default_cursor_visible: bool,
+
     /// If `true`, mouse inputs will be treated as touches.
     /// Useful for debugging touch support in egui.
     ///
@@ -101,6 +105,9 @@ impl State {
             any_pointer_button_down: false,
             current_cursor_icon: None,
 
+            default_cursor: Default::default(),
+            default_cursor_visible: true,
+
             simulate_touch_screen: false,
             pointer_touch_id: None,
 
@@ -696,7 +703,10 @@ impl State {
         if is_pointer_in_window {
             self.current_cursor_icon = Some(cursor_icon);
 
-            if let Some(winit_cursor_icon) = translate_cursor(cursor_icon) {
+            if cursor_icon == egui::CursorIcon::Default {
+                window.set_cursor_visible(self.default_cursor_visible);
+                window.set_cursor(self.default_cursor.clone());
+            } else if let Some(winit_cursor_icon) = translate_cursor(cursor_icon) {
                 window.set_cursor_visible(true);
                 window.set_cursor(winit_cursor_icon);
             } else {
@@ -708,6 +718,16 @@ impl State {
         }
     }
 
+    /// Replaces the cursor shown when egui doesn't need a specific one,
+    /// it will be applied with the next platform output.
+    pub fn set_default_cursor(&mut self, cursor: winit::window::Cursor, visible: bool) {
+        self.default_cursor = cursor;
+        self.default_cursor_visible = visible;
+
+        // force the cursor to be set again
+        self.current_cursor_icon = None;
+    }
+
     /// Update the given viewport info with the current state of the window.
     ///
     /// Call before [`State::take_egui_input`].
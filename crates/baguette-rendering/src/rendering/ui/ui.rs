@@ -124,6 +124,13 @@ impl UiData
         self.state.on_window_event(window, event)
     }
 
+    /// sets the cursor shown when the ui doesn't need a specific one,
+    /// like a text cursor when hovering a text field
+    pub fn set_default_cursor(&mut self, cursor: input::winit::window::Cursor, visible: bool)
+    {
+        self.state.set_default_cursor(cursor, visible)
+    }
+
     pub(crate) fn update_screen_size(&mut self, width: u32, height: u32)
     {
         self.screen.width = width;
@@ -6,6 +6,8 @@ pub use input;
 pub use audio;
 pub use rendering;
 pub use math;
+#[cfg(feature = "physics")]
+pub use physics;
 
 use app::*;
 use input::winit::*;
@@ -20,9 +22,13 @@ pub struct AppBuilder<T>
 {
     /// keeps track of how to create the application window
     w_attributes: window::WindowAttributes,
-    clear_color: Option<(f64, f64, f64)>,
+    clear_color: Option<math::Color>,
     /// whether the app window will be focused or not
     focus: bool,
+    /// whether the input will be recorded or replayed
+    input_replay: Option<InputReplay>,
+    /// where the sounds are played, the player is created with it when the app runs
+    audio_backend: audio::AudioBackend,
     fsm: T
 }
 
@@ -42,6 +48,8 @@ pub fn new() -> AppBuilder<UninitDynFsm>
         fsm: Default::default(),
         focus: true,
         clear_color: None,
+        input_replay: None,
+        audio_backend: Default::default(),
     }
 }
 
@@ -69,10 +77,12 @@ impl AppBuilder<UninitDynFsm>
     
     /// sets the `clear color` of the `background`,
     /// 
-    /// accepted values are between `0.` and `1.`
-    pub fn set_clear_color(mut self, r: f64, g: f64, b: f64) -> Self
+    /// ```
+    /// AppBuilder::new().set_clear_color(Color::hex("#1e1e2e").unwrap())
+    /// ```
+    pub fn set_clear_color(mut self, color: math::Color) -> Self
     {
-        self.clear_color = Some((r, g, b));
+        self.clear_color = Some(color);
         self
     }
 
@@ -80,12 +90,15 @@ impl AppBuilder<UninitDynFsm>
     pub fn run(self)
     {
         let eventloop = event_loop::EventLoop::new().unwrap();
+
+        let mut handler = AppHandler::new(self.w_attributes, self.clear_color, self.audio_backend, Fsm::Unactive(self.fsm));
+
+        if let Some(replay) = self.input_replay
+        {
+            handler = handler.with_input_replay(replay)
+        }
         
-        eventloop.run_app
-        (
-            &mut AppHandler::new(self.w_attributes, self.clear_color, Fsm::Unactive(self.fsm))
-        )
-        .unwrap();
+        eventloop.run_app(&mut handler).unwrap();
     }
 }
 
@@ -169,4 +182,32 @@ impl<T> AppBuilder<T>
         self.w_attributes = self.w_attributes.with_theme(Some(theme));
         self
     }
+
+    /// chooses where the sounds are played,
+    /// use [audio::AudioBackend::Null] to run without an output device, for example on a server
+    pub fn set_audio_backend(mut self, backend: audio::AudioBackend) -> Self
+    {
+        self.audio_backend = backend;
+        self
+    }
+
+    /// records the input of every frame and saves it to `path` when the app exits,
+    /// the file can be replayed with [AppBuilder::replay_input]
+    pub fn record_input(mut self, path: impl Into<std::path::PathBuf>) -> Self
+    {
+        self.input_replay = Some(InputReplay::Record(path.into()));
+        self
+    }
+
+    /// replaces the live input with a recording made with [AppBuilder::record_input],
+    /// the live input takes over once the recording ends.
+    ///
+    /// fails if the recording is missing or can't be decoded
+    pub fn replay_input(mut self, path: impl AsRef<std::path::Path>) -> Result<Self, input::ReplayError>
+    {
+        let recording = input::InputRecording::from_path(path)?;
+
+        self.input_replay = Some(InputReplay::Playback(recording));
+        Ok(self)
+    }
 }